@@ -0,0 +1,340 @@
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use semver::{Version, VersionReq};
+use serde::Deserialize;
+use tracing::warn;
+
+use crate::error::{AiDocsError, Result};
+
+pub const ADVISORIES_FILE: &str = "ADVISORIES.md";
+
+/// A local checkout of the RustSec advisory database; advisories live in
+/// `crates/{name}/RUSTSEC-*.md`.
+pub struct AdvisoryDb {
+    root: PathBuf,
+}
+
+/// One RustSec advisory for a crate.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Advisory {
+    pub id: String,
+    pub title: String,
+    pub description: String,
+    pub date: String,
+    pub url: Option<String>,
+    pub aliases: Vec<String>,
+    /// `unmaintained`, `unsound` or `notice`; `None` for vulnerabilities.
+    pub informational: Option<String>,
+    pub withdrawn: bool,
+    /// Affected function paths with the version requirements they are vulnerable in.
+    pub functions: BTreeMap<String, Vec<String>>,
+    pub patched: Vec<String>,
+    pub unaffected: Vec<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct AdvisoryFile {
+    advisory: AdvisoryFields,
+    #[serde(default)]
+    affected: AffectedFields,
+    #[serde(default)]
+    versions: VersionFields,
+}
+
+#[derive(Debug, Deserialize)]
+struct AdvisoryFields {
+    id: String,
+    #[serde(default)]
+    date: Option<toml::Value>,
+    #[serde(default)]
+    url: Option<String>,
+    #[serde(default)]
+    aliases: Vec<String>,
+    #[serde(default)]
+    informational: Option<String>,
+    #[serde(default)]
+    withdrawn: Option<toml::Value>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+struct AffectedFields {
+    #[serde(default)]
+    functions: BTreeMap<String, Vec<String>>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+struct VersionFields {
+    #[serde(default)]
+    patched: Vec<String>,
+    #[serde(default)]
+    unaffected: Vec<String>,
+}
+
+impl AdvisoryDb {
+    pub fn open(root: &Path) -> Result<Self> {
+        if !root.join("crates").is_dir() {
+            return Err(AiDocsError::AdvisoryDb(format!(
+                "{} has no crates/ directory; expected a clone of rustsec/advisory-db",
+                root.display()
+            )));
+        }
+        Ok(Self {
+            root: root.to_path_buf(),
+        })
+    }
+
+    pub fn root(&self) -> &Path {
+        &self.root
+    }
+
+    /// Advisories that affect `crate_name@version`, ordered by id. Withdrawn
+    /// advisories and unparsable files are skipped.
+    pub fn advisories_for(&self, crate_name: &str, version: &str) -> Vec<Advisory> {
+        let Ok(version) = Version::parse(version) else {
+            return Vec::new();
+        };
+        let Ok(entries) = fs::read_dir(self.root.join("crates").join(crate_name)) else {
+            return Vec::new();
+        };
+
+        let mut paths: Vec<PathBuf> = entries
+            .filter_map(|e| e.ok().map(|e| e.path()))
+            .filter(|p| p.extension().is_some_and(|ext| ext == "md"))
+            .collect();
+        paths.sort();
+
+        paths
+            .into_iter()
+            .filter_map(|path| {
+                let text = fs::read_to_string(&path).ok()?;
+                let advisory = parse_advisory(&text);
+                if advisory.is_none() {
+                    warn!("skipping unparsable advisory {}", path.display());
+                }
+                advisory
+            })
+            .filter(|a| !a.withdrawn && affects(a, &version))
+            .collect()
+    }
+}
+
+/// Parse an advisory file: a fenced TOML front matter block followed by
+/// a `# Title` heading and the Markdown description.
+pub fn parse_advisory(text: &str) -> Option<Advisory> {
+    let rest = text.trim_start().strip_prefix("```toml")?;
+    let (front_matter, body) = rest.split_once("\n```")?;
+    let file: AdvisoryFile = toml::from_str(front_matter).ok()?;
+
+    let body = body.trim();
+    let (title, description) = match body.strip_prefix("# ") {
+        Some(heading) => match heading.split_once('\n') {
+            Some((title, description)) => (title.trim(), description.trim()),
+            None => (heading.trim(), ""),
+        },
+        None => ("", body),
+    };
+
+    Some(Advisory {
+        id: file.advisory.id,
+        title: title.to_string(),
+        description: description.to_string(),
+        date: file
+            .advisory
+            .date
+            .as_ref()
+            .map(value_text)
+            .unwrap_or_default(),
+        url: file.advisory.url,
+        aliases: file.advisory.aliases,
+        informational: file.advisory.informational,
+        withdrawn: file.advisory.withdrawn.is_some(),
+        functions: file.affected.functions,
+        patched: file.versions.patched,
+        unaffected: file.versions.unaffected,
+    })
+}
+
+fn value_text(value: &toml::Value) -> String {
+    match value {
+        toml::Value::String(s) => s.clone(),
+        other => other.to_string(),
+    }
+}
+
+/// RustSec semantics: a version is affected unless it is patched or unaffected.
+pub fn affects(advisory: &Advisory, version: &Version) -> bool {
+    !advisory
+        .patched
+        .iter()
+        .chain(&advisory.unaffected)
+        .any(|req| matches_req(req, version))
+}
+
+fn matches_req(req: &str, version: &Version) -> bool {
+    VersionReq::parse(req).is_ok_and(|req| req.matches(version))
+}
+
+/// `ADVISORIES.md` for one crate version.
+pub fn render_advisories_markdown(
+    crate_name: &str,
+    version: &str,
+    advisories: &[Advisory],
+    db_root: &Path,
+) -> String {
+    let parsed = Version::parse(version).ok();
+    let mut out = format!("# {crate_name}@{version} — Security advisories\n\n");
+    out.push_str(&format!(
+        "Source: RustSec advisory database at `{}`. Do not suggest the affected APIs below; prefer a patched version.\n\n",
+        db_root.display()
+    ));
+
+    for advisory in advisories {
+        if advisory.title.is_empty() {
+            out.push_str(&format!("## {}\n\n", advisory.id));
+        } else {
+            out.push_str(&format!("## {}: {}\n\n", advisory.id, advisory.title));
+        }
+        let kind = match &advisory.informational {
+            Some(kind) => format!("informational ({kind})"),
+            None => "vulnerability".to_string(),
+        };
+        out.push_str(&format!("- Kind: {kind}\n"));
+        if !advisory.date.is_empty() {
+            out.push_str(&format!("- Date: {}\n", advisory.date));
+        }
+        if !advisory.aliases.is_empty() {
+            out.push_str(&format!("- Aliases: {}\n", code_list(&advisory.aliases)));
+        }
+        out.push_str(&format!(
+            "- Patched versions: {}\n",
+            if advisory.patched.is_empty() {
+                "none".to_string()
+            } else {
+                code_list(&advisory.patched)
+            }
+        ));
+        if !advisory.unaffected.is_empty() {
+            out.push_str(&format!(
+                "- Unaffected versions: {}\n",
+                code_list(&advisory.unaffected)
+            ));
+        }
+        let functions: Vec<String> = advisory
+            .functions
+            .iter()
+            .filter(|(_, reqs)| match &parsed {
+                Some(v) => reqs.is_empty() || reqs.iter().any(|r| matches_req(r, v)),
+                None => true,
+            })
+            .map(|(path, _)| path.clone())
+            .collect();
+        if !functions.is_empty() {
+            out.push_str(&format!(
+                "- Affected functions: {}\n",
+                code_list(&functions)
+            ));
+        }
+        if let Some(url) = &advisory.url {
+            out.push_str(&format!("- Details: {url}\n"));
+        }
+        if !advisory.description.is_empty() {
+            out.push('\n');
+            out.push_str(&advisory.description);
+            out.push('\n');
+        }
+        out.push('\n');
+    }
+    out
+}
+
+fn code_list(items: &[String]) -> String {
+    items
+        .iter()
+        .map(|i| format!("`{i}`"))
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{affects, parse_advisory, render_advisories_markdown, AdvisoryDb};
+    use semver::Version;
+    use std::fs;
+    use std::path::Path;
+
+    const ADVISORY: &str = r#"```toml
+[advisory]
+id = "RUSTSEC-2020-0071"
+package = "time"
+date = "2020-11-18"
+url = "https://github.com/time-rs/time/issues/293"
+categories = ["code-execution", "memory-corruption"]
+aliases = ["CVE-2020-26235", "GHSA-wcg3-cvx6-7396"]
+
+[affected.functions]
+"time::OffsetDateTime::now_local" = ["< 0.2.23"]
+"time::UtcOffset::current_local_offset" = ["< 0.2.23"]
+
+[versions]
+patched = [">= 0.2.23"]
+unaffected = ["= 0.1.0", "^0.1.4"]
+```
+
+# Potential segfault in the time crate
+
+Unix-like operating systems may segfault due to dereferencing a dangling pointer.
+"#;
+
+    #[test]
+    fn parses_front_matter_and_matches_versions() {
+        let advisory = parse_advisory(ADVISORY).expect("parse advisory");
+        assert_eq!(advisory.id, "RUSTSEC-2020-0071");
+        assert_eq!(advisory.title, "Potential segfault in the time crate");
+        assert!(advisory.description.starts_with("Unix-like"));
+        assert_eq!(advisory.date, "2020-11-18");
+        assert!(advisory.informational.is_none());
+        assert_eq!(advisory.functions.len(), 2);
+
+        assert!(affects(&advisory, &Version::parse("0.2.22").unwrap()));
+        assert!(affects(&advisory, &Version::parse("0.1.2").unwrap()));
+        assert!(!affects(&advisory, &Version::parse("0.1.45").unwrap()));
+        assert!(!affects(&advisory, &Version::parse("0.2.23").unwrap()));
+        assert!(!affects(&advisory, &Version::parse("0.3.36").unwrap()));
+    }
+
+    #[test]
+    fn reads_matching_advisories_from_db_checkout() {
+        let tmp = std::env::temp_dir().join(format!("ai-fdocs-advisory-db-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&tmp);
+        fs::create_dir_all(tmp.join("crates/time")).expect("mkdir");
+        fs::write(tmp.join("crates/time/RUSTSEC-2020-0071.md"), ADVISORY).expect("write");
+        fs::write(
+            tmp.join("crates/time/RUSTSEC-2099-0001.md"),
+            ADVISORY
+                .replace("RUSTSEC-2020-0071", "RUSTSEC-2099-0001")
+                .replace("[advisory]\n", "[advisory]\nwithdrawn = \"2021-01-01\"\n"),
+        )
+        .expect("write");
+
+        assert!(AdvisoryDb::open(&tmp.join("crates")).is_err());
+        let db = AdvisoryDb::open(&tmp).expect("open db");
+        let found = db.advisories_for("time", "0.2.22");
+        assert_eq!(found.len(), 1);
+        assert_eq!(found[0].id, "RUSTSEC-2020-0071");
+        assert!(db.advisories_for("time", "0.3.0").is_empty());
+        assert!(db.advisories_for("serde", "1.0.0").is_empty());
+
+        let markdown = render_advisories_markdown("time", "0.2.22", &found, Path::new("/db"));
+        assert!(markdown.starts_with("# time@0.2.22 — Security advisories\n"));
+        assert!(markdown.contains("## RUSTSEC-2020-0071: Potential segfault in the time crate\n"));
+        assert!(markdown.contains("- Kind: vulnerability\n"));
+        assert!(markdown.contains("- Patched versions: `>= 0.2.23`\n"));
+        assert!(markdown.contains(
+            "- Affected functions: `time::OffsetDateTime::now_local`, `time::UtcOffset::current_local_offset`\n"
+        ));
+
+        let _ = fs::remove_dir_all(&tmp);
+    }
+}
@@ -0,0 +1,351 @@
+use std::fs;
+use std::path::Path;
+
+use tracing::{info, warn};
+
+use crate::config::Config;
+use crate::error::Result;
+use crate::processor::truncate::truncate_markdown;
+use crate::storage::{self, BudgetMeta, SavedCrate};
+use crate::tokens::{estimator, TokenEstimator};
+
+pub const BUDGET_MARKER: &str = "[TRUNCATED by ai-fdocs to fit the total budget]";
+
+/// Untrimmed copies of budget-trimmed files, so a later sync can hand a crate a
+/// larger share without fetching again.
+pub const FULL_COPY_DIR: &str = ".aifd-full";
+
+/// `settings.total_budget_kb` or `settings.total_budget_tokens`.
+enum Limit {
+    Bytes(usize),
+    Tokens(usize, &'static dyn TokenEstimator),
+}
+
+impl Limit {
+    fn from_config(config: &Config) -> Option<Self> {
+        let settings = &config.settings;
+        if let Some(kb) = settings.total_budget_kb {
+            return Some(Self::Bytes(kb * 1024));
+        }
+        settings
+            .total_budget_tokens
+            .map(|tokens| Self::Tokens(tokens, estimator(settings.token_estimator)))
+    }
+
+    const fn total(&self) -> usize {
+        match self {
+            Self::Bytes(total) | Self::Tokens(total, _) => *total,
+        }
+    }
+
+    fn measure(&self, text: &str) -> usize {
+        match self {
+            Self::Bytes(_) => text.len(),
+            Self::Tokens(_, tokens) => tokens.estimate(text),
+        }
+    }
+
+    const fn unit(&self) -> &'static str {
+        match self {
+            Self::Bytes(_) => "bytes",
+            Self::Tokens(..) => "tokens",
+        }
+    }
+
+    fn describe(&self) -> String {
+        match self {
+            Self::Bytes(total) => format!("{}KB", total / 1024),
+            Self::Tokens(total, _) => format!("{total} tokens"),
+        }
+    }
+}
+
+/// A Markdown file that may be trimmed, with its untrimmed content.
+struct Document {
+    name: String,
+    content: String,
+    size: usize,
+}
+
+/// Split `budget` across `(demand, weight)` pairs: each gets a share in
+/// proportion to its weight, capped at its demand, and what capped entries
+/// leave over goes to the rest (weighted max-min fairness).
+pub fn allocate(budget: usize, demands: &[(usize, u32)]) -> Vec<usize> {
+    let mut shares = vec![0; demands.len()];
+    let mut open: Vec<usize> = (0..demands.len()).filter(|&i| demands[i].0 > 0).collect();
+    let mut left = budget;
+    while !open.is_empty() {
+        let weight: u128 = open.iter().map(|&i| u128::from(demands[i].1.max(1))).sum();
+        let fair = |i: usize| (left as u128 * u128::from(demands[i].1.max(1)) / weight) as usize;
+        let (full, partial): (Vec<usize>, Vec<usize>) =
+            open.iter().partition(|&&i| demands[i].0 <= fair(i));
+        if full.is_empty() {
+            for i in partial {
+                shares[i] = fair(i);
+            }
+            break;
+        }
+        for i in full {
+            shares[i] = demands[i].0;
+            left -= demands[i].0;
+        }
+        open = partial;
+    }
+    shares
+}
+
+/// Fit the Markdown of every synced crate into the total budget. Summaries are
+/// always kept and counted first; the rest is shared out by crate `priority`,
+/// then evenly across a crate's files, and files over their share are cut at a
+/// heading or paragraph boundary. Crates that fit get their full files back.
+pub fn apply_total_budget(
+    config: &Config,
+    rust_output_dir: &Path,
+    saved_crates: &[SavedCrate],
+) -> Result<()> {
+    let Some(limit) = Limit::from_config(config) else {
+        return Ok(());
+    };
+
+    let mut reserved = 0;
+    let mut crates = Vec::with_capacity(saved_crates.len());
+    for saved in saved_crates {
+        let crate_dir = rust_output_dir.join(format!("{}@{}", saved.name, saved.version));
+        if let Ok(summary) = fs::read_to_string(crate_dir.join("_SUMMARY.md")) {
+            reserved += limit.measure(&summary);
+        }
+        let documents = load_documents(&crate_dir, saved, &limit);
+        let priority = config
+            .crates
+            .get(&saved.name)
+            .map_or(1, |c| c.budget_priority());
+        crates.push((saved, crate_dir, documents, priority));
+    }
+
+    let available = limit.total().saturating_sub(reserved);
+    if available == 0 {
+        warn!(
+            "⚠ total budget {} is used up by _SUMMARY.md files alone",
+            limit.describe()
+        );
+    }
+    let demands: Vec<(usize, u32)> = crates
+        .iter()
+        .map(|(_, _, docs, priority)| (docs.iter().map(|d| d.size).sum(), *priority))
+        .collect();
+    let shares = allocate(available, &demands);
+
+    for ((saved, crate_dir, documents, priority), (share, (demand, _))) in
+        crates.into_iter().zip(shares.into_iter().zip(demands))
+    {
+        let file_shares = allocate(
+            share,
+            &documents.iter().map(|d| (d.size, 1)).collect::<Vec<_>>(),
+        );
+        let mut trimmed_files = Vec::new();
+        for (doc, file_share) in documents.iter().zip(file_shares) {
+            if fit_document(&crate_dir, doc, file_share, &limit)? {
+                trimmed_files.push(doc.name.clone());
+            }
+        }
+
+        let budget = if trimmed_files.is_empty() {
+            let _ = fs::remove_dir_all(crate_dir.join(FULL_COPY_DIR));
+            None
+        } else {
+            info!(
+                "  ✂ {}@{}: trimmed {} file(s) to fit the total budget",
+                saved.name,
+                saved.version,
+                trimmed_files.len()
+            );
+            Some(BudgetMeta {
+                limit: limit.describe(),
+                priority,
+                unit: limit.unit().to_string(),
+                allocated: share,
+                demand,
+                trimmed_files,
+            })
+        };
+        storage::update_budget_notice(
+            rust_output_dir,
+            saved,
+            budget,
+            config.settings.token_estimator,
+        )?;
+    }
+    Ok(())
+}
+
+/// Markdown files of a crate other than `_SUMMARY.md`, restored from
+/// `.aifd-full/` where an earlier budget pass trimmed them.
+fn load_documents(crate_dir: &Path, saved: &SavedCrate, limit: &Limit) -> Vec<Document> {
+    let mut documents = Vec::new();
+    for name in &saved.files {
+        let is_markdown = Path::new(name)
+            .extension()
+            .is_some_and(|ext| ext.eq_ignore_ascii_case("md"));
+        if !is_markdown || name == "_SUMMARY.md" {
+            continue;
+        }
+        let Ok(current) = fs::read_to_string(crate_dir.join(name)) else {
+            continue;
+        };
+        let content = if current.contains(BUDGET_MARKER) {
+            fs::read_to_string(crate_dir.join(FULL_COPY_DIR).join(name)).unwrap_or(current)
+        } else {
+            current
+        };
+        documents.push(Document {
+            name: name.clone(),
+            size: limit.measure(&content),
+            content,
+        });
+    }
+    documents
+}
+
+/// Write `doc` cut to `share`, or in full when it fits. Returns whether it was cut.
+fn fit_document(crate_dir: &Path, doc: &Document, share: usize, limit: &Limit) -> Result<bool> {
+    let path = crate_dir.join(&doc.name);
+    let full_copy = crate_dir.join(FULL_COPY_DIR).join(&doc.name);
+    if doc.size <= share {
+        if fs::read_to_string(&path).ok().as_deref() != Some(doc.content.as_str()) {
+            fs::write(&path, &doc.content)?;
+        }
+        if full_copy.exists() {
+            fs::remove_file(&full_copy)?;
+        }
+        return Ok(false);
+    }
+
+    let max_bytes = match limit {
+        Limit::Bytes(_) => share,
+        Limit::Tokens(..) => {
+            (doc.content.len() as u128 * share as u128 / doc.size as u128) as usize
+        }
+    };
+    fs::create_dir_all(crate_dir.join(FULL_COPY_DIR))?;
+    fs::write(&full_copy, &doc.content)?;
+    fs::write(
+        &path,
+        truncate_markdown(&doc.content, max_bytes, BUDGET_MARKER),
+    )?;
+    Ok(true)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+    use std::fs;
+
+    use super::{allocate, apply_total_budget, BUDGET_MARKER, FULL_COPY_DIR};
+    use crate::config::{Config, CrateDoc, Settings};
+    use crate::storage::SavedCrate;
+
+    #[test]
+    fn allocates_by_priority_and_redistributes_unused_share() {
+        assert_eq!(
+            allocate(900, &[(100, 1), (2000, 1), (2000, 2)]),
+            vec![100, 266, 533]
+        );
+        assert_eq!(allocate(900, &[(100, 1), (200, 3)]), vec![100, 200]);
+        assert_eq!(allocate(0, &[(100, 1)]), vec![0]);
+        assert_eq!(allocate(50, &[(0, 1), (100, 1)]), vec![0, 50]);
+    }
+
+    fn write_crate(root: &std::path::Path, name: &str, api: &str) -> SavedCrate {
+        let dir = root.join(format!("{name}@1.0.0"));
+        fs::create_dir_all(&dir).expect("crate dir");
+        fs::write(dir.join("_SUMMARY.md"), format!("# {name}\n\n## Files\n")).expect("summary");
+        fs::write(dir.join("API.md"), api).expect("api");
+        fs::write(
+            dir.join(".aifd-meta.toml"),
+            format!(
+                "version = \"1.0.0\"\nartifact_bytes = {0}\n\n[[files]]\npath = \"API.md\"\nbytes = {0}\ntokens = 0\n",
+                api.len()
+            ),
+        )
+        .expect("meta");
+        SavedCrate {
+            name: name.to_string(),
+            version: "1.0.0".to_string(),
+            git_ref: "v1.0.0".to_string(),
+            is_fallback: false,
+            files: vec!["API.md".to_string()],
+            ai_notes: String::new(),
+        }
+    }
+
+    #[test]
+    fn trims_low_priority_crates_and_restores_when_budget_grows() {
+        let tmp = std::env::temp_dir().join(format!("ai-fdocs-budget-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&tmp);
+        let mut api = String::new();
+        for i in 0..40 {
+            api.push_str(&format!("## Item{i}\n\n{}\n\n", "Docs text. ".repeat(20)));
+        }
+        let saved = vec![
+            write_crate(&tmp, "tokio", &api),
+            write_crate(&tmp, "serde", &api),
+        ];
+        let mut config = Config {
+            settings: Settings {
+                total_budget_kb: Some(12),
+                ..Settings::default()
+            },
+            crates: HashMap::from([
+                (
+                    "tokio".to_string(),
+                    CrateDoc {
+                        priority: Some(3),
+                        ..CrateDoc::default()
+                    },
+                ),
+                ("serde".to_string(), CrateDoc::default()),
+            ]),
+        };
+
+        apply_total_budget(&config, &tmp, &saved).expect("apply budget");
+        let tokio = fs::read_to_string(tmp.join("tokio@1.0.0/API.md")).expect("tokio api");
+        let serde = fs::read_to_string(tmp.join("serde@1.0.0/API.md")).expect("serde api");
+        assert!(tokio.contains(BUDGET_MARKER) && serde.contains(BUDGET_MARKER));
+        assert!(tokio.len() > 2 * serde.len());
+        assert!(tokio.len() + serde.len() <= 12 * 1024);
+        assert_eq!(
+            fs::read_to_string(tmp.join("serde@1.0.0").join(FULL_COPY_DIR).join("API.md"))
+                .expect("full copy"),
+            api
+        );
+        let summary =
+            fs::read_to_string(tmp.join("serde@1.0.0/_SUMMARY.md")).expect("serde summary");
+        assert!(summary.contains("## Trimmed to fit the context budget\n"));
+        assert!(summary.contains("(priority 1). Trimmed: [API.md](API.md)."));
+        let meta = crate::storage::read_meta(&tmp, "tokio", "1.0.0").expect("meta");
+        assert_eq!(meta.budget.map(|b| b.priority), Some(3));
+        assert_eq!(meta.files[0].bytes, tokio.len());
+        assert!(meta.files[0].tokens > 0);
+        assert_eq!(meta.artifact_bytes, Some(tokio.len()));
+        let trimmed_sha = meta.artifact_sha256;
+        assert!(trimmed_sha.is_some());
+
+        config.settings.total_budget_kb = Some(1024);
+        apply_total_budget(&config, &tmp, &saved).expect("apply larger budget");
+        assert_eq!(
+            fs::read_to_string(tmp.join("serde@1.0.0/API.md")).expect("restored"),
+            api
+        );
+        assert!(!tmp.join("serde@1.0.0").join(FULL_COPY_DIR).exists());
+        let summary =
+            fs::read_to_string(tmp.join("serde@1.0.0/_SUMMARY.md")).expect("serde summary");
+        assert!(!summary.contains("context budget"));
+        let meta = crate::storage::read_meta(&tmp, "serde", "1.0.0").expect("meta");
+        assert!(meta.budget.is_none());
+        assert_eq!(meta.artifact_bytes, Some(api.len()));
+        let tokio = crate::storage::read_meta(&tmp, "tokio", "1.0.0").expect("meta");
+        assert_ne!(tokio.artifact_sha256, trimmed_sha);
+
+        let _ = fs::remove_dir_all(&tmp);
+    }
+}
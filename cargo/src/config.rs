@@ -54,6 +54,195 @@ impl<'de> Deserialize<'de> for SyncMode {
     }
 }
 
+/// Where latest-docs and hybrid modes take the crate API reference from.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ApiSource {
+    /// Scrape rendered rustdoc HTML pages from docs.rs.
+    DocsRsHtml,
+    /// Render `API.md` from rustdoc JSON (docs.rs build or a local file).
+    RustdocJson,
+    /// Run rustdoc on the crate source in the cargo registry or `vendor/`.
+    LocalRustdoc,
+}
+
+impl ApiSource {
+    pub const fn as_str(self) -> &'static str {
+        match self {
+            Self::DocsRsHtml => "docsrs_html",
+            Self::RustdocJson => "rustdoc_json",
+            Self::LocalRustdoc => "local_rustdoc",
+        }
+    }
+}
+
+impl<'de> Deserialize<'de> for ApiSource {
+    fn deserialize<D>(deserializer: D) -> std::result::Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        let value = String::deserialize(deserializer)?;
+        match value.as_str() {
+            "docsrs_html" | "docsrs-html" => Ok(Self::DocsRsHtml),
+            "rustdoc_json" | "rustdoc-json" => Ok(Self::RustdocJson),
+            "local_rustdoc" | "local-rustdoc" => Ok(Self::LocalRustdoc),
+            _ => Err(de::Error::custom(format!(
+                "settings.api_source must be \"docsrs_html\", \"rustdoc_json\", or \"local_rustdoc\", got: {value}"
+            ))),
+        }
+    }
+}
+
+/// What sync does with Markdown files larger than `max_file_size_kb`.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum ChunkStrategy {
+    /// Cut the file at a heading or paragraph boundary.
+    #[default]
+    Truncate,
+    /// Split the file into `*.part-NN.md` chunks at heading boundaries.
+    Headings,
+}
+
+impl ChunkStrategy {
+    pub const fn as_str(self) -> &'static str {
+        match self {
+            Self::Truncate => "truncate",
+            Self::Headings => "headings",
+        }
+    }
+}
+
+impl<'de> Deserialize<'de> for ChunkStrategy {
+    fn deserialize<D>(deserializer: D) -> std::result::Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        let value = String::deserialize(deserializer)?;
+        match value.as_str() {
+            "truncate" => Ok(Self::Truncate),
+            "headings" => Ok(Self::Headings),
+            _ => Err(de::Error::custom(format!(
+                "settings.chunk_strategy must be \"truncate\" or \"headings\", got: {value}"
+            ))),
+        }
+    }
+}
+
+/// How token counts are estimated for meta, `_CHUNKS.json` and `cost`.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum TokenEstimatorKind {
+    /// Word pieces, punctuation and non-Latin characters counted separately.
+    #[default]
+    Heuristic,
+    /// Four characters per token.
+    Chars,
+}
+
+impl TokenEstimatorKind {
+    pub const fn as_str(self) -> &'static str {
+        match self {
+            Self::Heuristic => "heuristic",
+            Self::Chars => "chars",
+        }
+    }
+}
+
+impl<'de> Deserialize<'de> for TokenEstimatorKind {
+    fn deserialize<D>(deserializer: D) -> std::result::Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        let value = String::deserialize(deserializer)?;
+        match value.as_str() {
+            "heuristic" => Ok(Self::Heuristic),
+            "chars" => Ok(Self::Chars),
+            _ => Err(de::Error::custom(format!(
+                "settings.token_estimator must be \"heuristic\" or \"chars\", got: {value}"
+            ))),
+        }
+    }
+}
+
+/// What the Markdown cleanup pass strips from fetched files (`[settings.markdown_cleanup]`).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(default)]
+pub struct MarkdownCleanup {
+    /// Run the pass at all.
+    pub enabled: bool,
+    /// CI, crates.io, docs.rs and other status badges.
+    pub badges: bool,
+    /// Lines holding only images (logos, screenshots) and their HTML wrappers.
+    /// Off by default: diagrams can carry content.
+    pub images: bool,
+    pub html_comments: bool,
+    /// Sponsor, backer and contributor sections. Off by default: the heading
+    /// match can take a real section with it.
+    pub sponsor_sections: bool,
+    /// Unused link reference definitions and links without text.
+    pub link_references: bool,
+}
+
+impl Default for MarkdownCleanup {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            badges: true,
+            images: false,
+            html_comments: true,
+            sponsor_sections: false,
+            link_references: true,
+        }
+    }
+}
+
+/// Which changelog entries a crate's saved `CHANGELOG.md` keeps.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub enum ChangelogWindow {
+    /// The synced version plus the previous minor series.
+    #[default]
+    PreviousMinor,
+    /// The synced version plus this many earlier versions.
+    Previous(usize),
+    /// Every version from this one up.
+    Since(String),
+    /// The `Cargo.lock` version and every newer one (mainly for latest-docs mode,
+    /// where it also fetches the repository changelog next to the docs.rs API).
+    NewerThanLocked,
+}
+
+impl ChangelogWindow {
+    pub fn as_config_str(&self) -> String {
+        match self {
+            Self::PreviousMinor => "minor".to_string(),
+            Self::Previous(n) => format!("previous:{n}"),
+            Self::Since(version) => format!("since:{version}"),
+            Self::NewerThanLocked => "newer".to_string(),
+        }
+    }
+}
+
+impl<'de> Deserialize<'de> for ChangelogWindow {
+    fn deserialize<D>(deserializer: D) -> std::result::Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        let value = String::deserialize(deserializer)?;
+        let window = match value.split_once(':') {
+            None if value == "minor" => Some(Self::PreviousMinor),
+            None if value == "newer" => Some(Self::NewerThanLocked),
+            Some(("previous", n)) => n.trim().parse().ok().map(Self::Previous),
+            Some(("since", version)) => crate::version::parse(version)
+                .is_some()
+                .then(|| Self::Since(version.trim().to_string())),
+            _ => None,
+        };
+        window.ok_or_else(|| {
+            de::Error::custom(format!(
+                "changelog_window must be \"minor\", \"newer\", \"previous:<N>\" or \"since:<version>\", got: {value}"
+            ))
+        })
+    }
+}
+
 impl<'de> Deserialize<'de> for DocsSource {
     fn deserialize<D>(deserializer: D) -> std::result::Result<Self, D::Error>
     where
@@ -77,6 +266,10 @@ const fn default_sync_mode() -> SyncMode {
     SyncMode::Lockfile
 }
 
+const fn default_api_source() -> ApiSource {
+    ApiSource::DocsRsHtml
+}
+
 #[derive(Debug, Deserialize)]
 pub struct Settings {
     #[serde(default = "default_output_dir")]
@@ -85,6 +278,33 @@ pub struct Settings {
     #[serde(default = "default_max_file_size_kb")]
     pub max_file_size_kb: usize,
 
+    /// Truncate oversized Markdown files or split them into chunks.
+    #[serde(default)]
+    pub chunk_strategy: ChunkStrategy,
+
+    /// Estimated-token budget per chunk with `chunk_strategy = "headings"`, on top
+    /// of `max_file_size_kb`.
+    #[serde(default)]
+    pub chunk_max_tokens: Option<usize>,
+
+    /// Token estimator used for meta, chunk budgets and `cost`.
+    #[serde(default)]
+    pub token_estimator: TokenEstimatorKind,
+
+    /// Size budget for the Markdown of all synced crates together, shared out by
+    /// crate `priority`.
+    #[serde(default)]
+    pub total_budget_kb: Option<usize>,
+
+    /// Like `total_budget_kb`, in estimated tokens.
+    #[serde(default)]
+    pub total_budget_tokens: Option<usize>,
+
+    /// Badges, HTML comments and unused link references stripped from fetched
+    /// Markdown; images and sponsor sections on request.
+    #[serde(default)]
+    pub markdown_cleanup: MarkdownCleanup,
+
     #[serde(default = "default_true")]
     pub prune: bool,
 
@@ -102,9 +322,92 @@ pub struct Settings {
 
     #[serde(default = "default_true")]
     pub docsrs_single_page: bool,
+
+    /// Maximum number of rustdoc pages fetched per crate when `docsrs_single_page = false`.
+    #[serde(default = "default_docsrs_max_pages")]
+    pub docsrs_max_pages: usize,
+
+    /// Maximum link depth from the rustdoc root when `docsrs_single_page = false`.
+    #[serde(default = "default_docsrs_max_depth")]
+    pub docsrs_max_depth: usize,
+
+    /// API reference source for latest-docs and hybrid modes.
+    #[serde(default = "default_api_source")]
+    pub api_source: ApiSource,
+
+    /// Ask the crates.io API for license, MSRV, yanked and deprecated facts at
+    /// sync time (throttled to one request per second).
+    #[serde(default = "default_true")]
+    pub crates_io_metadata: bool,
+
+    /// Load `examples/*.rs` from the crate repository for `API.md`; by default
+    /// only when `GITHUB_TOKEN` is set, as it costs several GitHub requests per crate.
+    #[serde(default)]
+    pub repo_examples: Option<bool>,
+
+    /// Sparse registry index (or a mirror of it) used to resolve latest versions.
+    #[serde(default = "default_sparse_index")]
+    pub sparse_index: String,
+
+    /// Let latest-version resolution pick prereleases (`2.0.0-rc.1`).
+    #[serde(default)]
+    pub latest_prereleases: bool,
+
+    /// Lockfile/hybrid mode: ask the sparse index whether newer releases exist
+    /// (`status` reports `UpdateAvailable`, `_SUMMARY.md` gets a notice).
+    #[serde(default)]
+    pub check_updates: bool,
+
+    /// Hybrid mode: write `API_DIFF.md` for locked crates behind the latest
+    /// release (a sparse-index request per crate, two rustdoc JSON downloads
+    /// per lagging crate).
+    #[serde(default)]
+    pub api_diff: bool,
+
+    /// `check` fails when a locked version is yanked on crates.io.
+    #[serde(default = "default_true")]
+    pub check_fail_on_yanked: bool,
+
+    /// `check` fails when a crate is deprecated on crates.io or its GitHub repo is archived.
+    #[serde(default)]
+    pub check_fail_on_deprecated: bool,
+
+    /// Ask the GitHub API at sync time whether the crate repository is archived;
+    /// by default only when `GITHUB_TOKEN` is set.
+    #[serde(default)]
+    pub check_archived: Option<bool>,
+
+    /// Local clone of the RustSec advisory database (`rustsec/advisory-db`).
+    #[serde(default)]
+    pub advisory_db: Option<PathBuf>,
+
+    /// `check` fails when a RustSec vulnerability affects a synced version.
+    #[serde(default = "default_true")]
+    pub check_fail_on_advisory: bool,
 }
 
-#[derive(Debug, Deserialize, Clone)]
+/// Global settings that change the files saved for every crate; part of each
+/// crate's `config_hash`, so changing one re-syncs cached crates.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ContentSettings {
+    pub markdown_cleanup: MarkdownCleanup,
+    pub chunk_strategy: ChunkStrategy,
+    pub chunk_max_tokens: Option<usize>,
+    pub api_source: ApiSource,
+}
+
+impl Default for ContentSettings {
+    fn default() -> Self {
+        Self {
+            markdown_cleanup: MarkdownCleanup::default(),
+            chunk_strategy: ChunkStrategy::default(),
+            chunk_max_tokens: None,
+            api_source: default_api_source(),
+        }
+    }
+}
+
+#[derive(Debug, Deserialize, Clone, Default)]
 pub struct CrateDoc {
     /// New format: explicit repository in crate section.
     pub repo: Option<String>,
@@ -118,6 +421,39 @@ pub struct CrateDoc {
 
     #[serde(default)]
     pub ai_notes: String,
+
+    /// Module path patterns (e.g. `sync`, `sync::*`) to crawl in multi-page docs.rs mode.
+    #[serde(default)]
+    pub docsrs_include_modules: Vec<String>,
+
+    /// Module path patterns to skip in multi-page docs.rs mode; wins over includes.
+    #[serde(default)]
+    pub docsrs_exclude_modules: Vec<String>,
+
+    /// Locally built rustdoc JSON (`cargo +nightly rustdoc -- --output-format json`)
+    /// used instead of the docs.rs build when `api_source = "rustdoc_json"`.
+    pub rustdoc_json: Option<PathBuf>,
+
+    /// Crate source directory documented when `api_source = "local_rustdoc"`;
+    /// defaults to `vendor/` or the cargo registry cache.
+    pub source_dir: Option<PathBuf>,
+
+    /// Changelog entries kept in the saved Markdown; `CHANGELOG.json` keeps all
+    /// that fit in `max_file_size_kb`.
+    #[serde(default)]
+    pub changelog_window: ChangelogWindow,
+
+    /// Weight of the crate in the total budget (default 1); higher keeps more.
+    pub priority: Option<u32>,
+
+    /// Markdown sections (heading text, `*` wildcards) to keep in fetched files
+    /// that have any of them; everything else outside those sections is dropped.
+    #[serde(default)]
+    pub include_sections: Vec<String>,
+
+    /// Markdown sections dropped from fetched files; wins over includes.
+    #[serde(default)]
+    pub exclude_sections: Vec<String>,
 }
 
 #[derive(Debug, Deserialize, Clone)]
@@ -145,6 +481,10 @@ impl CrateDoc {
         })
     }
 
+    pub fn budget_priority(&self) -> u32 {
+        self.priority.unwrap_or(1)
+    }
+
     pub fn effective_files(&self) -> Option<Vec<String>> {
         if let Some(files) = &self.files {
             return Some(files.clone());
@@ -158,7 +498,7 @@ impl CrateDoc {
         })
     }
 
-    pub fn config_hash(&self) -> String {
+    pub fn config_hash(&self, content: &ContentSettings) -> String {
         use sha2::{Digest, Sha256};
         let mut hasher = Sha256::new();
 
@@ -180,6 +520,52 @@ impl CrateDoc {
         }
         hasher.update(b"notes:");
         hasher.update(self.ai_notes.as_bytes());
+        if !self.docsrs_include_modules.is_empty() {
+            hasher.update(b"docsrs_include:");
+            hasher.update(self.docsrs_include_modules.join(",").as_bytes());
+        }
+        if !self.docsrs_exclude_modules.is_empty() {
+            hasher.update(b"docsrs_exclude:");
+            hasher.update(self.docsrs_exclude_modules.join(",").as_bytes());
+        }
+        if let Some(path) = &self.rustdoc_json {
+            hasher.update(b"rustdoc_json:");
+            hasher.update(path.to_string_lossy().as_bytes());
+        }
+        if let Some(dir) = &self.source_dir {
+            hasher.update(b"source_dir:");
+            hasher.update(dir.to_string_lossy().as_bytes());
+        }
+        if self.changelog_window != ChangelogWindow::default() {
+            hasher.update(b"changelog_window:");
+            hasher.update(self.changelog_window.as_config_str().as_bytes());
+        }
+        if !self.include_sections.is_empty() {
+            hasher.update(b"include_sections:");
+            hasher.update(self.include_sections.join(",").as_bytes());
+        }
+        if !self.exclude_sections.is_empty() {
+            hasher.update(b"exclude_sections:");
+            hasher.update(self.exclude_sections.join(",").as_bytes());
+        }
+
+        // Global settings, only when changed so default setups keep their hashes
+        if content.markdown_cleanup != MarkdownCleanup::default() {
+            hasher.update(b"markdown_cleanup:");
+            hasher.update(format!("{:?}", content.markdown_cleanup).as_bytes());
+        }
+        if content.chunk_strategy != ChunkStrategy::default() {
+            hasher.update(b"chunk_strategy:");
+            hasher.update(content.chunk_strategy.as_str().as_bytes());
+        }
+        if let Some(max_tokens) = content.chunk_max_tokens {
+            hasher.update(b"chunk_max_tokens:");
+            hasher.update(max_tokens.to_string().as_bytes());
+        }
+        if content.api_source != default_api_source() {
+            hasher.update(b"api_source:");
+            hasher.update(content.api_source.as_str().as_bytes());
+        }
 
         // Legacy sources fallback
         if let Some(sources) = &self.sources {
@@ -201,6 +587,10 @@ const fn default_max_file_size_kb() -> usize {
     200
 }
 
+fn default_sparse_index() -> String {
+    crate::fetcher::sparse_index::DEFAULT_SPARSE_INDEX.to_string()
+}
+
 const fn default_true() -> bool {
     true
 }
@@ -213,17 +603,45 @@ const fn default_latest_ttl_hours() -> usize {
     24
 }
 
+const fn default_docsrs_max_pages() -> usize {
+    25
+}
+
+const fn default_docsrs_max_depth() -> usize {
+    2
+}
+
 impl Default for Settings {
     fn default() -> Self {
         Self {
             output_dir: default_output_dir(),
             max_file_size_kb: default_max_file_size_kb(),
+            chunk_strategy: ChunkStrategy::default(),
+            chunk_max_tokens: None,
+            token_estimator: TokenEstimatorKind::default(),
+            total_budget_kb: None,
+            total_budget_tokens: None,
+            markdown_cleanup: MarkdownCleanup::default(),
             prune: default_true(),
             sync_concurrency: default_sync_concurrency(),
             docs_source: default_docs_source(),
             sync_mode: default_sync_mode(),
             latest_ttl_hours: default_latest_ttl_hours(),
             docsrs_single_page: default_true(),
+            docsrs_max_pages: default_docsrs_max_pages(),
+            docsrs_max_depth: default_docsrs_max_depth(),
+            api_source: default_api_source(),
+            crates_io_metadata: default_true(),
+            repo_examples: None,
+            sparse_index: default_sparse_index(),
+            latest_prereleases: false,
+            check_updates: false,
+            api_diff: false,
+            check_fail_on_yanked: default_true(),
+            check_fail_on_deprecated: false,
+            check_archived: None,
+            advisory_db: None,
+            check_fail_on_advisory: default_true(),
         }
     }
 }
@@ -259,19 +677,67 @@ impl Config {
             ));
         }
 
+        if self.settings.chunk_max_tokens == Some(0) {
+            return Err(AiDocsError::InvalidConfig(
+                "settings.chunk_max_tokens must be greater than 0".to_string(),
+            ));
+        }
+
+        if self.settings.total_budget_kb == Some(0) || self.settings.total_budget_tokens == Some(0)
+        {
+            return Err(AiDocsError::InvalidConfig(
+                "settings.total_budget_kb and settings.total_budget_tokens must be greater than 0"
+                    .to_string(),
+            ));
+        }
+
+        if self.settings.total_budget_kb.is_some() && self.settings.total_budget_tokens.is_some() {
+            return Err(AiDocsError::InvalidConfig(
+                "set only one of settings.total_budget_kb and settings.total_budget_tokens"
+                    .to_string(),
+            ));
+        }
+
+        for (crate_name, crate_cfg) in &self.crates {
+            if crate_cfg.priority == Some(0) {
+                return Err(AiDocsError::InvalidConfig(format!(
+                    "crates.{crate_name}.priority must be greater than 0"
+                )));
+            }
+            let mut sections = crate_cfg
+                .include_sections
+                .iter()
+                .chain(&crate_cfg.exclude_sections);
+            if sections.any(|s| s.trim().is_empty()) {
+                return Err(AiDocsError::InvalidConfig(format!(
+                    "crates.{crate_name}.include_sections and exclude_sections must not contain empty headings"
+                )));
+            }
+        }
+
         if self.settings.latest_ttl_hours == 0 {
             return Err(AiDocsError::InvalidConfig(
                 "settings.latest_ttl_hours must be greater than 0".to_string(),
             ));
         }
 
-        if !self.settings.docsrs_single_page {
+        if self.settings.docsrs_max_pages == 0 {
             return Err(AiDocsError::InvalidConfig(
-                "settings.docsrs_single_page=false is not supported yet; use true".to_string(),
+                "settings.docsrs_max_pages must be greater than 0".to_string(),
             ));
         }
 
-        let require_github_repo = !matches!(self.settings.sync_mode, SyncMode::LatestDocs);
+        let index = self.settings.sparse_index.trim_start_matches("sparse+");
+        if !(index.starts_with("https://") || index.starts_with("http://")) {
+            return Err(AiDocsError::InvalidConfig(format!(
+                "settings.sparse_index must be an http(s) URL, got: {}",
+                self.settings.sparse_index
+            )));
+        }
+
+        // Local rustdoc builds cover crates without a public repo (private registries).
+        let require_github_repo = !matches!(self.settings.sync_mode, SyncMode::LatestDocs)
+            && self.settings.api_source != ApiSource::LocalRustdoc;
         if require_github_repo {
             for (crate_name, crate_cfg) in &self.crates {
                 if crate_cfg.github_repo().is_none() {
@@ -292,7 +758,7 @@ mod tests {
     use std::path::Path;
     use std::time::{SystemTime, UNIX_EPOCH};
 
-    use super::{Config, SyncMode};
+    use super::{ApiSource, ChangelogWindow, Config, ContentSettings, CrateDoc, SyncMode};
 
     #[test]
     fn example_config_parses_with_config_load() {
@@ -332,6 +798,16 @@ repo = "serde-rs/serde"
         assert_eq!(config.settings.sync_mode, SyncMode::Lockfile);
         assert_eq!(config.settings.latest_ttl_hours, 24);
         assert!(config.settings.docsrs_single_page);
+        assert_eq!(config.settings.api_source, ApiSource::DocsRsHtml);
+        assert!(config.settings.check_fail_on_yanked);
+        assert_eq!(config.settings.sparse_index, "https://index.crates.io");
+        assert!(!config.settings.latest_prereleases);
+        assert!(!config.settings.check_updates);
+        assert!(!config.settings.api_diff);
+        assert!(!config.settings.check_fail_on_deprecated);
+        assert!(config.settings.crates_io_metadata);
+        assert_eq!(config.settings.repo_examples, None);
+        assert_eq!(config.settings.check_archived, None);
     }
 
     #[test]
@@ -614,18 +1090,309 @@ repo = "serde-rs/serde"
     }
 
     #[test]
-    fn config_with_docsrs_single_page_false_fails_validation() {
+    fn config_with_docsrs_single_page_false_enables_crawl_settings() {
         let suffix = SystemTime::now()
             .duration_since(UNIX_EPOCH)
             .expect("system time should be valid")
             .as_nanos();
-        let path =
-            std::env::temp_dir().join(format!("ai-fdocs-invalid-docsrs-single-page-{suffix}.toml"));
+        let path = std::env::temp_dir().join(format!("ai-fdocs-docsrs-multi-page-{suffix}.toml"));
 
         fs::write(
             &path,
             r#"[settings]
 docsrs_single_page = false
+docsrs_max_pages = 40
+docsrs_max_depth = 3
+
+[crates.tokio]
+repo = "tokio-rs/tokio"
+docsrs_include_modules = ["sync", "sync::*"]
+docsrs_exclude_modules = ["sync::broadcast"]
+"#,
+        )
+        .expect("must write temporary config");
+
+        let cfg = Config::load(&path).expect("docsrs_single_page=false should parse");
+        fs::remove_file(&path).expect("must cleanup temporary config");
+
+        assert!(!cfg.settings.docsrs_single_page);
+        assert_eq!(cfg.settings.docsrs_max_pages, 40);
+        assert_eq!(cfg.settings.docsrs_max_depth, 3);
+        let tokio = &cfg.crates["tokio"];
+        assert_eq!(tokio.docsrs_include_modules, vec!["sync", "sync::*"]);
+        assert_eq!(tokio.docsrs_exclude_modules, vec!["sync::broadcast"]);
+    }
+
+    #[test]
+    fn config_with_rustdoc_json_api_source_parses() {
+        let suffix = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .expect("system time should be valid")
+            .as_nanos();
+        let path = std::env::temp_dir().join(format!("ai-fdocs-rustdoc-json-{suffix}.toml"));
+
+        fs::write(
+            &path,
+            r#"[settings]
+api_source = "rustdoc_json"
+
+[crates.demo]
+repo = "owner/demo"
+rustdoc_json = "target/doc/demo.json"
+"#,
+        )
+        .expect("must write temporary config");
+
+        let cfg = Config::load(&path).expect("api_source=rustdoc_json should parse");
+        fs::remove_file(&path).expect("must cleanup temporary config");
+
+        assert_eq!(cfg.settings.api_source, ApiSource::RustdocJson);
+        let demo = &cfg.crates["demo"];
+        assert_eq!(
+            demo.rustdoc_json.as_deref(),
+            Some(Path::new("target/doc/demo.json"))
+        );
+        assert_ne!(
+            demo.config_hash(&ContentSettings::default()),
+            CrateDoc::default().config_hash(&ContentSettings::default())
+        );
+    }
+
+    #[test]
+    fn config_with_local_rustdoc_api_source_parses() {
+        let suffix = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .expect("system time should be valid")
+            .as_nanos();
+        let path = std::env::temp_dir().join(format!("ai-fdocs-local-rustdoc-{suffix}.toml"));
+
+        fs::write(
+            &path,
+            r#"[settings]
+api_source = "local_rustdoc"
+
+[crates.internal]
+source_dir = "vendor/internal"
+"#,
+        )
+        .expect("must write temporary config");
+
+        let cfg = Config::load(&path).expect("api_source=local_rustdoc should parse");
+        fs::remove_file(&path).expect("must cleanup temporary config");
+
+        assert_eq!(cfg.settings.api_source, ApiSource::LocalRustdoc);
+        assert_eq!(
+            cfg.crates["internal"].source_dir.as_deref(),
+            Some(Path::new("vendor/internal"))
+        );
+    }
+
+    #[test]
+    fn config_with_unknown_api_source_fails() {
+        let suffix = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .expect("system time should be valid")
+            .as_nanos();
+        let path = std::env::temp_dir().join(format!("ai-fdocs-bad-api-source-{suffix}.toml"));
+
+        fs::write(&path, "[settings]\napi_source = \"html\"\n")
+            .expect("must write temporary config");
+
+        let err = Config::load(&path).expect_err("unknown api_source must fail");
+        fs::remove_file(&path).expect("must cleanup temporary config");
+
+        assert!(err.to_string().contains("settings.api_source must be"));
+    }
+
+    #[test]
+    fn config_with_changelog_windows() {
+        let suffix = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .expect("system time should be valid")
+            .as_nanos();
+        let path = std::env::temp_dir().join(format!("ai-fdocs-changelog-window-{suffix}.toml"));
+
+        fs::write(
+            &path,
+            r#"[crates.tokio]
+repo = "tokio-rs/tokio"
+changelog_window = "previous:3"
+
+[crates.serde]
+repo = "serde-rs/serde"
+changelog_window = "since:1.0.180"
+
+[crates.axum]
+repo = "tokio-rs/axum"
+changelog_window = "newer"
+"#,
+        )
+        .expect("must write temporary config");
+        let cfg = Config::load(&path).expect("changelog windows should parse");
+        assert_eq!(
+            cfg.crates["tokio"].changelog_window,
+            ChangelogWindow::Previous(3)
+        );
+        assert_eq!(
+            cfg.crates["serde"].changelog_window,
+            ChangelogWindow::Since("1.0.180".to_string())
+        );
+        assert_eq!(
+            cfg.crates["axum"].changelog_window,
+            ChangelogWindow::NewerThanLocked
+        );
+        assert_ne!(
+            cfg.crates["axum"].config_hash(&ContentSettings::default()),
+            CrateDoc {
+                repo: Some("tokio-rs/axum".to_string()),
+                ..CrateDoc::default()
+            }
+            .config_hash(&ContentSettings::default())
+        );
+
+        fs::write(
+            &path,
+            "[crates.tokio]
+repo = \"tokio-rs/tokio\"
+changelog_window = \"previous:many\"
+",
+        )
+        .expect("must write temporary config");
+        let err = Config::load(&path).expect_err("bad changelog_window must fail");
+        fs::remove_file(&path).expect("must cleanup temporary config");
+
+        assert!(err.to_string().contains("changelog_window must be"));
+    }
+
+    #[test]
+    fn config_with_total_budget_and_priorities() {
+        let suffix = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .expect("system time should be valid")
+            .as_nanos();
+        let path = std::env::temp_dir().join(format!("ai-fdocs-total-budget-{suffix}.toml"));
+
+        fs::write(
+            &path,
+            r#"[settings]
+total_budget_kb = 512
+
+[crates.tokio]
+repo = "tokio-rs/tokio"
+priority = 3
+
+[crates.serde]
+repo = "serde-rs/serde"
+"#,
+        )
+        .expect("must write temporary config");
+        let cfg = Config::load(&path).expect("budget config should parse");
+        assert_eq!(cfg.settings.total_budget_kb, Some(512));
+        assert_eq!(cfg.crates["tokio"].budget_priority(), 3);
+        assert_eq!(cfg.crates["serde"].budget_priority(), 1);
+
+        fs::write(
+            &path,
+            "[settings]\ntotal_budget_kb = 512\ntotal_budget_tokens = 100000\n",
+        )
+        .expect("must write temporary config");
+        let err = Config::load(&path).expect_err("two budgets must fail");
+        assert!(err.to_string().contains("set only one of"));
+
+        fs::write(
+            &path,
+            "[crates.tokio]\nrepo = \"tokio-rs/tokio\"\npriority = 0\n",
+        )
+        .expect("must write temporary config");
+        let err = Config::load(&path).expect_err("zero priority must fail");
+        fs::remove_file(&path).expect("must cleanup temporary config");
+
+        assert!(err
+            .to_string()
+            .contains("crates.tokio.priority must be greater than 0"));
+    }
+
+    #[test]
+    fn config_with_section_rules() {
+        let suffix = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .expect("system time should be valid")
+            .as_nanos();
+        let path = std::env::temp_dir().join(format!("ai-fdocs-sections-{suffix}.toml"));
+
+        fs::write(
+            &path,
+            r#"[crates.clap]
+repo = "clap-rs/clap"
+include_sections = ["Usage", "Examples"]
+exclude_sections = ["Contributing", "License"]
+"#,
+        )
+        .expect("must write temporary config");
+        let cfg = Config::load(&path).expect("section rules should parse");
+        assert_eq!(cfg.crates["clap"].include_sections, ["Usage", "Examples"]);
+        assert_eq!(
+            cfg.crates["clap"].exclude_sections,
+            ["Contributing", "License"]
+        );
+        assert_ne!(
+            cfg.crates["clap"].config_hash(&ContentSettings::default()),
+            CrateDoc {
+                repo: Some("clap-rs/clap".to_string()),
+                ..CrateDoc::default()
+            }
+            .config_hash(&ContentSettings::default())
+        );
+
+        fs::write(
+            &path,
+            "[crates.clap]\nrepo = \"clap-rs/clap\"\nexclude_sections = [\" \"]\n",
+        )
+        .expect("must write temporary config");
+        let err = Config::load(&path).expect_err("empty section must fail");
+        fs::remove_file(&path).expect("must cleanup temporary config");
+        assert!(err.to_string().contains("must not contain empty headings"));
+    }
+
+    #[test]
+    fn config_with_sparse_index_mirror() {
+        let suffix = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .expect("system time should be valid")
+            .as_nanos();
+        let path = std::env::temp_dir().join(format!("ai-fdocs-sparse-index-{suffix}.toml"));
+
+        fs::write(
+            &path,
+            "[settings]\nsparse_index = \"sparse+https://mirror.example.com/index/\"\nlatest_prereleases = true\n",
+        )
+        .expect("must write temporary config");
+        let cfg = Config::load(&path).expect("mirror index should parse");
+        assert!(cfg.settings.latest_prereleases);
+
+        fs::write(&path, "[settings]\nsparse_index = \"index.crates.io\"\n")
+            .expect("must write temporary config");
+        let err = Config::load(&path).expect_err("index without scheme must fail");
+        fs::remove_file(&path).expect("must cleanup temporary config");
+
+        assert!(err
+            .to_string()
+            .contains("settings.sparse_index must be an http(s) URL"));
+    }
+
+    #[test]
+    fn config_with_zero_docsrs_max_pages_fails_validation() {
+        let suffix = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .expect("system time should be valid")
+            .as_nanos();
+        let path =
+            std::env::temp_dir().join(format!("ai-fdocs-invalid-docsrs-max-pages-{suffix}.toml"));
+
+        fs::write(
+            &path,
+            r#"[settings]
+docsrs_max_pages = 0
 
 [crates.serde]
 repo = "serde-rs/serde"
@@ -633,11 +1400,11 @@ repo = "serde-rs/serde"
         )
         .expect("must write temporary config");
 
-        let err = Config::load(&path).expect_err("docsrs_single_page=false must fail");
+        let err = Config::load(&path).expect_err("zero docsrs_max_pages must fail");
         fs::remove_file(&path).expect("must cleanup temporary config");
 
         assert!(err
             .to_string()
-            .contains("settings.docsrs_single_page=false is not supported yet; use true"));
+            .contains("settings.docsrs_max_pages must be greater than 0"));
     }
 }
@@ -0,0 +1,349 @@
+use std::fmt::Write as _;
+use std::fs;
+use std::path::Path;
+
+use serde::Serialize;
+
+use crate::config::TokenEstimatorKind;
+use crate::processor::chunk::CHUNKS_MANIFEST_FILE;
+use crate::storage::{read_meta, split_name_version};
+use crate::tokens::estimator;
+
+/// Files that only exist for tools, not for the AI to read; matched on the end
+/// of the name so flattened paths such as `docs__CHANGELOG.json` count too.
+const MACHINE_FILES: [&str; 2] = ["CHANGELOG.json", CHUNKS_MANIFEST_FILE];
+
+#[derive(Debug, Clone, Serialize)]
+pub struct FileCost {
+    pub crate_name: String,
+    pub version: String,
+    pub file: String,
+    pub bytes: usize,
+    pub tokens: usize,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct CrateCost {
+    pub crate_name: String,
+    pub version: String,
+    pub files: usize,
+    pub bytes: usize,
+    pub tokens: usize,
+}
+
+/// A crate or file to drop or shrink to get under the budget.
+#[derive(Debug, Clone, Serialize)]
+pub struct Suggestion {
+    pub target: String,
+    pub saves_tokens: usize,
+    pub hint: String,
+}
+
+#[derive(Debug, Serialize)]
+pub struct CostReport {
+    pub estimator: &'static str,
+    pub total_tokens: usize,
+    pub total_bytes: usize,
+    /// `_INDEX.md`, `_UPGRADES.md` and other files outside crate folders.
+    pub shared_tokens: usize,
+    pub crates: Vec<CrateCost>,
+    pub largest_files: Vec<FileCost>,
+    pub budget: Option<usize>,
+    pub suggestions: Vec<Suggestion>,
+}
+
+/// Estimate the tokens of everything under `output_dir` (the `rust` folder).
+/// Counts recorded in `.aifd-meta.toml` are reused for files whose size has
+/// not changed since sync; everything else is estimated from disk.
+pub fn collect_cost(
+    output_dir: &Path,
+    kind: TokenEstimatorKind,
+    top: usize,
+    budget: Option<usize>,
+) -> CostReport {
+    let tokens = estimator(kind);
+    let mut files = Vec::new();
+    let mut shared_tokens = 0;
+    let mut shared_bytes = 0;
+
+    let mut entries: Vec<_> = fs::read_dir(output_dir)
+        .map(|dir| dir.flatten().map(|e| e.path()).collect())
+        .unwrap_or_default();
+    entries.sort();
+    for path in entries {
+        let name = path
+            .file_name()
+            .map(|n| n.to_string_lossy().to_string())
+            .unwrap_or_default();
+        if name.starts_with('.') {
+            continue;
+        }
+        if path.is_file() {
+            if let Ok(content) = fs::read_to_string(&path) {
+                shared_tokens += tokens.estimate(&content);
+                shared_bytes += content.len();
+            }
+            continue;
+        }
+        let Some((crate_name, version)) = split_name_version(&name) else {
+            continue;
+        };
+        let recorded = read_meta(output_dir, crate_name, version)
+            .filter(|meta| meta.token_estimator.as_deref() == Some(kind.as_str()))
+            .map(|meta| meta.files)
+            .unwrap_or_default();
+
+        let mut crate_files: Vec<_> = fs::read_dir(&path)
+            .map(|dir| dir.flatten().map(|e| e.path()).collect())
+            .unwrap_or_default();
+        crate_files.sort();
+        for file_path in crate_files {
+            let Some(file) = file_path
+                .file_name()
+                .map(|n| n.to_string_lossy().to_string())
+            else {
+                continue;
+            };
+            if file.starts_with('.') || !file_path.is_file() {
+                continue;
+            }
+            let bytes = fs::metadata(&file_path).map_or(0, |m| m.len() as usize);
+            let known = recorded
+                .iter()
+                .find(|f| f.path == file && f.bytes == bytes)
+                .map(|f| f.tokens);
+            let Some(file_tokens) = known.or_else(|| {
+                fs::read_to_string(&file_path)
+                    .ok()
+                    .map(|content| tokens.estimate(&content))
+            }) else {
+                continue;
+            };
+            files.push(FileCost {
+                crate_name: crate_name.to_string(),
+                version: version.to_string(),
+                file,
+                bytes,
+                tokens: file_tokens,
+            });
+        }
+    }
+
+    let mut crates: Vec<CrateCost> = Vec::new();
+    for file in &files {
+        match crates
+            .iter_mut()
+            .find(|c| c.crate_name == file.crate_name && c.version == file.version)
+        {
+            Some(entry) => {
+                entry.files += 1;
+                entry.bytes += file.bytes;
+                entry.tokens += file.tokens;
+            }
+            None => crates.push(CrateCost {
+                crate_name: file.crate_name.clone(),
+                version: file.version.clone(),
+                files: 1,
+                bytes: file.bytes,
+                tokens: file.tokens,
+            }),
+        }
+    }
+    crates.sort_by(|a, b| {
+        b.tokens
+            .cmp(&a.tokens)
+            .then(a.crate_name.cmp(&b.crate_name))
+    });
+
+    let total_tokens = shared_tokens + crates.iter().map(|c| c.tokens).sum::<usize>();
+    let total_bytes = shared_bytes + crates.iter().map(|c| c.bytes).sum::<usize>();
+
+    files.sort_by(|a, b| b.tokens.cmp(&a.tokens).then(a.file.cmp(&b.file)));
+    let suggestions = match budget {
+        Some(budget) if total_tokens > budget => {
+            suggest_trims(&crates, &files, total_tokens - budget)
+        }
+        _ => Vec::new(),
+    };
+    files.truncate(top);
+
+    CostReport {
+        estimator: kind.as_str(),
+        total_tokens,
+        total_bytes,
+        shared_tokens,
+        crates,
+        largest_files: files,
+        budget,
+        suggestions,
+    }
+}
+
+/// Crates that alone cover `excess` (smallest first), then the largest files
+/// until their savings add up to it.
+fn suggest_trims(crates: &[CrateCost], files: &[FileCost], excess: usize) -> Vec<Suggestion> {
+    let mut suggestions = Vec::new();
+
+    let mut enough: Vec<&CrateCost> = crates.iter().filter(|c| c.tokens >= excess).collect();
+    enough.sort_by_key(|c| c.tokens);
+    if let Some(smallest) = enough.first() {
+        suggestions.push(Suggestion {
+            target: format!("{}@{}", smallest.crate_name, smallest.version),
+            saves_tokens: smallest.tokens,
+            hint: "remove the crate from ai-fdocs.toml".to_string(),
+        });
+    }
+
+    let mut saved = 0;
+    for file in files.iter().filter(|f| f.file != "_SUMMARY.md") {
+        if saved >= excess {
+            break;
+        }
+        saved += file.tokens;
+        suggestions.push(Suggestion {
+            target: format!("{}@{}/{}", file.crate_name, file.version, file.file),
+            saves_tokens: file.tokens,
+            hint: trim_hint(&file.file).to_string(),
+        });
+    }
+    suggestions
+}
+
+fn trim_hint(file: &str) -> &'static str {
+    if MACHINE_FILES.iter().any(|name| file.ends_with(name)) {
+        "machine-readable; keep it out of the AI context"
+    } else if file.contains("CHANGELOG") {
+        "narrow the crate's `changelog_window`"
+    } else if file.starts_with("API") {
+        "lower `max_file_size_kb` or split with `chunk_strategy = \"headings\"`"
+    } else {
+        "drop it from the crate's `files`"
+    }
+}
+
+pub fn format_cost_table(report: &CostReport) -> String {
+    const COL_CRATE: usize = 24;
+    const COL_VERSION: usize = 16;
+    const COL_NUM: usize = 10;
+
+    let mut output = String::new();
+    let _ = writeln!(
+        output,
+        "{:<COL_CRATE$} {:<COL_VERSION$} {:>COL_NUM$} {:>COL_NUM$} {:>COL_NUM$}",
+        "Crate", "Version", "Files", "KB", "Tokens"
+    );
+    let _ = writeln!(
+        output,
+        "{:-<COL_CRATE$} {:-<COL_VERSION$} {:->COL_NUM$} {:->COL_NUM$} {:->COL_NUM$}",
+        "", "", "", "", ""
+    );
+    for item in &report.crates {
+        let _ = writeln!(
+            output,
+            "{:<COL_CRATE$} {:<COL_VERSION$} {:>COL_NUM$} {:>COL_NUM$.1} {:>COL_NUM$}",
+            item.crate_name,
+            item.version,
+            item.files,
+            item.bytes as f64 / 1024.0,
+            item.tokens,
+        );
+    }
+
+    let _ = writeln!(output);
+    let _ = writeln!(
+        output,
+        "Total: ~{} tokens ({:.1} KB, {} estimator; shared index files ~{} tokens)",
+        report.total_tokens,
+        report.total_bytes as f64 / 1024.0,
+        report.estimator,
+        report.shared_tokens
+    );
+
+    if !report.largest_files.is_empty() {
+        let _ = writeln!(output, "\nLargest files:");
+        for file in &report.largest_files {
+            let _ = writeln!(
+                output,
+                "- {}@{}/{}: ~{} tokens",
+                file.crate_name, file.version, file.file, file.tokens
+            );
+        }
+    }
+
+    if let Some(budget) = report.budget {
+        if report.total_tokens <= budget {
+            let _ = writeln!(output, "\nWithin budget of {budget} tokens.");
+        } else {
+            let _ = writeln!(
+                output,
+                "\nOver budget of {budget} tokens by {}. To trim:",
+                report.total_tokens - budget
+            );
+            for suggestion in &report.suggestions {
+                let _ = writeln!(
+                    output,
+                    "- {} (saves ~{} tokens): {}",
+                    suggestion.target, suggestion.saves_tokens, suggestion.hint
+                );
+            }
+        }
+    }
+
+    output
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+
+    use super::{collect_cost, format_cost_table, trim_hint};
+    use crate::config::TokenEstimatorKind;
+
+    #[test]
+    fn reports_tokens_per_crate_and_suggests_trims() {
+        let tmp = std::env::temp_dir().join(format!("ai-fdocs-cost-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&tmp);
+        fs::create_dir_all(tmp.join("tokio@1.44.0")).expect("tokio dir");
+        fs::create_dir_all(tmp.join("serde@1.0.210")).expect("serde dir");
+        fs::write(tmp.join("_INDEX.md"), "# Index\n").expect("index");
+        fs::write(tmp.join("tokio@1.44.0/API.md"), "word ".repeat(400)).expect("api");
+        fs::write(tmp.join("tokio@1.44.0/CHANGELOG.md"), "fix ".repeat(100)).expect("changelog");
+        fs::write(
+            tmp.join("tokio@1.44.0/.aifd-meta.toml"),
+            "version = \"1.44.0\"\n",
+        )
+        .expect("meta");
+        fs::write(tmp.join("serde@1.0.210/README.md"), "serde ".repeat(50)).expect("readme");
+
+        let report = collect_cost(&tmp, TokenEstimatorKind::Heuristic, 2, Some(200));
+        assert_eq!(report.shared_tokens, 3);
+        assert_eq!(report.total_tokens, 3 + 400 + 100 + 100);
+        assert_eq!(report.crates[0].crate_name, "tokio");
+        assert_eq!(report.crates[0].tokens, 500);
+        assert_eq!(report.crates[0].files, 2);
+        assert_eq!(report.largest_files.len(), 2);
+        assert_eq!(report.largest_files[0].file, "API.md");
+
+        assert_eq!(report.suggestions[0].target, "tokio@1.44.0");
+        assert_eq!(report.suggestions[1].target, "tokio@1.44.0/API.md");
+        assert!(report.suggestions[1].hint.contains("max_file_size_kb"));
+
+        let table = format_cost_table(&report);
+        assert!(table.contains("Total: ~603 tokens"));
+        assert!(table.contains("Over budget of 200 tokens by 403. To trim:"));
+        assert!(table.contains("- tokio@1.44.0/API.md: ~400 tokens"));
+
+        let _ = fs::remove_dir_all(&tmp);
+    }
+
+    #[test]
+    fn flattened_changelog_json_is_machine_readable() {
+        assert_eq!(
+            trim_hint("CHANGELOG.json"),
+            trim_hint("docs__CHANGELOG.json")
+        );
+        assert!(trim_hint("docs__CHANGELOG.json").starts_with("machine-readable"));
+        assert!(trim_hint("docs__CHANGELOG.md").contains("changelog_window"));
+        assert!(trim_hint("API.md").contains("max_file_size_kb"));
+    }
+}
@@ -42,6 +42,9 @@ pub enum AiDocsError {
     #[error("HTTP request failed for {url}: status {status}")]
     HttpStatus { url: String, status: u16 },
 
+    #[error("docs.rs has no documentation for {url}: {detail}")]
+    DocsRsBuildFailed { url: String, detail: String },
+
     #[error("Cargo.lock parsing error: {0}")]
     CargoLockParse(String),
 
@@ -55,6 +58,15 @@ pub enum AiDocsError {
         tried_tags: Vec<String>,
     },
 
+    #[error("Rustdoc JSON error: {0}")]
+    RustdocJson(String),
+
+    #[error("Local rustdoc build failed: {0}")]
+    LocalRustdoc(String),
+
+    #[error("RustSec advisory database error: {0}")]
+    AdvisoryDb(String),
+
     #[error("Optional file not found: {0}")]
     OptionalFileNotFound(String),
 
@@ -68,9 +80,9 @@ impl AiDocsError {
             Self::GitHubAuth { .. } => SyncErrorKind::Auth,
             Self::GitHubRateLimit { .. } => SyncErrorKind::RateLimit,
             Self::Http(_) | Self::Fetch { .. } => SyncErrorKind::Network,
-            Self::GitHubFileNotFound { .. } | Self::OptionalFileNotFound(_) => {
-                SyncErrorKind::NotFound
-            }
+            Self::GitHubFileNotFound { .. }
+            | Self::OptionalFileNotFound(_)
+            | Self::DocsRsBuildFailed { .. } => SyncErrorKind::NotFound,
             Self::HttpStatus { status, .. } if *status == 404 => SyncErrorKind::NotFound,
             Self::HttpStatus { status, .. } if *status >= 500 => SyncErrorKind::Network,
             _ => SyncErrorKind::Other,
@@ -0,0 +1,280 @@
+use serde::{Deserialize, Serialize};
+
+/// Registry facts about one published version, stored in `.aifd-meta.toml`
+/// and shown in `_SUMMARY.md`.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CratesIoMetadata {
+    #[serde(default)]
+    pub description: Option<String>,
+    #[serde(default)]
+    pub license: Option<String>,
+    /// `package.rust-version` (MSRV) of this version.
+    #[serde(default)]
+    pub rust_version: Option<String>,
+    #[serde(default)]
+    pub edition: Option<String>,
+    #[serde(default)]
+    pub keywords: Vec<String>,
+    #[serde(default)]
+    pub categories: Vec<String>,
+    #[serde(default)]
+    pub homepage: Option<String>,
+    #[serde(default)]
+    pub documentation: Option<String>,
+    /// Publish date (`YYYY-MM-DD`).
+    #[serde(default)]
+    pub published_at: Option<String>,
+    #[serde(default)]
+    pub yanked: bool,
+    /// The crate carries a `maintenance = { status = "deprecated" }` badge.
+    #[serde(default)]
+    pub deprecated: bool,
+}
+
+#[derive(Debug, Deserialize)]
+struct CrateResponse {
+    #[serde(rename = "crate")]
+    crate_data: CrateFields,
+    #[serde(default)]
+    versions: Vec<VersionFields>,
+}
+
+#[derive(Debug, Deserialize)]
+struct VersionResponse {
+    version: VersionFields,
+}
+
+#[derive(Debug, Default, Deserialize)]
+struct CrateFields {
+    description: Option<String>,
+    homepage: Option<String>,
+    documentation: Option<String>,
+    #[serde(default)]
+    keywords: Vec<String>,
+    #[serde(default)]
+    categories: Vec<String>,
+    #[serde(default)]
+    badges: serde_json::Value,
+}
+
+#[derive(Debug, Default, Deserialize)]
+struct VersionFields {
+    num: String,
+    license: Option<String>,
+    rust_version: Option<String>,
+    edition: Option<String>,
+    #[serde(default)]
+    yanked: bool,
+    created_at: Option<String>,
+    // Newer API responses carry per-version manifest fields; they win over
+    // the crate-level values, which describe the newest release.
+    description: Option<String>,
+    homepage: Option<String>,
+    documentation: Option<String>,
+}
+
+/// Crate-level fields from `/api/v1/crates/{name}` plus the entry for `version`
+/// from its `versions` list. The flag is `false` when the list does not include
+/// that version and `/api/v1/crates/{name}/{version}` has to be asked instead.
+pub fn parse_crate_response(
+    json: &[u8],
+    version: &str,
+) -> serde_json::Result<(CratesIoMetadata, bool)> {
+    let response: CrateResponse = serde_json::from_slice(json)?;
+    let found = response.versions.into_iter().find(|v| v.num == version);
+    let is_found = found.is_some();
+    Ok((metadata_from(&response.crate_data, found), is_found))
+}
+
+/// Merge a `/api/v1/crates/{name}/{version}` response into crate-level metadata.
+pub fn apply_version_response(
+    crate_level: CratesIoMetadata,
+    json: &[u8],
+) -> serde_json::Result<CratesIoMetadata> {
+    let response: VersionResponse = serde_json::from_slice(json)?;
+    let crate_fields = CrateFields {
+        description: crate_level.description,
+        homepage: crate_level.homepage,
+        documentation: crate_level.documentation,
+        keywords: crate_level.keywords,
+        categories: crate_level.categories,
+        badges: serde_json::Value::Null,
+    };
+    Ok(CratesIoMetadata {
+        deprecated: crate_level.deprecated,
+        ..metadata_from(&crate_fields, Some(response.version))
+    })
+}
+
+fn metadata_from(crate_data: &CrateFields, version: Option<VersionFields>) -> CratesIoMetadata {
+    let version = version.unwrap_or_default();
+
+    CratesIoMetadata {
+        description: non_empty(
+            version
+                .description
+                .or_else(|| crate_data.description.clone()),
+        )
+        .map(|d| d.split_whitespace().collect::<Vec<_>>().join(" ")),
+        license: non_empty(version.license),
+        rust_version: non_empty(version.rust_version),
+        edition: non_empty(version.edition),
+        keywords: crate_data.keywords.clone(),
+        categories: crate_data.categories.clone(),
+        homepage: non_empty(version.homepage.or_else(|| crate_data.homepage.clone())),
+        documentation: non_empty(
+            version
+                .documentation
+                .or_else(|| crate_data.documentation.clone()),
+        ),
+        published_at: version
+            .created_at
+            .map(|ts| ts.chars().take(10).collect::<String>())
+            .filter(|d| !d.is_empty()),
+        yanked: version.yanked,
+        deprecated: has_deprecated_badge(&crate_data.badges),
+    }
+}
+
+/// crates.io returns badges as `[{"badge_type": "maintenance", "attributes": {"status": ...}}]`.
+fn has_deprecated_badge(badges: &serde_json::Value) -> bool {
+    badges.as_array().is_some_and(|badges| {
+        badges.iter().any(|badge| {
+            badge.get("badge_type").and_then(|t| t.as_str()) == Some("maintenance")
+                && badge.pointer("/attributes/status").and_then(|s| s.as_str())
+                    == Some("deprecated")
+        })
+    })
+}
+
+fn non_empty(value: Option<String>) -> Option<String> {
+    value
+        .map(|v| v.trim().to_string())
+        .filter(|v| !v.is_empty())
+}
+
+/// `## Crate info` block for `_SUMMARY.md`; only known facts are listed.
+pub fn render_crate_info(meta: &CratesIoMetadata) -> String {
+    let mut lines = Vec::new();
+    if let Some(description) = &meta.description {
+        lines.push(format!("- Description: {description}"));
+    }
+    if let Some(license) = &meta.license {
+        lines.push(format!("- License: `{license}`"));
+    }
+    if let Some(msrv) = &meta.rust_version {
+        lines.push(format!("- MSRV (`rust-version`): `{msrv}`"));
+    }
+    if let Some(edition) = &meta.edition {
+        lines.push(format!("- Edition: `{edition}`"));
+    }
+    if !meta.keywords.is_empty() {
+        lines.push(format!("- Keywords: {}", code_list(&meta.keywords)));
+    }
+    if !meta.categories.is_empty() {
+        lines.push(format!("- Categories: {}", code_list(&meta.categories)));
+    }
+    if let Some(homepage) = &meta.homepage {
+        lines.push(format!("- Homepage: {homepage}"));
+    }
+    if let Some(documentation) = &meta.documentation {
+        lines.push(format!("- Documentation: {documentation}"));
+    }
+    if let Some(published_at) = &meta.published_at {
+        lines.push(format!("- Published: {published_at}"));
+    }
+    lines.join("\n")
+}
+
+fn code_list(items: &[String]) -> String {
+    items
+        .iter()
+        .map(|i| format!("`{i}`"))
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{apply_version_response, parse_crate_response, render_crate_info};
+
+    const CRATE_JSON: &str = r#"{
+        "crate": {
+            "name": "serde",
+            "description": "A generic serialization/deserialization framework\n",
+            "homepage": "https://serde.rs",
+            "documentation": "https://docs.rs/serde",
+            "keywords": ["serde", "serialization", "no_std"],
+            "categories": ["encoding", "no-std"],
+            "badges": []
+        },
+        "versions": [
+            {"num": "1.0.211", "license": "MIT OR Apache-2.0", "rust_version": "1.31", "yanked": false, "created_at": "2024-10-19T10:00:00.000000Z"},
+            {"num": "1.0.210", "license": "MIT OR Apache-2.0", "rust_version": "1.31", "edition": "2018", "yanked": true, "created_at": "2024-09-06T22:09:31.393021Z"}
+        ]
+    }"#;
+
+    #[test]
+    fn reads_exact_version_metadata() {
+        let (meta, found) = parse_crate_response(CRATE_JSON.as_bytes(), "1.0.210").expect("parse");
+        assert!(found);
+        assert_eq!(
+            meta.description.as_deref(),
+            Some("A generic serialization/deserialization framework")
+        );
+        assert_eq!(meta.license.as_deref(), Some("MIT OR Apache-2.0"));
+        assert_eq!(meta.rust_version.as_deref(), Some("1.31"));
+        assert_eq!(meta.edition.as_deref(), Some("2018"));
+        assert_eq!(meta.keywords, vec!["serde", "serialization", "no_std"]);
+        assert_eq!(meta.published_at.as_deref(), Some("2024-09-06"));
+        assert!(meta.yanked);
+        assert!(!meta.deprecated);
+    }
+
+    #[test]
+    fn detects_deprecated_maintenance_badge() {
+        let json = br#"{
+            "crate": {
+                "name": "failure",
+                "badges": [{"badge_type": "maintenance", "attributes": {"status": "deprecated"}}]
+            },
+            "versions": [{"num": "0.1.8", "yanked": false}]
+        }"#;
+        let (meta, found) = parse_crate_response(json, "0.1.8").expect("parse");
+        assert!(found);
+        assert!(meta.deprecated);
+
+        let (crate_level, _) = parse_crate_response(json, "0.1.0").expect("parse");
+        let meta = apply_version_response(crate_level, br#"{"version": {"num": "0.1.0"}}"#)
+            .expect("parse version");
+        assert!(meta.deprecated);
+    }
+
+    #[test]
+    fn falls_back_to_version_endpoint() {
+        let (crate_level, found) =
+            parse_crate_response(CRATE_JSON.as_bytes(), "1.0.100").expect("parse");
+        assert!(!found);
+        assert_eq!(crate_level.license, None);
+
+        let version_json = br#"{"version": {"num": "1.0.100", "license": "MIT/Apache-2.0", "yanked": false, "created_at": "2019-05-01T00:00:00Z"}}"#;
+        let meta = apply_version_response(crate_level, version_json).expect("parse version");
+        assert_eq!(meta.license.as_deref(), Some("MIT/Apache-2.0"));
+        assert_eq!(meta.homepage.as_deref(), Some("https://serde.rs"));
+        assert_eq!(meta.published_at.as_deref(), Some("2019-05-01"));
+    }
+
+    #[test]
+    fn renders_known_facts_only() {
+        let (meta, _) = parse_crate_response(CRATE_JSON.as_bytes(), "1.0.210").expect("parse");
+        let info = render_crate_info(&meta);
+        assert!(info.starts_with("- Description: "));
+        assert!(info.contains("- MSRV (`rust-version`): `1.31`"));
+        assert!(info.contains("- Categories: `encoding`, `no-std`"));
+
+        let (meta, _) = parse_crate_response(CRATE_JSON.as_bytes(), "1.0.211").expect("parse");
+        let info = render_crate_info(&meta);
+        assert!(!info.contains("yanked"));
+        assert!(!info.contains("Edition"));
+    }
+}
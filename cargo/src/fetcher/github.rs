@@ -3,6 +3,7 @@ use std::time::Duration;
 
 const MAX_RETRY_ATTEMPTS: usize = 3;
 const RETRY_BASE_BACKOFF_MS: u64 = 500;
+const MAX_EXAMPLE_FILES: usize = 8;
 
 use reqwest::{Client, StatusCode};
 use serde::Deserialize;
@@ -42,6 +43,38 @@ pub struct GitHubFetcher {
 #[derive(Deserialize)]
 struct RepoInfo {
     default_branch: String,
+    #[serde(default)]
+    archived: bool,
+}
+
+/// A published GitHub release and its notes.
+#[derive(Debug, Clone, Deserialize)]
+pub struct ReleaseNote {
+    pub tag_name: String,
+    #[serde(default)]
+    pub body: Option<String>,
+    #[serde(default)]
+    pub published_at: Option<String>,
+    #[serde(default)]
+    pub html_url: Option<String>,
+    #[serde(default)]
+    pub draft: bool,
+}
+
+#[derive(Deserialize)]
+struct ContentEntry {
+    path: String,
+    #[serde(rename = "type")]
+    kind: String,
+    #[serde(default)]
+    size: u64,
+}
+
+/// `GITHUB_TOKEN` (or `GH_TOKEN`) used to authenticate GitHub requests.
+pub fn github_token() -> Option<String> {
+    env::var("GITHUB_TOKEN")
+        .or_else(|_| env::var("GH_TOKEN"))
+        .ok()
 }
 
 impl GitHubFetcher {
@@ -59,9 +92,7 @@ impl GitHubFetcher {
     }
 
     fn with_base_urls_internal(api_base_url: &str, raw_base_url: &str, no_proxy: bool) -> Self {
-        let token = env::var("GITHUB_TOKEN")
-            .or_else(|_| env::var("GH_TOKEN"))
-            .ok();
+        let token = github_token();
 
         let mut headers = reqwest::header::HeaderMap::new();
         if let Some(t) = token {
@@ -105,6 +136,13 @@ impl GitHubFetcher {
         format!("{}/repos/{owner_repo}", self.api_base_url)
     }
 
+    fn api_contents_url(&self, owner_repo: &str, git_ref: &str, path: &str) -> String {
+        format!(
+            "{}/repos/{owner_repo}/contents/{path}?ref={git_ref}",
+            self.api_base_url
+        )
+    }
+
     fn raw_file_url(&self, repo: &str, git_ref: &str, candidate: &str) -> String {
         format!("{}/{repo}/{git_ref}/{candidate}", self.raw_base_url)
     }
@@ -115,14 +153,7 @@ impl GitHubFetcher {
         crate_name: &str,
         version: &str,
     ) -> Result<ResolvedRef> {
-        let candidates = [
-            format!("v{version}"),
-            version.to_string(),
-            format!("{crate_name}-v{version}"),
-            format!("{crate_name}-{version}"),
-        ];
-
-        for tag in candidates {
+        for tag in crate::version::tag_candidates(crate_name, version) {
             let url = self.api_tag_url(owner_repo, &tag);
             let res = self.send_with_retry(url.as_str()).await?;
             if res.status().is_success() {
@@ -151,6 +182,37 @@ impl GitHubFetcher {
         })
     }
 
+    /// Whether the repository is archived (read-only) on GitHub.
+    pub async fn is_archived(&self, owner_repo: &str) -> Result<bool> {
+        let repo_url = self.api_repo_url(owner_repo);
+        let repo_resp = self.send_with_retry(repo_url.as_str()).await?;
+        if !repo_resp.status().is_success() {
+            return Err(Self::status_error(repo_url.as_str(), repo_resp.status()));
+        }
+
+        let repo_info: RepoInfo = repo_resp.json().await?;
+        Ok(repo_info.archived)
+    }
+
+    /// The most recent (up to 100) published releases of a repository; drafts
+    /// are skipped and a repository without releases yields nothing.
+    pub async fn fetch_releases(&self, owner_repo: &str) -> Result<Vec<ReleaseNote>> {
+        let url = format!(
+            "{}/repos/{owner_repo}/releases?per_page=100",
+            self.api_base_url
+        );
+        let res = self.send_with_retry(url.as_str()).await?;
+        if res.status() == StatusCode::NOT_FOUND {
+            return Ok(Vec::new());
+        }
+        if !res.status().is_success() {
+            return Err(Self::status_error(url.as_str(), res.status()));
+        }
+
+        let releases: Vec<ReleaseNote> = res.json().await?;
+        Ok(releases.into_iter().filter(|r| !r.draft).collect())
+    }
+
     pub async fn fetch_files(
         &self,
         repo: &str,
@@ -164,6 +226,53 @@ impl GitHubFetcher {
         out
     }
 
+    /// Fetch the smallest `.rs` files under `examples/` (relative to `subpath`)
+    /// at `git_ref`. A repository without an examples directory yields nothing.
+    pub async fn fetch_examples(
+        &self,
+        repo: &str,
+        git_ref: &str,
+        subpath: Option<&str>,
+    ) -> Result<Vec<FetchedFile>> {
+        let dir = match subpath
+            .map(|s| s.trim_matches('/'))
+            .filter(|s| !s.is_empty())
+        {
+            Some(subpath) => format!("{subpath}/examples"),
+            None => "examples".to_string(),
+        };
+        let url = self.api_contents_url(repo, git_ref, &dir);
+        let res = self.send_with_retry(url.as_str()).await?;
+        if res.status() == StatusCode::NOT_FOUND {
+            return Ok(Vec::new());
+        }
+        if !res.status().is_success() {
+            return Err(Self::status_error(url.as_str(), res.status()));
+        }
+
+        let mut entries: Vec<ContentEntry> = res
+            .json::<Vec<ContentEntry>>()
+            .await?
+            .into_iter()
+            .filter(|e| e.kind == "file" && e.path.ends_with(".rs"))
+            .collect();
+        entries.sort_by_key(|e| e.size);
+
+        let mut files = Vec::new();
+        for entry in entries.into_iter().take(MAX_EXAMPLE_FILES) {
+            let req = FileRequest {
+                original_path: entry.path.clone(),
+                candidates: vec![entry.path],
+                required: false,
+            };
+            match self.fetch_file(repo, git_ref, &req).await {
+                Ok(file) => files.push(file),
+                Err(e) => debug!("skipping example {}: {e}", req.original_path),
+            }
+        }
+        Ok(files)
+    }
+
     async fn fetch_file(
         &self,
         repo: &str,
@@ -381,6 +490,105 @@ mod tests {
         assert!(resolved.is_fallback);
     }
 
+    #[tokio::test]
+    async fn reads_archived_flag_from_repo_info() {
+        let mut routes = HashMap::new();
+        routes.insert(
+            "/repos/owner/old".to_string(),
+            (
+                200,
+                "{\"default_branch\":\"master\",\"archived\":true}".to_string(),
+            ),
+        );
+        routes.insert(
+            "/repos/owner/repo".to_string(),
+            (200, "{\"default_branch\":\"main\"}".to_string()),
+        );
+
+        let api_base = start_mock_server(routes);
+        let fetcher =
+            GitHubFetcher::with_base_urls_no_proxy(api_base.as_str(), "http://raw.invalid");
+
+        assert!(fetcher.is_archived("owner/old").await.expect("repo info"));
+        assert!(!fetcher.is_archived("owner/repo").await.expect("repo info"));
+    }
+
+    #[tokio::test]
+    async fn fetch_releases_skips_drafts() {
+        let mut routes = HashMap::new();
+        routes.insert(
+            "/repos/owner/repo/releases?per_page=100".to_string(),
+            (
+                200,
+                r#"[{"tag_name":"v1.1.0","body":"- Fixed","published_at":"2024-02-01T00:00:00Z","html_url":"https://github.com/owner/repo/releases/tag/v1.1.0"},{"tag_name":"v1.2.0","draft":true}]"#
+                    .to_string(),
+            ),
+        );
+
+        let api_base = start_mock_server(routes);
+        let fetcher =
+            GitHubFetcher::with_base_urls_no_proxy(api_base.as_str(), "http://raw.invalid");
+
+        let releases = fetcher
+            .fetch_releases("owner/repo")
+            .await
+            .expect("releases");
+        assert_eq!(releases.len(), 1);
+        assert_eq!(releases[0].tag_name, "v1.1.0");
+        assert_eq!(releases[0].body.as_deref(), Some("- Fixed"));
+        assert!(fetcher
+            .fetch_releases("owner/missing")
+            .await
+            .expect("no releases")
+            .is_empty());
+    }
+
+    #[tokio::test]
+    async fn fetch_examples_lists_rust_files_smallest_first() {
+        let mut routes = HashMap::new();
+        routes.insert(
+            "/repos/owner/repo/contents/crates/demo/examples?ref=v1.0.0".to_string(),
+            (
+                200,
+                r#"[{"path":"crates/demo/examples/big.rs","type":"file","size":900},
+                    {"path":"crates/demo/examples/small.rs","type":"file","size":10},
+                    {"path":"crates/demo/examples/data.json","type":"file","size":5},
+                    {"path":"crates/demo/examples/nested","type":"dir","size":0}]"#
+                    .to_string(),
+            ),
+        );
+        routes.insert(
+            "/owner/repo/v1.0.0/crates/demo/examples/small.rs".to_string(),
+            (200, "fn main() {}".to_string()),
+        );
+        routes.insert(
+            "/owner/repo/v1.0.0/crates/demo/examples/big.rs".to_string(),
+            (200, "fn main() { big() }".to_string()),
+        );
+
+        let base = start_mock_server(routes);
+        let fetcher = GitHubFetcher::with_base_urls_no_proxy(base.as_str(), base.as_str());
+
+        let files = fetcher
+            .fetch_examples("owner/repo", "v1.0.0", Some("crates/demo/"))
+            .await
+            .expect("examples");
+        let paths: Vec<&str> = files.iter().map(|f| f.path.as_str()).collect();
+        assert_eq!(
+            paths,
+            vec![
+                "crates/demo/examples/small.rs",
+                "crates/demo/examples/big.rs"
+            ]
+        );
+
+        let none = fetcher
+            .fetch_examples("owner/repo", "v1.0.0", None)
+            .await
+            .expect("missing examples dir is not an error");
+        assert!(none.is_empty());
+    }
+
     #[tokio::test]
     async fn fetch_files_reports_partial_failures_and_optional_miss() {
         let mut routes = HashMap::new();
@@ -1,37 +1,92 @@
-use std::time::Duration;
+use std::collections::{HashSet, VecDeque};
+use std::io::Read;
+use std::path::Path;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
 
+use flate2::read::GzDecoder;
+
+use regex::Regex;
 use reqwest::{Client, StatusCode};
-use serde::Deserialize;
+use scraper::{Html, Selector};
 use tokio::time::sleep;
 use tracing::debug;
 
+use crate::config::Settings;
 use crate::error::{AiDocsError, Result};
+use crate::fetcher::crates_io::{apply_version_response, parse_crate_response, CratesIoMetadata};
+use crate::fetcher::sparse_index::{
+    index_path, normalize_index_url, parse_index_versions, select_latest, IndexVersion,
+    DEFAULT_SPARSE_INDEX,
+};
+use crate::processor::examples::{
+    doc_code_blocks, example_budget_bytes, render_examples_section, select_examples, Example,
+};
+use crate::processor::features::{parse_docsrs_features_page, FeatureInfo};
+use crate::processor::html::{absolutize_url, select_to_markdown};
+use crate::processor::rustdoc_json::render_api_markdown;
+use crate::usage::covers;
 
 const APP_USER_AGENT: &str = concat!("cargo-ai-fdocs/", env!("CARGO_PKG_VERSION"));
 const MAX_RETRY_ATTEMPTS: usize = 3;
 const RETRY_BASE_BACKOFF_MS: u64 = 500;
+const DOCSRS_ORIGIN: &str = "https://docs.rs";
+const ROOT_ARTIFACT_PATH: &str = "API.md";
+/// crates.io asks API clients for at most one request per second.
+const CRATES_IO_API_INTERVAL: Duration = Duration::from_secs(1);
+
+/// Earliest time the next crates.io API request may start, shared by every
+/// fetcher in the process since crates are synced concurrently.
+static CRATES_IO_NEXT_SLOT: Mutex<Option<Instant>> = Mutex::new(None);
 
 pub struct LatestDocsFetcher {
     client: Client,
+    /// Sparse index (or mirror) used to resolve the latest version.
+    index_url: String,
+    include_prereleases: bool,
 }
 
 #[derive(Debug, Clone)]
 pub struct DocsRsArtifact {
+    /// `source_kind` recorded in crate metadata (`docsrs` or `rustdoc_json`).
+    pub source_kind: &'static str,
     pub markdown: String,
     pub docsrs_input_url: String,
-    pub truncated: bool,
+    /// Markdown files written next to `API.md`: module pages in multi-page mode
+    /// and `FEATURES.md`.
+    pub module_files: Vec<ModuleFile>,
+    /// Every docs.rs page that contributed to the artifact, in crawl order.
+    pub pages: Vec<CrawledPage>,
+}
+
+#[derive(Debug, Clone)]
+pub struct ModuleFile {
+    pub path: String,
+    pub markdown: String,
 }
 
-#[derive(Debug, Deserialize)]
-struct CratesIoResponse {
-    #[serde(rename = "crate")]
-    crate_data: CratesIoCrate,
+#[derive(Debug, Clone)]
+pub struct CrawledPage {
+    pub url: String,
+    pub artifact_path: String,
 }
 
-#[derive(Debug, Deserialize)]
-struct CratesIoCrate {
-    max_stable_version: Option<String>,
-    max_version: Option<String>,
+/// Limits for the multi-page rustdoc crawl (`settings.docsrs_single_page = false`).
+#[derive(Debug, Clone)]
+pub struct CrawlOptions {
+    pub max_pages: usize,
+    pub max_depth: usize,
+    pub include_modules: Vec<String>,
+    pub exclude_modules: Vec<String>,
+    /// Paths the project uses, relative to the crate root (`sync::mpsc::Sender`):
+    /// their modules are fetched first.
+    pub used_paths: Vec<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct RustdocPage {
+    module: String,
+    is_module_index: bool,
 }
 
 impl LatestDocsFetcher {
@@ -41,11 +96,275 @@ impl LatestDocsFetcher {
             .timeout(Duration::from_secs(30))
             .build()
             .expect("reqwest client");
-        Self { client }
+        Self {
+            client,
+            index_url: DEFAULT_SPARSE_INDEX.to_string(),
+            include_prereleases: false,
+        }
     }
 
+    /// Fetcher that resolves versions via `settings.sparse_index`, honoring
+    /// `settings.latest_prereleases`.
+    pub fn from_settings(settings: &Settings) -> Self {
+        Self {
+            index_url: normalize_index_url(&settings.sparse_index),
+            include_prereleases: settings.latest_prereleases,
+            ..Self::new()
+        }
+    }
+
+    /// Latest non-yanked version from the sparse index; the CDN-served index
+    /// avoids the rate-limited crates.io API.
     pub async fn resolve_latest_version(&self, crate_name: &str) -> Result<String> {
+        let versions = self.fetch_index_versions(crate_name).await?;
+        select_latest(&versions, self.include_prereleases).ok_or_else(|| {
+            AiDocsError::Other(format!(
+                "sparse index has no non-yanked version of '{crate_name}' ({}/{})",
+                self.index_url,
+                index_path(crate_name)
+            ))
+        })
+    }
+
+    /// Every version of `crate_name` listed in the sparse index.
+    pub async fn fetch_index_versions(&self, crate_name: &str) -> Result<Vec<IndexVersion>> {
+        let url = format!("{}/{}", self.index_url, index_path(crate_name));
+        let body = self.fetch_page(&url).await?;
+        Ok(parse_index_versions(&body))
+    }
+
+    /// crates.io facts (license, MSRV, yanked, ...) for the exact `version`.
+    pub async fn fetch_crate_metadata(
+        &self,
+        crate_name: &str,
+        version: &str,
+    ) -> Result<CratesIoMetadata> {
         let url = format!("https://crates.io/api/v1/crates/{crate_name}");
+        crates_io_api_slot().await;
+        let body = self.get_bytes(&url).await?;
+        let (metadata, found) = parse_crate_response(&body, version).map_err(|e| {
+            AiDocsError::Other(format!("invalid crates.io response for {url}: {e}"))
+        })?;
+        if found {
+            return Ok(metadata);
+        }
+
+        let url = format!("https://crates.io/api/v1/crates/{crate_name}/{version}");
+        crates_io_api_slot().await;
+        let body = self.get_bytes(&url).await?;
+        apply_version_response(metadata, &body)
+            .map_err(|e| AiDocsError::Other(format!("invalid crates.io response for {url}: {e}")))
+    }
+
+    pub async fn fetch_api_markdown(
+        &self,
+        crate_name: &str,
+        version: &str,
+        max_file_size_kb: usize,
+        repo_examples: &[Example],
+    ) -> Result<DocsRsArtifact> {
+        let docsrs_input_url = format!("https://docs.rs/crate/{crate_name}/{version}");
+        let response = self.send_with_retry(&docsrs_input_url).await?;
+        if !response.status().is_success() {
+            return Err(AiDocsError::HttpStatus {
+                url: docsrs_input_url,
+                status: response.status().as_u16(),
+            });
+        }
+
+        let html = response.text().await?;
+        check_docsrs_build(&docsrs_input_url, &html)?;
+        let markdown = render_docsrs_markdown(
+            crate_name,
+            version,
+            &html,
+            repo_examples,
+            example_budget_bytes(max_file_size_kb),
+        );
+
+        Ok(DocsRsArtifact {
+            source_kind: "docsrs",
+            markdown,
+            pages: vec![CrawledPage {
+                url: docsrs_input_url.clone(),
+                artifact_path: ROOT_ARTIFACT_PATH.to_string(),
+            }],
+            docsrs_input_url,
+            module_files: Vec::new(),
+        })
+    }
+
+    /// Crawl rustdoc pages breadth-first from the crate root and group them into
+    /// one Markdown file per module. Only a failure on the root page is an error;
+    /// other pages that cannot be fetched are skipped.
+    pub async fn crawl_api_markdown(
+        &self,
+        crate_name: &str,
+        version: &str,
+        max_file_size_kb: usize,
+        options: &CrawlOptions,
+        repo_examples: &[Example],
+    ) -> Result<DocsRsArtifact> {
+        let root_path = rustdoc_root_path(crate_name, version);
+        let root_page = format!("{root_path}index.html");
+        let include = compile_module_patterns(&options.include_modules);
+        let exclude = compile_module_patterns(&options.exclude_modules);
+
+        let mut queue = VecDeque::from([(root_page.clone(), 0_usize)]);
+        let mut seen = HashSet::from([root_page.clone()]);
+        let used_seeds = used_module_paths(&options.used_paths)
+            .into_iter()
+            .filter(|module| module_allowed(module, &include, &exclude))
+            .map(|module| format!("{root_path}{}/index.html", module.replace("::", "/")));
+        for seed in used_seeds.chain(include_seed_pages(&root_path, &options.include_modules)) {
+            if seen.insert(seed.clone()) {
+                queue.push_back((seed, 1));
+            }
+        }
+
+        let mut modules: Vec<(String, Vec<(String, String)>)> = Vec::new();
+        let mut fetched_pages = 0;
+
+        while let Some((path, depth)) = queue.pop_front() {
+            if fetched_pages >= options.max_pages {
+                debug!("docs.rs crawl for {crate_name}@{version} hit the page budget");
+                break;
+            }
+
+            let url = format!("{DOCSRS_ORIGIN}{path}");
+            let html = match self.fetch_page(&url).await {
+                Ok(html) if path == root_page => {
+                    check_docsrs_build(&url, &html)?;
+                    html
+                }
+                Ok(html) => html,
+                Err(e) if path == root_page => return Err(e),
+                Err(e) => {
+                    debug!("skipping docs.rs page {url}: {e}");
+                    continue;
+                }
+            };
+            fetched_pages += 1;
+
+            let Some(page) = classify_rustdoc_page(&root_path, &path) else {
+                continue;
+            };
+            let markdown = select_to_markdown(&html, &["#main-content"], &url).unwrap_or_default();
+            match modules.iter_mut().find(|(m, _)| *m == page.module) {
+                Some((_, pages)) => pages.push((url.clone(), markdown)),
+                None => modules.push((page.module.clone(), vec![(url.clone(), markdown)])),
+            }
+
+            if depth >= options.max_depth {
+                continue;
+            }
+
+            let mut candidates: Vec<(String, RustdocPage)> =
+                extract_docs_links(crate_name, version, &url, &html)
+                    .into_iter()
+                    .map(|link| {
+                        if link.ends_with('/') {
+                            format!("{link}index.html")
+                        } else {
+                            link
+                        }
+                    })
+                    .filter_map(|link| {
+                        let page = classify_rustdoc_page(&root_path, &link)?;
+                        Some((link, page))
+                    })
+                    .filter(|(_, page)| module_allowed(&page.module, &include, &exclude))
+                    .collect();
+            // Pages the project uses first, then module pages: they unlock the rest
+            // of the tree within the budget.
+            candidates.sort_by_key(|(link, page)| {
+                let item_path = match rustdoc_item_name(link) {
+                    Some(name) if !page.is_module_index && !page.module.is_empty() => {
+                        format!("{}::{name}", page.module)
+                    }
+                    Some(name) if !page.is_module_index => name,
+                    _ => page.module.clone(),
+                };
+                (
+                    !covers(&options.used_paths, &item_path),
+                    !page.is_module_index,
+                )
+            });
+
+            for (link, _) in candidates {
+                if seen.insert(link.clone()) {
+                    queue.push_back((link, depth + 1));
+                }
+            }
+        }
+
+        let docsrs_input_url = format!("{DOCSRS_ORIGIN}/crate/{crate_name}/{version}");
+        let mut pages = Vec::new();
+        let mut module_files = Vec::new();
+        let mut root_markdown = String::new();
+        let mut candidates = Vec::new();
+        let mut public_items = Vec::new();
+
+        for (module, module_pages) in &modules {
+            let artifact_path = module_artifact_path(module);
+            for (url, markdown) in module_pages {
+                pages.push(CrawledPage {
+                    url: url.clone(),
+                    artifact_path: artifact_path.clone(),
+                });
+                let item = rustdoc_item_name(url);
+                if module.is_empty() {
+                    let origin = match &item {
+                        Some(name) if !url.ends_with("/index.html") => format!("`{name}` docs"),
+                        _ => "crate docs".to_string(),
+                    };
+                    candidates.extend(doc_code_blocks(&origin, markdown));
+                }
+                public_items.extend(item);
+            }
+            let rendered = render_module_markdown(crate_name, version, module, module_pages);
+            if module.is_empty() {
+                root_markdown = rendered;
+            } else {
+                module_files.push((artifact_path, rendered));
+            }
+        }
+
+        candidates.extend(repo_examples.iter().cloned());
+        let examples = select_examples(
+            candidates,
+            &public_items,
+            example_budget_bytes(max_file_size_kb),
+        );
+        let examples_section = render_examples_section(&examples);
+        if !examples_section.is_empty() {
+            root_markdown.push('\n');
+            root_markdown.push_str(&examples_section);
+        }
+        root_markdown.push_str(&render_module_index(&module_files, fetched_pages));
+
+        let module_files: Vec<ModuleFile> = module_files
+            .into_iter()
+            .map(|(path, markdown)| ModuleFile { path, markdown })
+            .collect();
+
+        Ok(DocsRsArtifact {
+            source_kind: "docsrs",
+            markdown: root_markdown,
+            docsrs_input_url,
+            module_files,
+            pages,
+        })
+    }
+
+    /// Feature flags from the docs.rs features page, with the page URL. Used
+    /// when the crate's `Cargo.toml` is not available locally.
+    pub async fn fetch_features(
+        &self,
+        crate_name: &str,
+        version: &str,
+    ) -> Result<(String, Vec<FeatureInfo>)> {
+        let url = format!("{DOCSRS_ORIGIN}/crate/{crate_name}/{version}/features");
         let response = self.send_with_retry(&url).await?;
         if !response.status().is_success() {
             return Err(AiDocsError::HttpStatus {
@@ -54,43 +373,87 @@ impl LatestDocsFetcher {
             });
         }
 
-        let body: CratesIoResponse = response.json().await?;
-        body.crate_data
-            .max_stable_version
-            .filter(|v| !v.trim().is_empty())
-            .or(body.crate_data.max_version)
-            .ok_or_else(|| {
-                AiDocsError::Other(format!(
-                    "crates.io response for '{crate_name}' has no max version"
-                ))
-            })
+        let html = response.text().await?;
+        Ok((url, parse_docsrs_features_page(&html)))
     }
 
-    pub async fn fetch_api_markdown(
+    /// Render `API.md` from rustdoc JSON: either a locally built file or the
+    /// build docs.rs publishes for the crate version.
+    pub async fn fetch_rustdoc_api_markdown(
         &self,
         crate_name: &str,
         version: &str,
         max_file_size_kb: usize,
+        local_json: Option<&Path>,
+        repo_examples: &[Example],
+        used_paths: &[String],
     ) -> Result<DocsRsArtifact> {
-        let docsrs_input_url = format!("https://docs.rs/crate/{crate_name}/{version}");
-        let response = self.send_with_retry(&docsrs_input_url).await?;
+        let (input_url, doc) = match local_json {
+            Some(path) => {
+                let doc = decode_rustdoc_json(&std::fs::read(path)?)?;
+                check_rustdoc_json_version(&path.display().to_string(), &doc, version)?;
+                (path.display().to_string(), doc)
+            }
+            None => (
+                rustdoc_json_url(crate_name, version),
+                self.fetch_rustdoc_json(crate_name, version).await?,
+            ),
+        };
+
+        let markdown = render_api_markdown(
+            crate_name,
+            version,
+            &doc,
+            repo_examples,
+            example_budget_bytes(max_file_size_kb),
+            used_paths,
+        )?;
+
+        Ok(DocsRsArtifact {
+            source_kind: "rustdoc_json",
+            markdown,
+            pages: vec![CrawledPage {
+                url: input_url.clone(),
+                artifact_path: ROOT_ARTIFACT_PATH.to_string(),
+            }],
+            docsrs_input_url: input_url,
+            module_files: Vec::new(),
+        })
+    }
+
+    /// The rustdoc JSON docs.rs publishes for a crate version.
+    pub async fn fetch_rustdoc_json(
+        &self,
+        crate_name: &str,
+        version: &str,
+    ) -> Result<serde_json::Value> {
+        let bytes = self
+            .get_bytes(&rustdoc_json_url(crate_name, version))
+            .await?;
+        decode_rustdoc_json(&bytes)
+    }
+
+    async fn fetch_page(&self, url: &str) -> Result<String> {
+        let response = self.send_with_retry(url).await?;
         if !response.status().is_success() {
             return Err(AiDocsError::HttpStatus {
-                url: docsrs_input_url,
+                url: url.to_string(),
                 status: response.status().as_u16(),
             });
         }
+        Ok(response.text().await?)
+    }
 
-        let html = response.text().await?;
-        let markdown = render_docsrs_markdown(crate_name, version, &html);
-        let (markdown, truncated) = crate::storage::truncate_if_needed(&markdown, max_file_size_kb);
- 
-         Ok(DocsRsArtifact {
-             markdown,
-             docsrs_input_url: format!("https://docs.rs/crate/{crate_name}/{version}"),
-             truncated,
-         })
-     }
+    async fn get_bytes(&self, url: &str) -> Result<Vec<u8>> {
+        let response = self.send_with_retry(url).await?;
+        if !response.status().is_success() {
+            return Err(AiDocsError::HttpStatus {
+                url: url.to_string(),
+                status: response.status().as_u16(),
+            });
+        }
+        Ok(response.bytes().await?.to_vec())
+    }
 
     async fn send_with_retry(&self, url: &str) -> Result<reqwest::Response> {
         let mut backoff_ms = RETRY_BASE_BACKOFF_MS;
@@ -143,6 +506,20 @@ impl LatestDocsFetcher {
     }
 }
 
+/// Wait for this request's turn under the crates.io rate limit.
+async fn crates_io_api_slot() {
+    let slot = {
+        let mut next = CRATES_IO_NEXT_SLOT
+            .lock()
+            .unwrap_or_else(std::sync::PoisonError::into_inner);
+        let now = Instant::now();
+        let slot = next.map_or(now, |next| next.max(now));
+        *next = Some(slot + CRATES_IO_API_INTERVAL);
+        slot
+    };
+    tokio::time::sleep_until(slot.into()).await;
+}
+
 pub fn is_docsrs_fallback_eligible(error: &AiDocsError) -> bool {
     match error {
         AiDocsError::HttpStatus { status, .. } => {
@@ -150,16 +527,131 @@ pub fn is_docsrs_fallback_eligible(error: &AiDocsError) -> bool {
                 || *status == StatusCode::TOO_MANY_REQUESTS.as_u16()
                 || (*status >= 500 && *status < 600)
         }
-        AiDocsError::Http(_) | AiDocsError::Fetch { .. } => true,
+        AiDocsError::Http(_)
+        | AiDocsError::Fetch { .. }
+        | AiDocsError::DocsRsBuildFailed { .. } => true,
         _ => false,
     }
 }
 
-fn render_docsrs_markdown(crate_name: &str, version: &str, html: &str) -> String {
+/// Reason code recorded in crate metadata when `error` sends a crate to the GitHub fallback.
+pub fn docsrs_fallback_reason(error: &AiDocsError) -> &'static str {
+    match error {
+        AiDocsError::DocsRsBuildFailed { .. } => "docsrs_build_failed",
+        _ => "docsrs_unavailable",
+    }
+}
+
+/// Why docs.rs has no documentation for a version, judged from the notice
+/// (`.warning` / `.info` block) on a page it served with `200 OK`: `"build failed"`
+/// or `"not yet built"`. Rustdoc pages (`#main-content`) never count, whatever
+/// their docs say.
+pub fn docsrs_build_problem(page: &str) -> Option<&'static str> {
+    let document = Html::parse_document(page);
+    let rustdoc = Selector::parse("#main-content").expect("valid main content selector");
+    if document.select(&rustdoc).next().is_some() {
+        return None;
+    }
+    let notices = Selector::parse(".warning, .info").expect("valid notice selector");
+    let patterns = BuildNotices::new();
+    document.select(&notices).find_map(|notice| {
+        let text = notice.text().collect::<Vec<_>>().join(" ");
+        patterns.problem(&text.split_whitespace().collect::<Vec<_>>().join(" "))
+    })
+}
+
+/// Lines at the top of a saved `API.md` where a build notice can appear.
+const SAVED_NOTICE_LINES: usize = 40;
+
+/// Like [`docsrs_build_problem`] for an `API.md` saved from such a page, where
+/// the notice survives only as a line of text near the top.
+pub fn saved_build_problem(markdown: &str) -> Option<&'static str> {
+    let patterns = BuildNotices::new();
+    markdown.lines().take(SAVED_NOTICE_LINES).find_map(|line| {
+        patterns.problem(line.trim_start_matches(|c: char| c == '>' || c.is_whitespace()))
+    })
+}
+
+struct BuildNotices {
+    failed: Regex,
+    pending: Regex,
+}
+
+impl BuildNotices {
+    fn new() -> Self {
+        Self {
+            failed: Regex::new(r"(?i)^docs\.rs failed to build \S+")
+                .expect("valid failed notice regex"),
+            pending: Regex::new(
+                r"(?i)^(?:\S+[- ]v?\d+\.\d+\.\d+\S* (?:is currently being built|is queued for build|has not been built yet)|build is in progress\b)",
+            )
+            .expect("valid pending notice regex"),
+        }
+    }
+
+    /// Classify one docs.rs build notice, which must start with the notice itself
+    /// (`docs.rs failed to build demo-0.1.0`, `demo-0.2.0 is currently being built`).
+    fn problem(&self, text: &str) -> Option<&'static str> {
+        let text = text.trim();
+        if self.failed.is_match(text) {
+            Some("build failed")
+        } else if self.pending.is_match(text) {
+            Some("not yet built")
+        } else {
+            None
+        }
+    }
+}
+
+fn check_docsrs_build(url: &str, html: &str) -> Result<()> {
+    match docsrs_build_problem(html) {
+        Some(detail) => Err(AiDocsError::DocsRsBuildFailed {
+            url: url.to_string(),
+            detail: detail.to_string(),
+        }),
+        None => Ok(()),
+    }
+}
+
+/// docs.rs URL of the gzipped rustdoc JSON for a crate version.
+fn rustdoc_json_url(crate_name: &str, version: &str) -> String {
+    format!("{DOCSRS_ORIGIN}/crate/{crate_name}/{version}/json.gz")
+}
+
+/// Parse rustdoc JSON, gunzipping it first when it carries the gzip magic bytes.
+fn decode_rustdoc_json(bytes: &[u8]) -> Result<serde_json::Value> {
+    let parsed = if bytes.starts_with(&[0x1f, 0x8b]) {
+        let mut json = Vec::new();
+        GzDecoder::new(bytes).read_to_end(&mut json)?;
+        serde_json::from_slice(&json)
+    } else {
+        serde_json::from_slice(bytes)
+    };
+    parsed.map_err(|e| AiDocsError::RustdocJson(format!("invalid rustdoc JSON: {e}")))
+}
+
+/// Reject a local rustdoc JSON built for another version than the one being
+/// synced, so a stale file is never published as that version's `API.md`.
+fn check_rustdoc_json_version(path: &str, doc: &serde_json::Value, version: &str) -> Result<()> {
+    match doc.get("crate_version").and_then(serde_json::Value::as_str) {
+        Some(found) if found != version => Err(AiDocsError::RustdocJson(format!(
+            "{path} documents version {found}, expected {version}"
+        ))),
+        _ => Ok(()),
+    }
+}
+
+fn render_docsrs_markdown(
+    crate_name: &str,
+    version: &str,
+    html: &str,
+    repo_examples: &[Example],
+    example_budget_bytes: usize,
+) -> String {
     let canonical_base = format!("https://docs.rs/{crate_name}/{version}");
     let input_url = format!("https://docs.rs/crate/{crate_name}/{version}");
     let title = extract_title(html).unwrap_or_else(|| format!("{crate_name} {version}"));
-    let links = extract_docs_links(crate_name, version, html);
+    let links = extract_docs_links(crate_name, version, &input_url, html);
     let main_content = extract_main_content(crate_name, version, html);
 
     let mut out = String::new();
@@ -180,14 +672,16 @@ fn render_docsrs_markdown(crate_name: &str, version: &str, html: &str) -> String
     out.push_str(&format!(
         "- [rustdoc root]({canonical_base}/{crate_name}/)\n"
     ));
-    for link in links.into_iter().take(20) {
+    for link in links.iter().take(20) {
         out.push_str(&format!("- [{link}](https://docs.rs{link})\n"));
     }
+    out.push('\n');
 
-    out.push_str("\n## Example\n\n");
-    out.push_str("```rust\n");
-    out.push_str(&format!("use {crate_name} as _;\n"));
-    out.push_str("```\n\n");
+    let mut candidates = doc_code_blocks("crate docs", &main_content);
+    candidates.extend(repo_examples.iter().cloned());
+    let public_items: Vec<String> = links.iter().filter_map(|l| rustdoc_item_name(l)).collect();
+    let examples = select_examples(candidates, &public_items, example_budget_bytes);
+    out.push_str(&render_examples_section(&examples));
 
     out.push_str("---\n");
     out.push_str(&format!("Source: {input_url}\n"));
@@ -203,158 +697,208 @@ fn extract_title(html: &str) -> Option<String> {
 
 fn extract_main_content(crate_name: &str, version: &str, html: &str) -> String {
     // docs.rs usually has the main content in <div id="main-content"> or <div class="docblock">
-    let mut content = String::new();
+    let page_url = format!("https://docs.rs/{crate_name}/{version}/{crate_name}/");
+    crate::processor::html::select_to_markdown(html, &["#main-content", ".docblock"], &page_url)
+        .unwrap_or_default()
+}
 
-    if let Some(start) = html.find("<div id=\"main-content\"") {
-        let rest = &html[start..];
-        if let Some(end) = find_closing_div(rest) {
-            content = strip_html_tags(crate_name, version, &rest[..end]);
-        }
-    } else if let Some(start) = html.find("<div class=\"docblock\"") {
-        let rest = &html[start..];
-        if let Some(end) = find_closing_div(rest) {
-            content = strip_html_tags(crate_name, version, &rest[..end]);
+fn rustdoc_root_path(crate_name: &str, version: &str) -> String {
+    let ident = crate_name.replace('-', "_");
+    format!("/{crate_name}/{version}/{ident}/")
+}
+
+/// Map a docs.rs path under the rustdoc root to its module path.
+///
+/// Returns `None` for anything that is not a module index or an item page
+/// (sidebar helpers, `all.html`, source views).
+fn classify_rustdoc_page(root_path: &str, path: &str) -> Option<RustdocPage> {
+    const ITEM_KINDS: &[&str] = &[
+        "struct",
+        "enum",
+        "trait",
+        "fn",
+        "macro",
+        "type",
+        "union",
+        "constant",
+        "static",
+        "attr",
+        "derive",
+        "traitalias",
+    ];
+
+    let rest = path.strip_prefix(root_path)?;
+    let (dir, file) = rest.rsplit_once('/').unwrap_or(("", rest));
+    let segments: Vec<&str> = dir.split('/').filter(|s| !s.is_empty()).collect();
+    let is_ident = |s: &str| !s.is_empty() && s.chars().all(|c| c.is_alphanumeric() || c == '_');
+    if !segments.iter().all(|s| is_ident(s)) {
+        return None;
+    }
+
+    let is_module_index = file == "index.html" || file.is_empty();
+    if !is_module_index {
+        let (kind, rest) = file.split_once('.')?;
+        let name = rest.strip_suffix(".html")?;
+        if !ITEM_KINDS.contains(&kind) || !is_ident(name) {
+            return None;
         }
     }
 
-    content.trim().to_string()
+    Some(RustdocPage {
+        module: segments.join("::"),
+        is_module_index,
+    })
 }
 
-fn find_closing_div(html: &str) -> Option<usize> {
-    let mut depth = 0;
-    let mut i = 0;
-    let bytes = html.as_bytes();
-    
-    while i < bytes.len() {
-        if bytes[i..].starts_with(b"<div") {
-            // Check if it's a real div tag start (followed by space or >)
-            let next_char = bytes.get(i + 4);
-            if next_char.is_none() || matches!(next_char, Some(b' ') | Some(b'>')) {
-                depth += 1;
-                i += 4;
-                continue;
+/// Compile `sync::*`-style glob patterns into anchored regexes.
+fn compile_module_patterns(patterns: &[String]) -> Vec<Regex> {
+    patterns
+        .iter()
+        .filter_map(|pattern| {
+            let escaped = regex::escape(pattern.trim()).replace(r"\*", ".*");
+            Regex::new(&format!("^{escaped}$")).ok()
+        })
+        .collect()
+}
+
+fn module_allowed(module: &str, include: &[Regex], exclude: &[Regex]) -> bool {
+    if module.is_empty() {
+        return true;
+    }
+    if exclude.iter().any(|re| re.is_match(module)) {
+        return false;
+    }
+    include.is_empty() || include.iter().any(|re| re.is_match(module))
+}
+
+/// Modules that hold the used paths: every parent with a lowercase name, so
+/// `sync::mpsc::Sender` yields `sync` and `sync::mpsc`.
+fn used_module_paths(used: &[String]) -> Vec<String> {
+    let mut modules: Vec<String> = Vec::new();
+    for path in used {
+        let segments: Vec<&str> = path.split("::").collect();
+        for end in 1..segments.len() {
+            if !segments[end - 1].starts_with(|c: char| c.is_ascii_lowercase()) {
+                break;
             }
-        }
-        if bytes[i..].starts_with(b"</div>") {
-            depth -= 1;
-            i += 6;
-            if depth == 0 {
-                return Some(i);
+            let module = segments[..end].join("::");
+            if !modules.contains(&module) {
+                modules.push(module);
             }
-            continue;
         }
-        i += 1;
     }
-    None
+    modules
 }
 
-fn strip_html_tags(_crate_name: &str, _version: &str, html: &str) -> String {
-    let mut out = String::new();
-    let mut in_tag = false;
-    let mut tag_buffer = String::new();
-
-    let bytes = html.as_bytes();
-    let mut i = 0;
-    while i < bytes.len() {
-        if bytes[i..].starts_with(b"<pre") {
-            out.push_str("\n```rust\n");
-            while i < bytes.len() && bytes[i] != b'>' {
-                i += 1;
+/// Module index pages for the literal part of each include pattern, so that
+/// deeply nested includes are reachable without crawling their parents.
+fn include_seed_pages(root_path: &str, include: &[String]) -> Vec<String> {
+    include
+        .iter()
+        .filter_map(|pattern| {
+            let literal = pattern.split('*').next()?.trim().trim_end_matches(':');
+            if literal.is_empty() {
+                return None;
             }
-            if i < bytes.len() { i += 1; }
-            continue;
-        }
-        if bytes[i..].starts_with(b"</pre>") {
-            out.push_str("\n```\n");
-            i += 6;
-            continue;
-        }
+            Some(format!(
+                "{root_path}{}/index.html",
+                literal.replace("::", "/")
+            ))
+        })
+        .collect()
+}
 
-        if bytes[i] == b'<' {
-            in_tag = true;
-            tag_buffer.clear();
-        } else if bytes[i] == b'>' {
-            in_tag = false;
-            let normalized_tag = tag_buffer.to_lowercase();
-
-            if normalized_tag.starts_with("/p")
-                || normalized_tag.starts_with("/h")
-                || normalized_tag.starts_with("/li")
-                || normalized_tag.starts_with("/div")
-                || normalized_tag.starts_with("br")
-            {
-                out.push('\n');
-            }
-        } else if in_tag {
-            tag_buffer.push(bytes[i] as char);
-        } else if !in_tag {
-            // Handle common entities
-            if bytes[i..].starts_with(b"&nbsp;") {
-                out.push(' ');
-                i += 5;
-            } else if bytes[i..].starts_with(b"&lt;") {
-                out.push('<');
-                i += 3;
-            } else if bytes[i..].starts_with(b"&gt;") {
-                out.push('>');
-                i += 3;
-            } else if bytes[i..].starts_with(b"&amp;") {
-                out.push('&');
-                i += 4;
-            } else if bytes[i..].starts_with(b"&quot;") {
-                out.push('"');
-                i += 5;
-            } else {
-                out.push(bytes[i] as char);
-            }
-        }
-        i += 1;
+fn module_artifact_path(module: &str) -> String {
+    if module.is_empty() {
+        ROOT_ARTIFACT_PATH.to_string()
+    } else {
+        format!("API__{}.md", module.replace("::", "__"))
     }
-
-    // Unescape some basic entities and clean up whitespace
-    let result = out.replace("&lt;", "<")
-        .replace("&gt;", ">")
-        .replace("&amp;", "&")
-        .replace("&quot;", "\"")
-        .replace("&#39;", "'");
-    
-    clean_markdown_whitespace(&result)
 }
 
-fn clean_markdown_whitespace(s: &str) -> String {
+fn render_module_markdown(
+    crate_name: &str,
+    version: &str,
+    module: &str,
+    pages: &[(String, String)],
+) -> String {
     let mut out = String::new();
-    let mut last_was_empty = false;
-    for line in s.lines() {
-        let trimmed = line.trim();
-        if trimmed.is_empty() {
-            if !last_was_empty {
-                out.push_str("\n");
-                last_was_empty = true;
-            }
-        } else {
-            out.push_str(trimmed);
-            out.push('\n');
-            last_was_empty = false;
-        }
+    if module.is_empty() {
+        out.push_str(&format!("# {crate_name}@{version}\n\n"));
+        out.push_str("## Overview\n\n");
+        out.push_str(&format!(
+            "Generated from docs.rs rustdoc pages for `{crate_name}` `{version}`.\n\n"
+        ));
+    } else {
+        out.push_str(&format!("# {crate_name}::{module}@{version}\n\n"));
+    }
+
+    let sections: Vec<String> = pages
+        .iter()
+        .map(|(url, markdown)| format!("{}\n\nSource: {url}", markdown.trim()))
+        .collect();
+    out.push_str(&sections.join("\n\n---\n\n"));
+    out.push('\n');
+    out
+}
+
+/// Item or module name addressed by a rustdoc page (`struct.Counter.html` ->
+/// `Counter`, `sync/index.html` -> `sync`).
+fn rustdoc_item_name(path: &str) -> Option<String> {
+    let path = path.trim_end_matches("index.html");
+    if path.ends_with('/') {
+        return path
+            .trim_end_matches('/')
+            .rsplit('/')
+            .next()
+            .filter(|s| !s.is_empty())
+            .map(str::to_string);
+    }
+    let file = path.rsplit('/').next()?;
+    let mut parts = file.split('.');
+    match (parts.next(), parts.next(), parts.next(), parts.next()) {
+        (Some(_kind), Some(name), Some("html"), None) => Some(name.to_string()),
+        _ => None,
+    }
+}
+
+fn render_module_index(module_files: &[(String, String)], fetched_pages: usize) -> String {
+    let mut out = format!("\n## Crawled modules\n\nPages fetched: {fetched_pages}\n\n");
+    if module_files.is_empty() {
+        out.push_str("- _No module pages within the crawl budget._\n");
     }
-    out.trim().to_string()
+    for (path, _) in module_files {
+        let module = path
+            .trim_start_matches("API__")
+            .trim_end_matches(".md")
+            .replace("__", "::");
+        out.push_str(&format!("- [{module}]({path})\n"));
+    }
+    out
 }
 
-fn extract_docs_links(crate_name: &str, version: &str, html: &str) -> Vec<String> {
-    let needle = format!("href=\"/{crate_name}/{version}/");
-    let mut links = Vec::new();
-    let mut idx = 0;
-    while let Some(found) = html[idx..].find(&needle) {
-        let start = idx + found + "href=\"".len();
-        let rest = &html[start..];
-        let Some(end) = rest.find('"') else {
-            break;
+fn extract_docs_links(crate_name: &str, version: &str, page_url: &str, html: &str) -> Vec<String> {
+    let prefix = format!("/{crate_name}/{version}/");
+    let source_prefix = format!("{prefix}src/");
+    let document = Html::parse_document(html);
+    let selector = Selector::parse("a[href]").expect("valid link selector");
+
+    let mut links: Vec<String> = Vec::new();
+    for anchor in document.select(&selector) {
+        let Some(href) = anchor.value().attr("href") else {
+            continue;
+        };
+        let absolute = absolutize_url(page_url, href);
+        let Some(path) = absolute.strip_prefix(DOCSRS_ORIGIN) else {
+            continue;
         };
-        let href = &rest[..end];
-        if !links.iter().any(|v| v == href) {
-            links.push(href.to_string());
+        let path = path.split('#').next().unwrap_or_default();
+        if !path.starts_with(&prefix) || path.starts_with(&source_prefix) {
+            continue;
+        }
+        if !links.iter().any(|v| v == path) {
+            links.push(path.to_string());
         }
-        idx = start + end;
     }
     links
 }
@@ -362,9 +906,72 @@ fn extract_docs_links(crate_name: &str, version: &str, html: &str) -> Vec<String
 #[cfg(test)]
 mod tests {
     use super::{
-        extract_docs_links, extract_title, is_docsrs_fallback_eligible,
+        check_rustdoc_json_version, decode_rustdoc_json, docsrs_build_problem, extract_docs_links,
+        extract_title, is_docsrs_fallback_eligible, render_docsrs_markdown, rustdoc_item_name,
+        saved_build_problem, SAVED_NOTICE_LINES,
     };
     use crate::error::AiDocsError;
+    use crate::processor::examples::example_file;
+
+    #[test]
+    fn docsrs_markdown_uses_real_examples_instead_of_placeholder() {
+        let html = include_str!("../../tests/fixtures/docsrs/serde-1.0.210-root.html");
+        let repo = [example_file(
+            "examples/roundtrip.rs",
+            "fn main() { let _ = serde::de::value::Error::custom; }",
+        )];
+        let md = render_docsrs_markdown("serde", "1.0.210", html, &repo, 20 * 1024);
+        assert!(!md.contains("use serde as _;"));
+        assert!(md.contains("## Examples\n\n"));
+        assert!(md.contains("From crate docs:\n\n```rust\nuse serde::{Deserialize, Serialize};"));
+        assert!(md.contains("From `examples/roundtrip.rs`:"));
+
+        let bare = "<html><head><title>x</title></head><body><div id=\"main-content\"><p>No code.</p></div></body></html>";
+        let md = render_docsrs_markdown("x", "1.0.0", bare, &[], 20 * 1024);
+        assert!(!md.contains("## Example"));
+    }
+
+    #[test]
+    fn names_items_from_rustdoc_paths() {
+        assert_eq!(
+            rustdoc_item_name("/tokio/1.44.0/tokio/sync/struct.Mutex.html").as_deref(),
+            Some("Mutex")
+        );
+        assert_eq!(
+            rustdoc_item_name("/tokio/1.44.0/tokio/sync/index.html").as_deref(),
+            Some("sync")
+        );
+        assert_eq!(rustdoc_item_name("/tokio/1.44.0/tokio/all.html"), None);
+    }
+
+    #[test]
+    fn decodes_plain_and_gzipped_rustdoc_json() {
+        use std::io::Write;
+
+        let json = br#"{"root": 0, "format_version": 57, "index": {}}"#;
+        let mut encoder = flate2::write::GzEncoder::new(Vec::new(), flate2::Compression::default());
+        encoder.write_all(json).expect("gzip write");
+        let gz = encoder.finish().expect("gzip finish");
+
+        assert_eq!(decode_rustdoc_json(json).unwrap()["format_version"], 57);
+        assert_eq!(decode_rustdoc_json(&gz).unwrap()["format_version"], 57);
+        assert!(matches!(
+            decode_rustdoc_json(b"<html>"),
+            Err(AiDocsError::RustdocJson(_))
+        ));
+    }
+
+    #[test]
+    fn rejects_local_rustdoc_json_for_another_version() {
+        let doc = serde_json::json!({"root": 0, "crate_version": "1.2.0", "index": {}});
+        assert!(check_rustdoc_json_version("demo.json", &doc, "1.2.0").is_ok());
+        assert!(matches!(
+            check_rustdoc_json_version("demo.json", &doc, "1.3.0"),
+            Err(AiDocsError::RustdocJson(msg)) if msg.contains("documents version 1.2.0")
+        ));
+        let unversioned = serde_json::json!({"root": 0, "crate_version": null});
+        assert!(check_rustdoc_json_version("demo.json", &unversioned, "1.3.0").is_ok());
+    }
 
     #[test]
     fn extracts_title() {
@@ -375,7 +982,7 @@ mod tests {
     #[test]
     fn extracts_unique_docs_links() {
         let html = r#"<a href="/serde/1.0.0/serde/">A</a><a href="/serde/1.0.0/serde/">B</a>"#;
-        let links = extract_docs_links("serde", "1.0.0", html);
+        let links = extract_docs_links("serde", "1.0.0", "https://docs.rs/crate/serde/1.0.0", html);
         assert_eq!(links, vec!["/serde/1.0.0/serde/"]);
     }
 
@@ -397,6 +1004,41 @@ mod tests {
             url: "u".to_string(),
             status: 401,
         }));
+        assert!(is_docsrs_fallback_eligible(
+            &AiDocsError::DocsRsBuildFailed {
+                url: "u".to_string(),
+                detail: "build failed".to_string(),
+            }
+        ));
+    }
+
+    #[test]
+    fn detects_docsrs_build_failure_pages() {
+        let failed = r#"<div class="warning">docs.rs failed to build demo-0.1.0<br>Please check the <a href="/crate/demo/0.1.0/builds">build logs</a> for more information.</div>"#;
+        assert_eq!(docsrs_build_problem(failed), Some("build failed"));
+
+        let queued = r#"<div class="info">demo-0.2.0 is currently being built. Documentation will be available soon.</div>"#;
+        assert_eq!(docsrs_build_problem(queued), Some("not yet built"));
+
+        let html = include_str!("../../tests/fixtures/docsrs/serde-1.0.210-root.html");
+        assert_eq!(docsrs_build_problem(html), None);
+    }
+
+    #[test]
+    fn ignores_build_phrases_outside_docsrs_notices() {
+        let prose = "<p>The build is in progress while the queue has not been built yet.</p>";
+        assert_eq!(docsrs_build_problem(prose), None);
+
+        let rustdoc = r#"<section id="main-content"><div class="docblock"><div class="warning">docs.rs failed to build older releases of this crate.</div></div></section>"#;
+        assert_eq!(docsrs_build_problem(rustdoc), None);
+
+        let api = "# demo@1.0.0\n\nNote: a build is in progress when `Builder::run` is called.\n";
+        assert_eq!(saved_build_problem(api), None);
+        let saved =
+            "# demo@0.1.0\n\ndocs.rs failed to build demo-0.1.0 Please check the build logs.\n";
+        assert_eq!(saved_build_problem(saved), Some("build failed"));
+        let deep = format!("{}{saved}", "text\n".repeat(SAVED_NOTICE_LINES));
+        assert_eq!(saved_build_problem(&deep), None);
     }
 
     #[test]
@@ -410,34 +1052,124 @@ mod tests {
     #[test]
     fn test_extract_main_content_simple() {
         let html = r#"<div id="main-content"><h1>Hello</h1><p>World</p></div>"#;
-        assert_eq!(super::extract_main_content("test", "0.1.0", html), "Hello\nWorld");
+        assert_eq!(
+            super::extract_main_content("test", "0.1.0", html),
+            "# Hello\n\nWorld"
+        );
     }
 
     #[test]
     fn test_extract_main_content_with_code() {
-        let html = r#"<div class="docblock"><pre>pub fn test() {}</pre></div>"#;
+        let html =
+            r#"<div class="docblock"><pre class="rust"><code>pub fn test() {}</code></pre></div>"#;
         let content = super::extract_main_content("test", "0.1.0", html);
-        assert!(content.contains("```rust"));
-        assert!(content.contains("pub fn test() {}"));
-        assert!(content.contains("```"));
+        assert_eq!(content, "```rust\npub fn test() {}\n```");
     }
 
     #[test]
-    fn test_strip_html_tags_with_links_and_spacing() {
+    fn test_extract_main_content_with_links_and_spacing() {
         let html = r#"<div class="docblock"><h1>Title</h1><p>Para with <a href="/serde/1.0.0/serde/index.html">link</a>.</p><ul><li>Item 1</li><li>Item 2</li></ul></div>"#;
-        let content = super::strip_html_tags("serde", "1.0.0", html);
-        // Note: Our current rudimentary implementation doesn't do full link rewriting yet, 
-        // but it should at least handle the spacing and tag removal.
-        assert!(content.contains("Title"));
-        assert!(content.contains("Para with link."));
-        assert!(content.contains("Item 1"));
-        assert!(content.contains("Item 2"));
+        let content = super::extract_main_content("serde", "1.0.0", html);
+        assert_eq!(
+            content,
+            "# Title\n\nPara with [link](https://docs.rs/serde/1.0.0/serde/index.html).\n\n- Item 1\n- Item 2"
+        );
+    }
+
+    #[test]
+    fn extracts_relative_links_and_skips_sources_and_fragments() {
+        let html = r##"<a href="sync/index.html">sync</a><a href="struct.Mutex.html#method.lock">lock</a><a href="../src/tokio/lib.rs.html">src</a><a href="#modules">§</a>"##;
+        let links = super::extract_docs_links(
+            "tokio",
+            "1.44.0",
+            "https://docs.rs/tokio/1.44.0/tokio/index.html",
+            html,
+        );
+        assert_eq!(
+            links,
+            vec![
+                "/tokio/1.44.0/tokio/sync/index.html",
+                "/tokio/1.44.0/tokio/struct.Mutex.html",
+            ]
+        );
+    }
+
+    #[test]
+    fn classifies_module_and_item_pages() {
+        let root = super::rustdoc_root_path("axum-core", "0.4.0");
+        assert_eq!(root, "/axum-core/0.4.0/axum_core/");
+
+        let page =
+            super::classify_rustdoc_page(&root, "/axum-core/0.4.0/axum_core/body/index.html")
+                .expect("module page");
+        assert_eq!(page.module, "body");
+        assert!(page.is_module_index);
+
+        let item = super::classify_rustdoc_page(
+            &root,
+            "/axum-core/0.4.0/axum_core/extract/trait.FromRequest.html",
+        )
+        .expect("item page");
+        assert_eq!(item.module, "extract");
+        assert!(!item.is_module_index);
+
+        assert!(
+            super::classify_rustdoc_page(&root, "/axum-core/0.4.0/axum_core/all.html").is_none()
+        );
+    }
+
+    #[test]
+    fn module_patterns_respect_include_and_exclude() {
+        let include = super::compile_module_patterns(&["sync".to_string(), "sync::*".to_string()]);
+        let exclude = super::compile_module_patterns(&["sync::broadcast".to_string()]);
+
+        assert!(super::module_allowed("", &include, &exclude));
+        assert!(super::module_allowed("sync", &include, &exclude));
+        assert!(super::module_allowed("sync::mpsc", &include, &exclude));
+        assert!(!super::module_allowed(
+            "sync::broadcast",
+            &include,
+            &exclude
+        ));
+        assert!(!super::module_allowed("net", &include, &exclude));
+        assert!(super::module_allowed("net", &[], &exclude));
+    }
+
+    #[test]
+    fn include_patterns_seed_their_module_pages() {
+        let seeds = super::include_seed_pages(
+            "/tokio/1.44.0/tokio/",
+            &[
+                "sync::mpsc".to_string(),
+                "task::*".to_string(),
+                "*".to_string(),
+            ],
+        );
+        assert_eq!(
+            seeds,
+            vec![
+                "/tokio/1.44.0/tokio/sync/mpsc/index.html",
+                "/tokio/1.44.0/tokio/task/index.html",
+            ]
+        );
+    }
+
+    #[test]
+    fn used_paths_seed_their_parent_modules() {
+        let used = vec![
+            "sync::mpsc::Sender".to_string(),
+            "sync::Mutex".to_string(),
+            "spawn".to_string(),
+        ];
+        assert_eq!(super::used_module_paths(&used), vec!["sync", "sync::mpsc"]);
     }
 
     #[test]
-    fn test_clean_markdown_whitespace() {
-        let input = "Line 1\n\n\nLine 2\n   \nLine 3\n";
-        let expected = "Line 1\n\nLine 2\n\nLine 3";
-        assert_eq!(super::clean_markdown_whitespace(input), expected);
+    fn module_artifact_paths_are_flat() {
+        assert_eq!(super::module_artifact_path(""), "API.md");
+        assert_eq!(
+            super::module_artifact_path("sync::mpsc"),
+            "API__sync__mpsc.md"
+        );
     }
 }
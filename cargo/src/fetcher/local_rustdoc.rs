@@ -0,0 +1,326 @@
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+use tokio::process::Command;
+use tracing::debug;
+
+use crate::error::{AiDocsError, Result};
+use crate::fetcher::latest::{CrawledPage, DocsRsArtifact};
+use crate::fetcher::local_source::{read_manifest, LocalSources};
+use crate::processor::examples::{example_budget_bytes, Example};
+use crate::processor::rustdoc_json::render_api_markdown;
+
+const TARGET_SUBDIR: &str = "target/ai-fdocs-rustdoc";
+
+/// Manifest tables dropped from the scratch copy: only the library is
+/// documented, and dev-dependencies are absent from the project's lockfile.
+const DEV_TABLES: &[&str] = &["dev-dependencies", "dev_dependencies"];
+
+/// Builds rustdoc JSON from crate sources already on disk (cargo registry
+/// cache or a vendored copy), with the features the project enables.
+pub struct LocalRustdocBuilder {
+    sources: Arc<LocalSources>,
+}
+
+impl LocalRustdocBuilder {
+    /// Crates missing from `cargo metadata` are documented with their default features.
+    pub fn new(sources: Arc<LocalSources>) -> Self {
+        Self { sources }
+    }
+
+    pub async fn build_api_markdown(
+        &self,
+        crate_name: &str,
+        version: &str,
+        max_file_size_kb: usize,
+        source_dir: Option<&Path>,
+        repo_examples: &[Example],
+    ) -> Result<DocsRsArtifact> {
+        let source = match source_dir {
+            Some(dir) => self.sources.project_dir().join(dir),
+            None => self
+                .sources
+                .find_source_dir(crate_name, version)
+                .ok_or_else(|| {
+                    AiDocsError::LocalRustdoc(format!(
+                    "no local source for {crate_name}@{version} in the cargo registry or vendor/"
+                ))
+                })?,
+        };
+
+        let features = self.sources.enabled_features(crate_name, version);
+        let target_dir = self.sources.project_dir().join(TARGET_SUBDIR);
+        let scratch = target_dir
+            .join("src")
+            .join(format!("{crate_name}-{version}"));
+        prepare_scratch_crate(
+            &source,
+            &scratch,
+            &self.sources.project_dir().join("Cargo.lock"),
+        )?;
+        let json_path = run_rustdoc(crate_name, &scratch, &target_dir, features).await?;
+
+        let bytes = std::fs::read(&json_path)?;
+        let doc: serde_json::Value = serde_json::from_slice(&bytes)
+            .map_err(|e| AiDocsError::RustdocJson(format!("invalid rustdoc JSON: {e}")))?;
+        let markdown = render_api_markdown(
+            crate_name,
+            version,
+            &doc,
+            repo_examples,
+            example_budget_bytes(max_file_size_kb),
+            &self.sources.usage().relative_paths(crate_name),
+        )?;
+
+        let input = source.display().to_string();
+        Ok(DocsRsArtifact {
+            source_kind: "local_rustdoc",
+            markdown,
+            pages: vec![CrawledPage {
+                url: input.clone(),
+                artifact_path: "API.md".to_string(),
+            }],
+            docsrs_input_url: input,
+            module_files: Vec::new(),
+        })
+    }
+}
+
+/// Copy the crate source to `scratch` and make it a standalone workspace that
+/// resolves from the project's `Cargo.lock`, so cargo never writes into the
+/// shared registry or a checksummed `vendor/` directory, and a vendored crate
+/// is not mistaken for a member of the project's workspace.
+fn prepare_scratch_crate(source: &Path, scratch: &Path, project_lock: &Path) -> Result<()> {
+    if scratch.exists() {
+        std::fs::remove_dir_all(scratch)?;
+    }
+    copy_dir(source, scratch)?;
+    let _ = std::fs::remove_file(scratch.join("Cargo.lock"));
+
+    let mut manifest = read_manifest(source).ok_or_else(|| {
+        AiDocsError::LocalRustdoc(format!("no readable Cargo.toml in {}", source.display()))
+    })?;
+    isolate_manifest(&mut manifest, source);
+    let manifest = toml::to_string(&manifest)
+        .map_err(|e| AiDocsError::LocalRustdoc(format!("cannot rewrite Cargo.toml: {e}")))?;
+    std::fs::write(scratch.join("Cargo.toml"), manifest)?;
+
+    if project_lock.is_file() {
+        std::fs::copy(project_lock, scratch.join("Cargo.lock"))?;
+    }
+    Ok(())
+}
+
+/// Give the manifest its own `[workspace]`, drop dev-dependencies and anchor
+/// relative `path` dependencies to the original source directory.
+fn isolate_manifest(manifest: &mut toml::Value, source: &Path) {
+    let Some(root) = manifest.as_table_mut() else {
+        return;
+    };
+    root.insert(
+        "workspace".to_string(),
+        toml::Value::Table(toml::map::Map::new()),
+    );
+    for table in DEV_TABLES {
+        root.remove(*table);
+    }
+    anchor_path_dependencies(root, source);
+
+    if let Some(targets) = root.get_mut("target").and_then(toml::Value::as_table_mut) {
+        for cfg in targets.iter_mut().filter_map(|(_, cfg)| cfg.as_table_mut()) {
+            for table in DEV_TABLES {
+                cfg.remove(*table);
+            }
+            anchor_path_dependencies(cfg, source);
+        }
+    }
+}
+
+fn anchor_path_dependencies(table: &mut toml::map::Map<String, toml::Value>, source: &Path) {
+    for key in ["dependencies", "build-dependencies", "build_dependencies"] {
+        let Some(deps) = table.get_mut(key).and_then(toml::Value::as_table_mut) else {
+            continue;
+        };
+        for dep in deps.iter_mut().filter_map(|(_, dep)| dep.as_table_mut()) {
+            if let Some(toml::Value::String(path)) = dep.get_mut("path") {
+                if Path::new(path.as_str()).is_relative() {
+                    *path = source.join(path.as_str()).display().to_string();
+                }
+            }
+        }
+    }
+}
+
+/// Recursive copy that skips build output and VCS metadata.
+fn copy_dir(from: &Path, to: &Path) -> Result<()> {
+    std::fs::create_dir_all(to)?;
+    for entry in std::fs::read_dir(from)? {
+        let entry = entry?;
+        let name = entry.file_name();
+        if name == "target" || name == ".git" {
+            continue;
+        }
+        let path = entry.path();
+        if entry.file_type()?.is_dir() {
+            copy_dir(&path, &to.join(&name))?;
+        } else {
+            std::fs::copy(&path, to.join(&name))?;
+        }
+    }
+    Ok(())
+}
+
+fn rustdoc_args(source: &Path, target_dir: &Path, features: Option<&[String]>) -> Vec<String> {
+    let mut args = vec![
+        "rustdoc".to_string(),
+        "--lib".to_string(),
+        "--offline".to_string(),
+        "--manifest-path".to_string(),
+        source.join("Cargo.toml").display().to_string(),
+        "--target-dir".to_string(),
+        target_dir.display().to_string(),
+    ];
+    if let Some(features) = features {
+        args.push("--no-default-features".to_string());
+        if !features.is_empty() {
+            args.push("--features".to_string());
+            args.push(features.join(","));
+        }
+    }
+    args.extend(
+        ["--", "-Z", "unstable-options", "--output-format", "json"]
+            .into_iter()
+            .map(str::to_string),
+    );
+    args
+}
+
+async fn run_rustdoc(
+    crate_name: &str,
+    source: &Path,
+    target_dir: &Path,
+    features: Option<&[String]>,
+) -> Result<PathBuf> {
+    let args = rustdoc_args(source, target_dir, features);
+    debug!("running cargo {}", args.join(" "));
+
+    // JSON output is still unstable; RUSTC_BOOTSTRAP lets stable toolchains emit it.
+    let output = Command::new("cargo")
+        .args(&args)
+        .current_dir(source)
+        .env("RUSTC_BOOTSTRAP", "1")
+        .output()
+        .await?;
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        let tail: Vec<&str> = stderr.lines().rev().take(5).collect();
+        return Err(AiDocsError::LocalRustdoc(format!(
+            "rustdoc failed for {}: {}",
+            source.display(),
+            tail.into_iter().rev().collect::<Vec<_>>().join(" | ")
+        )));
+    }
+
+    let lib_name = manifest_lib_name(source).unwrap_or_else(|| crate_name.replace('-', "_"));
+    let json_path = target_dir.join("doc").join(format!("{lib_name}.json"));
+    if !json_path.exists() {
+        return Err(AiDocsError::LocalRustdoc(format!(
+            "rustdoc finished but {} was not written",
+            json_path.display()
+        )));
+    }
+    Ok(json_path)
+}
+
+/// Explicit `[lib] name`, which decides the rustdoc JSON file name.
+fn manifest_lib_name(dir: &Path) -> Option<String> {
+    read_manifest(dir)?
+        .get("lib")?
+        .get("name")?
+        .as_str()
+        .map(str::to_string)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{manifest_lib_name, prepare_scratch_crate, rustdoc_args};
+    use std::fs;
+    use std::path::Path;
+
+    #[test]
+    fn reads_explicit_lib_name() {
+        let dir =
+            std::env::temp_dir().join(format!("ai-fdocs-local-rustdoc-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).expect("mkdir");
+        fs::write(
+            dir.join("Cargo.toml"),
+            "[package]\nname = \"serde-json\"\nversion = \"1.0.0\"\n\n[lib]\nname = \"serde_json\"\n",
+        )
+        .expect("write manifest");
+
+        assert_eq!(manifest_lib_name(&dir).as_deref(), Some("serde_json"));
+
+        fs::remove_dir_all(&dir).expect("cleanup");
+    }
+
+    #[test]
+    fn prepares_isolated_scratch_copy_pinned_to_project_lock() {
+        let root =
+            std::env::temp_dir().join(format!("ai-fdocs-rustdoc-scratch-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&root);
+        let source = root.join("vendor/demo");
+        fs::create_dir_all(source.join("src")).expect("mkdir");
+        fs::write(
+            source.join("Cargo.toml"),
+            "[package]\nname = \"demo\"\nversion = \"1.0.0\"\n\n[dependencies]\nhelper = { path = \"../helper\" }\nserde = \"1\"\n\n[dev-dependencies]\ntokio = \"1\"\n\n[target.'cfg(unix)'.dev-dependencies]\nlibc = \"0.2\"\n",
+        )
+        .expect("write manifest");
+        fs::write(source.join("src/lib.rs"), "pub fn demo() {}\n").expect("write lib");
+        fs::write(source.join("Cargo.lock"), "stale").expect("write crate lock");
+        fs::write(root.join("Cargo.lock"), "# project lock\n").expect("write project lock");
+
+        let scratch = root.join("target/ai-fdocs-rustdoc/src/demo-1.0.0");
+        prepare_scratch_crate(&source, &scratch, &root.join("Cargo.lock")).expect("prepare");
+
+        let manifest: toml::Value =
+            toml::from_str(&fs::read_to_string(scratch.join("Cargo.toml")).expect("read"))
+                .expect("parse");
+        assert!(manifest.get("workspace").is_some());
+        assert!(manifest.get("dev-dependencies").is_none());
+        assert!(manifest["target"]["cfg(unix)"]
+            .get("dev-dependencies")
+            .is_none());
+        assert_eq!(
+            manifest["dependencies"]["helper"]["path"].as_str(),
+            Some(source.join("../helper").display().to_string().as_str())
+        );
+        assert!(scratch.join("src/lib.rs").exists());
+        assert_eq!(
+            fs::read_to_string(scratch.join("Cargo.lock")).expect("read lock"),
+            "# project lock\n"
+        );
+        assert_eq!(
+            fs::read_to_string(source.join("Cargo.lock")).expect("source lock untouched"),
+            "stale"
+        );
+
+        fs::remove_dir_all(&root).expect("cleanup");
+    }
+
+    #[test]
+    fn builds_rustdoc_args_with_project_features() {
+        let features = vec!["default".to_string(), "macros".to_string()];
+        let args = rustdoc_args(
+            Path::new("/src/tokio-1.44.0"),
+            Path::new("/t"),
+            Some(&features),
+        );
+        assert!(args.contains(&"--no-default-features".to_string()));
+        assert!(args.contains(&"default,macros".to_string()));
+        assert_eq!(args.last().map(String::as_str), Some("json"));
+
+        let args = rustdoc_args(Path::new("/src/tokio-1.44.0"), Path::new("/t"), None);
+        assert!(!args.contains(&"--no-default-features".to_string()));
+    }
+}
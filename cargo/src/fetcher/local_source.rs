@@ -0,0 +1,287 @@
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+
+use serde::Deserialize;
+use tokio::process::Command;
+use tracing::warn;
+
+use crate::error::{AiDocsError, Result};
+use crate::usage::ProjectUsage;
+
+/// Crate sources and resolved features available on this machine: the cargo
+/// registry cache, `vendor/`, the project's `cargo metadata`, and which
+/// dependency paths the project's own code uses.
+pub struct LocalSources {
+    project_dir: PathBuf,
+    registry_src: Option<PathBuf>,
+    features: HashMap<(String, String), Vec<String>>,
+    usage: ProjectUsage,
+}
+
+#[derive(Debug, Deserialize)]
+struct CargoMetadata {
+    packages: Vec<MetadataPackage>,
+    resolve: Option<MetadataResolve>,
+}
+
+#[derive(Debug, Deserialize)]
+struct MetadataPackage {
+    id: String,
+    name: String,
+    version: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct MetadataResolve {
+    nodes: Vec<MetadataNode>,
+}
+
+#[derive(Debug, Deserialize)]
+struct MetadataNode {
+    id: String,
+    #[serde(default)]
+    features: Vec<String>,
+}
+
+impl LocalSources {
+    /// Resolve enabled features once via `cargo metadata`; when that fails the
+    /// enabled feature set of every crate is unknown.
+    pub async fn load(project_dir: &Path) -> Self {
+        let features = match load_enabled_features(project_dir).await {
+            Ok(features) => features,
+            Err(e) => {
+                warn!("cargo metadata unavailable ({e}); enabled features are unknown");
+                HashMap::new()
+            }
+        };
+        let dir = project_dir.to_path_buf();
+        let usage = tokio::task::spawn_blocking(move || ProjectUsage::scan(&dir))
+            .await
+            .unwrap_or_default();
+
+        Self {
+            project_dir: project_dir.to_path_buf(),
+            registry_src: cargo_home().map(|home| home.join("registry").join("src")),
+            features,
+            usage,
+        }
+    }
+
+    pub fn project_dir(&self) -> &Path {
+        &self.project_dir
+    }
+
+    pub fn usage(&self) -> &ProjectUsage {
+        &self.usage
+    }
+
+    /// Features the project build enables for `crate_name@version`. When that
+    /// exact version is not in the build (latest-docs mode), the single locked
+    /// version of the crate is used instead.
+    pub fn enabled_features(&self, crate_name: &str, version: &str) -> Option<&[String]> {
+        if let Some(features) = self
+            .features
+            .get(&(crate_name.to_string(), version.to_string()))
+        {
+            return Some(features);
+        }
+
+        let mut locked = self
+            .features
+            .iter()
+            .filter(|((name, _), _)| name == crate_name);
+        match (locked.next(), locked.next()) {
+            (Some((_, features)), None) => Some(features),
+            _ => None,
+        }
+    }
+
+    /// Source directory of `crate_name@version`: `vendor/` first, then the
+    /// cargo registry cache. Directories whose manifest has another version are skipped.
+    pub fn find_source_dir(&self, crate_name: &str, version: &str) -> Option<PathBuf> {
+        let dir_name = format!("{crate_name}-{version}");
+        let vendor = self.project_dir.join("vendor");
+
+        let mut candidates = vec![vendor.join(&dir_name), vendor.join(crate_name)];
+        if let Some(registry_src) = &self.registry_src {
+            if let Ok(entries) = std::fs::read_dir(registry_src) {
+                let mut registries: Vec<PathBuf> =
+                    entries.filter_map(|e| e.ok().map(|e| e.path())).collect();
+                registries.sort();
+                candidates.extend(registries.into_iter().map(|r| r.join(&dir_name)));
+            }
+        }
+
+        candidates
+            .into_iter()
+            .find(|dir| manifest_version(dir).as_deref() == Some(version))
+    }
+}
+
+fn cargo_home() -> Option<PathBuf> {
+    std::env::var_os("CARGO_HOME")
+        .map(PathBuf::from)
+        .or_else(|| std::env::var_os("HOME").map(|home| PathBuf::from(home).join(".cargo")))
+}
+
+/// Parsed `Cargo.toml` of a crate source directory.
+pub fn read_manifest(dir: &Path) -> Option<toml::Value> {
+    let content = std::fs::read_to_string(dir.join("Cargo.toml")).ok()?;
+    toml::from_str(&content).ok()
+}
+
+/// Manifest text with the author's comments: published crates keep the original
+/// as `Cargo.toml.orig`, while the normalized `Cargo.toml` drops them.
+pub fn read_original_manifest(dir: &Path) -> Option<String> {
+    std::fs::read_to_string(dir.join("Cargo.toml.orig"))
+        .or_else(|_| std::fs::read_to_string(dir.join("Cargo.toml")))
+        .ok()
+}
+
+fn manifest_version(dir: &Path) -> Option<String> {
+    read_manifest(dir)?
+        .get("package")?
+        .get("version")?
+        .as_str()
+        .map(str::to_string)
+}
+
+async fn load_enabled_features(
+    project_dir: &Path,
+) -> Result<HashMap<(String, String), Vec<String>>> {
+    let output = Command::new("cargo")
+        .args(["metadata", "--format-version", "1", "--offline"])
+        .current_dir(project_dir)
+        .output()
+        .await?;
+    if !output.status.success() {
+        return Err(AiDocsError::LocalRustdoc(format!(
+            "cargo metadata failed: {}",
+            String::from_utf8_lossy(&output.stderr).trim()
+        )));
+    }
+    parse_enabled_features(&output.stdout)
+}
+
+fn parse_enabled_features(json: &[u8]) -> Result<HashMap<(String, String), Vec<String>>> {
+    let metadata: CargoMetadata = serde_json::from_slice(json)
+        .map_err(|e| AiDocsError::LocalRustdoc(format!("invalid cargo metadata: {e}")))?;
+    let nodes: HashMap<&str, &Vec<String>> = metadata
+        .resolve
+        .as_ref()
+        .map(|r| {
+            r.nodes
+                .iter()
+                .map(|n| (n.id.as_str(), &n.features))
+                .collect()
+        })
+        .unwrap_or_default();
+
+    Ok(metadata
+        .packages
+        .iter()
+        .filter_map(|p| {
+            let features = nodes.get(p.id.as_str())?;
+            Some(((p.name.clone(), p.version.clone()), (*features).clone()))
+        })
+        .collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{parse_enabled_features, LocalSources};
+    use crate::usage::ProjectUsage;
+    use std::collections::HashMap;
+    use std::fs;
+    use std::path::Path;
+
+    fn sources_for_tests(
+        project_dir: &Path,
+        registry_src: Option<&Path>,
+        features: HashMap<(String, String), Vec<String>>,
+    ) -> LocalSources {
+        LocalSources {
+            project_dir: project_dir.to_path_buf(),
+            registry_src: registry_src.map(Path::to_path_buf),
+            features,
+            usage: ProjectUsage::default(),
+        }
+    }
+
+    fn write_crate(dir: &Path, name: &str, version: &str) {
+        fs::create_dir_all(dir).expect("mkdir");
+        fs::write(
+            dir.join("Cargo.toml"),
+            format!("[package]\nname = \"{name}\"\nversion = \"{version}\"\n"),
+        )
+        .expect("write manifest");
+    }
+
+    #[test]
+    fn finds_vendored_and_registry_sources_by_version() {
+        let tmp =
+            std::env::temp_dir().join(format!("ai-fdocs-local-source-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&tmp);
+        let project = tmp.join("project");
+        let registry = tmp.join("registry-src");
+
+        write_crate(&project.join("vendor/serde"), "serde", "1.0.210");
+        write_crate(
+            &registry.join("index.crates.io-abc/tokio-1.44.0"),
+            "tokio",
+            "1.44.0",
+        );
+        write_crate(
+            &registry.join("index.crates.io-abc/tokio-1.43.0"),
+            "tokio",
+            "1.43.0",
+        );
+
+        let sources = sources_for_tests(&project, Some(&registry), HashMap::new());
+
+        assert_eq!(
+            sources.find_source_dir("serde", "1.0.210"),
+            Some(project.join("vendor/serde"))
+        );
+        assert_eq!(sources.find_source_dir("serde", "1.0.200"), None);
+        assert_eq!(
+            sources.find_source_dir("tokio", "1.44.0"),
+            Some(registry.join("index.crates.io-abc/tokio-1.44.0"))
+        );
+
+        fs::remove_dir_all(&tmp).expect("cleanup");
+    }
+
+    #[test]
+    fn reads_enabled_features_from_cargo_metadata() {
+        let json = br#"{
+            "packages": [
+                {"id": "registry+https://github.com/rust-lang/crates.io-index#tokio@1.44.0", "name": "tokio", "version": "1.44.0"},
+                {"id": "path+file:///app#app@0.1.0", "name": "app", "version": "0.1.0"}
+            ],
+            "resolve": {"nodes": [
+                {"id": "registry+https://github.com/rust-lang/crates.io-index#tokio@1.44.0", "features": ["default", "macros", "rt"]},
+                {"id": "path+file:///app#app@0.1.0", "features": []}
+            ]}
+        }"#;
+        let features = parse_enabled_features(json).expect("parse");
+        assert_eq!(
+            features[&("tokio".to_string(), "1.44.0".to_string())],
+            vec!["default", "macros", "rt"]
+        );
+        assert!(features[&("app".to_string(), "0.1.0".to_string())].is_empty());
+
+        let sources = sources_for_tests(Path::new("."), None, features);
+        assert_eq!(
+            sources.enabled_features("tokio", "1.45.0"),
+            Some(
+                &[
+                    "default".to_string(),
+                    "macros".to_string(),
+                    "rt".to_string()
+                ][..]
+            )
+        );
+        assert_eq!(sources.enabled_features("serde", "1.0.0"), None);
+    }
+}
@@ -1,3 +1,9 @@
+pub mod crates_io;
+
 pub mod github;
 
 pub mod latest;
+
+pub mod local_rustdoc;
+pub mod local_source;
+pub mod sparse_index;
@@ -0,0 +1,191 @@
+use semver::{Version, VersionReq};
+use serde::{Deserialize, Serialize};
+
+pub const DEFAULT_SPARSE_INDEX: &str = "https://index.crates.io";
+
+/// One published version from a sparse index file.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct IndexVersion {
+    pub version: String,
+    pub yanked: bool,
+}
+
+#[derive(Debug, Deserialize)]
+struct IndexLine {
+    vers: String,
+    #[serde(default)]
+    yanked: bool,
+}
+
+/// Base URL of a sparse index; accepts cargo's `sparse+https://...` form.
+pub fn normalize_index_url(url: &str) -> String {
+    url.trim()
+        .trim_start_matches("sparse+")
+        .trim_end_matches('/')
+        .to_string()
+}
+
+/// Path of a crate's file in the index: `1/a`, `2/ab`, `3/a/abc`, `se/rd/serde`.
+pub fn index_path(crate_name: &str) -> String {
+    let name = crate_name.to_ascii_lowercase();
+    match name.len() {
+        1 => format!("1/{name}"),
+        2 => format!("2/{name}"),
+        3 => format!("3/{}/{name}", &name[..1]),
+        _ => format!("{}/{}/{name}", &name[..2], &name[2..4]),
+    }
+}
+
+/// Versions listed in an index file, one JSON object per line; malformed lines are skipped.
+pub fn parse_index_versions(body: &str) -> Vec<IndexVersion> {
+    body.lines()
+        .filter(|line| !line.trim().is_empty())
+        .filter_map(|line| serde_json::from_str::<IndexLine>(line).ok())
+        .map(|line| IndexVersion {
+            version: line.vers,
+            yanked: line.yanked,
+        })
+        .collect()
+}
+
+/// Highest non-yanked version. Prereleases only count when `include_prereleases`
+/// is set or the crate has no stable release at all.
+pub fn select_latest(versions: &[IndexVersion], include_prereleases: bool) -> Option<String> {
+    let candidates: Vec<(Version, &str)> = versions
+        .iter()
+        .filter(|v| !v.yanked)
+        .filter_map(|v| Some((Version::parse(&v.version).ok()?, v.version.as_str())))
+        .collect();
+
+    let stable = candidates
+        .iter()
+        .filter(|(v, _)| v.pre.is_empty())
+        .max_by(|a, b| a.0.cmp(&b.0));
+    let any = candidates.iter().max_by(|a, b| a.0.cmp(&b.0));
+
+    let chosen = if include_prereleases {
+        any
+    } else {
+        stable.or(any)
+    };
+    chosen.map(|(_, raw)| (*raw).to_string())
+}
+
+/// Newer releases relative to a locked version, as reported by `status`.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+pub struct UpstreamVersions {
+    /// Highest non-yanked stable release.
+    pub latest_stable: Option<String>,
+    /// Highest non-yanked stable release that `^locked` accepts (same major, or
+    /// same minor for `0.x`).
+    pub latest_compatible: Option<String>,
+    /// Highest non-yanked release, prereleases included.
+    pub latest: Option<String>,
+}
+
+impl UpstreamVersions {
+    /// The release to suggest when it is newer than `locked`: the latest stable
+    /// one, else the latest compatible one.
+    pub fn newer_than(&self, locked: &str) -> Option<&str> {
+        let locked = crate::version::parse(locked)?;
+        [&self.latest_stable, &self.latest_compatible]
+            .into_iter()
+            .flatten()
+            .find(|v| crate::version::parse(v).is_some_and(|v| v > locked))
+            .map(String::as_str)
+    }
+}
+
+/// Latest stable, latest semver-compatible and latest overall versions for a
+/// crate locked at `locked`. Yanked versions never count.
+pub fn upstream_versions(versions: &[IndexVersion], locked: &str) -> UpstreamVersions {
+    let candidates: Vec<(Version, &str)> = versions
+        .iter()
+        .filter(|v| !v.yanked)
+        .filter_map(|v| Some((Version::parse(&v.version).ok()?, v.version.as_str())))
+        .collect();
+    let compatible = VersionReq::parse(&format!("^{locked}")).ok();
+
+    let max_of = |keep: &dyn Fn(&Version) -> bool| {
+        candidates
+            .iter()
+            .filter(|(v, _)| keep(v))
+            .max_by(|a, b| a.0.cmp(&b.0))
+            .map(|(_, raw)| (*raw).to_string())
+    };
+
+    UpstreamVersions {
+        latest_stable: max_of(&|v| v.pre.is_empty()),
+        latest_compatible: max_of(&|v| {
+            v.pre.is_empty() && compatible.as_ref().is_some_and(|req| req.matches(v))
+        }),
+        latest: max_of(&|_| true),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        index_path, normalize_index_url, parse_index_versions, select_latest, upstream_versions,
+    };
+
+    const INDEX: &str = r#"{"name":"demo","vers":"1.0.0","deps":[],"cksum":"a","features":{},"yanked":false}
+{"name":"demo","vers":"1.2.0","deps":[],"cksum":"b","features":{},"yanked":true}
+{"name":"demo","vers":"1.1.0","deps":[],"cksum":"c","features":{},"yanked":false}
+{"name":"demo","vers":"2.0.0-rc.10","deps":[],"cksum":"d","features":{},"yanked":false}
+{"name":"demo","vers":"2.0.0-rc.2","deps":[],"cksum":"e","features":{},"yanked":false}
+not json
+"#;
+
+    #[test]
+    fn builds_index_paths_and_urls() {
+        assert_eq!(index_path("a"), "1/a");
+        assert_eq!(index_path("cc"), "2/cc");
+        assert_eq!(index_path("syn"), "3/s/syn");
+        assert_eq!(index_path("Serde"), "se/rd/serde");
+        assert_eq!(
+            normalize_index_url("sparse+https://mirror.example.com/index/"),
+            "https://mirror.example.com/index"
+        );
+    }
+
+    #[test]
+    fn selects_latest_non_yanked_version() {
+        let versions = parse_index_versions(INDEX);
+        assert_eq!(versions.len(), 5);
+        assert_eq!(select_latest(&versions, false).as_deref(), Some("1.1.0"));
+        assert_eq!(
+            select_latest(&versions, true).as_deref(),
+            Some("2.0.0-rc.10")
+        );
+
+        let prerelease_only =
+            parse_index_versions(r#"{"name":"demo","vers":"0.1.0-alpha.1","yanked":false}"#);
+        assert_eq!(
+            select_latest(&prerelease_only, false).as_deref(),
+            Some("0.1.0-alpha.1")
+        );
+        let all_yanked = parse_index_versions(r#"{"name":"demo","vers":"0.1.0","yanked":true}"#);
+        assert_eq!(select_latest(&all_yanked, false), None);
+    }
+
+    #[test]
+    fn reports_stable_compatible_and_overall_upstream_versions() {
+        let versions = parse_index_versions(INDEX);
+        let upstream = upstream_versions(&versions, "1.0.0");
+        assert_eq!(upstream.latest_stable.as_deref(), Some("1.1.0"));
+        assert_eq!(upstream.latest_compatible.as_deref(), Some("1.1.0"));
+        assert_eq!(upstream.latest.as_deref(), Some("2.0.0-rc.10"));
+        assert_eq!(upstream.newer_than("1.0.0"), Some("1.1.0"));
+        assert_eq!(upstream.newer_than("1.1.0"), None);
+
+        let zero_x = parse_index_versions(
+            r#"{"name":"demo","vers":"0.3.4","yanked":false}
+{"name":"demo","vers":"0.4.1","yanked":false}
+{"name":"demo","vers":"0.3.9","yanked":true}"#,
+        );
+        let upstream = upstream_versions(&zero_x, "0.3.1");
+        assert_eq!(upstream.latest_stable.as_deref(), Some("0.4.1"));
+        assert_eq!(upstream.latest_compatible.as_deref(), Some("0.3.4"));
+    }
+}
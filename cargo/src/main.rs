@@ -1,4 +1,7 @@
+mod advisory;
+mod budget;
 mod config;
+mod cost;
 mod error;
 #[path = "fetcher/mod.rs"]
 mod fetcher;
@@ -8,7 +11,11 @@ mod processor;
 mod resolver;
 mod status;
 mod storage;
+mod tokens;
+mod upgrade;
+mod usage;
 mod utils;
+mod version;
 
 use std::path::{Path, PathBuf};
 use std::sync::Arc;
@@ -18,16 +25,35 @@ use tokio::sync::Semaphore;
 use clap::{Parser, Subcommand, ValueEnum};
 use tracing::{error, info, warn};
 
-use crate::config::{Config, DocsSource, SyncMode};
+use crate::advisory::AdvisoryDb;
+use crate::config::{
+    ApiSource, ChangelogWindow, ChunkStrategy, Config, ContentSettings, DocsSource,
+    MarkdownCleanup, SyncMode, TokenEstimatorKind,
+};
 use crate::error::AiDocsError;
 use crate::error::{Result, SyncErrorKind};
-use crate::fetcher::github::{FetchedFile, FileRequest, GitHubFetcher};
-use crate::fetcher::latest::{is_docsrs_fallback_eligible, LatestDocsFetcher};
+use crate::fetcher::crates_io::CratesIoMetadata;
+use crate::fetcher::github::{github_token, FetchedFile, FileRequest, GitHubFetcher};
+use crate::fetcher::latest::{
+    docsrs_fallback_reason, is_docsrs_fallback_eligible, CrawlOptions, DocsRsArtifact,
+    LatestDocsFetcher, ModuleFile,
+};
+use crate::fetcher::local_rustdoc::LocalRustdocBuilder;
+use crate::fetcher::local_source::{read_original_manifest, LocalSources};
+use crate::fetcher::sparse_index::{upstream_versions, UpstreamVersions};
 use crate::init::run_init as run_init_command;
-use crate::status::{collect_status, collect_status_latest, print_status_table, DocsStatus};
+use crate::processor::api_diff::{api_diff_title, diff_api, render_api_diff_markdown, ApiDiff};
+use crate::processor::examples::{example_file, Example};
+use crate::processor::features::{
+    parse_manifest_features, render_features_markdown, FeatureSource,
+};
+use crate::processor::rustdoc_json::api_surface;
+use crate::status::{collect_status, collect_status_latest, print_status_table};
+use crate::upgrade::{render_upgrade_markdown, render_upgrades_index, UpgradeBrief, UPGRADE_FILE};
 use crate::utils::is_latest_cache_fresh;
 
 const DEFAULT_CONFIG_PATH: &str = "ai-fdocs.toml";
+const FEATURES_FILE: &str = "FEATURES.md";
 
 #[derive(Parser)]
 #[command(name = "ai-fdocs")]
@@ -60,6 +86,9 @@ enum Commands {
         /// Output format for status report.
         #[arg(long, value_enum, default_value_t = OutputFormat::Table)]
         format: OutputFormat,
+        /// Report newer upstream releases of locked crates (`settings.check_updates`).
+        #[arg(long, default_value_t = false)]
+        check_updates: bool,
     },
     /// Exit non-zero if any crate docs are not synced.
     Check {
@@ -72,6 +101,20 @@ enum Commands {
         #[arg(long, value_enum, default_value_t = OutputFormat::Table)]
         format: OutputFormat,
     },
+    /// Estimate how many tokens the synced docs cost in an AI context.
+    Cost {
+        #[arg(short, long, default_value = DEFAULT_CONFIG_PATH)]
+        config: PathBuf,
+        /// Token budget to check against; suggests what to trim when exceeded.
+        #[arg(long)]
+        budget: Option<usize>,
+        /// Number of largest files to list.
+        #[arg(long, default_value_t = 10)]
+        top: usize,
+        /// Output format for the cost report.
+        #[arg(long, value_enum, default_value_t = OutputFormat::Table)]
+        format: OutputFormat,
+    },
     /// Generate or refresh ai-fdocs config template.
     Init {
         #[arg(short, long, default_value = DEFAULT_CONFIG_PATH)]
@@ -131,6 +174,67 @@ impl SyncStats {
     }
 }
 
+/// Settings shared by every sync worker.
+#[derive(Clone, Copy)]
+struct SyncOptions {
+    force: bool,
+    max_file_size_kb: usize,
+    chunk_strategy: ChunkStrategy,
+    chunk_max_tokens: Option<usize>,
+    token_estimator: TokenEstimatorKind,
+    markdown_cleanup: MarkdownCleanup,
+    latest_ttl_hours: usize,
+    docsrs_single_page: bool,
+    docsrs_max_pages: usize,
+    docsrs_max_depth: usize,
+    api_source: ApiSource,
+    crates_io_metadata: bool,
+    repo_examples: bool,
+    check_archived: bool,
+}
+
+impl SyncOptions {
+    fn from_settings(settings: &crate::config::Settings, force: bool) -> Self {
+        // Extra GitHub calls per crate quickly exhaust the unauthenticated 60 req/h.
+        let has_token = github_token().is_some();
+        Self {
+            force,
+            max_file_size_kb: settings.max_file_size_kb,
+            chunk_strategy: settings.chunk_strategy,
+            chunk_max_tokens: settings.chunk_max_tokens,
+            token_estimator: settings.token_estimator,
+            markdown_cleanup: settings.markdown_cleanup,
+            latest_ttl_hours: settings.latest_ttl_hours,
+            docsrs_single_page: settings.docsrs_single_page,
+            docsrs_max_pages: settings.docsrs_max_pages,
+            docsrs_max_depth: settings.docsrs_max_depth,
+            api_source: settings.api_source,
+            crates_io_metadata: settings.crates_io_metadata,
+            repo_examples: settings.repo_examples.unwrap_or(has_token),
+            check_archived: settings.check_archived.unwrap_or(has_token),
+        }
+    }
+
+    const fn output_limits(self) -> storage::OutputLimits {
+        storage::OutputLimits {
+            max_file_size_kb: self.max_file_size_kb,
+            chunk_strategy: self.chunk_strategy,
+            chunk_max_tokens: self.chunk_max_tokens,
+            token_estimator: self.token_estimator,
+            markdown_cleanup: self.markdown_cleanup,
+        }
+    }
+
+    const fn content_settings(self) -> ContentSettings {
+        ContentSettings {
+            markdown_cleanup: self.markdown_cleanup,
+            chunk_strategy: self.chunk_strategy,
+            chunk_max_tokens: self.chunk_max_tokens,
+            api_source: self.api_source,
+        }
+    }
+}
+
 #[derive(Debug)]
 enum SyncOutcome {
     Synced(storage::SavedCrate),
@@ -178,12 +282,19 @@ async fn run(cli: Cli) -> Result<()> {
             config,
             mode,
             format,
-        } => run_status(&config, mode, format).await,
+            check_updates,
+        } => run_status(&config, mode, format, check_updates).await,
         Commands::Check {
             config,
             mode,
             format,
         } => run_check(&config, mode, format).await,
+        Commands::Cost {
+            config,
+            budget,
+            top,
+            format,
+        } => run_cost(&config, budget, top, format),
         Commands::Init { config, force } => run_init_command(&config, force).await,
     }
 }
@@ -198,6 +309,7 @@ async fn run_sync(
 
     let sync_mode = resolve_sync_mode(mode_override, config.settings.sync_mode);
     info!("Resolved sync mode: {}", sync_mode.as_str());
+    info!("API source: {}", config.settings.api_source.as_str());
     if matches!(sync_mode, SyncMode::LatestDocs) {
         return run_sync_latest_docs(config, force).await;
     }
@@ -210,6 +322,7 @@ async fn run_sync(
     let rust_versions = resolver::resolve_cargo_versions(&cargo_lock_path)?;
 
     let rust_output_dir = storage::rust_output_dir(&config.settings.output_dir);
+    let previous_sync = storage::read_sync_state(&rust_output_dir);
     if config.settings.prune {
         storage::prune(&rust_output_dir, &config, &rust_versions)?;
     }
@@ -217,6 +330,8 @@ async fn run_sync(
     let fetcher = Arc::new(GitHubFetcher::new());
     let mut saved_crates = Vec::new();
     let mut stats = SyncStats::default();
+    let options = SyncOptions::from_settings(&config.settings, force);
+    let local_sources = load_local_sources().await;
 
     let outcomes = run_orchestrated_sync(
         &config,
@@ -225,17 +340,16 @@ async fn run_sync(
             let rust_output_dir = rust_output_dir.clone();
             let rust_versions = rust_versions.clone();
             let fetcher = Arc::clone(&fetcher);
-            let force = force;
-            let max_file_size_kb = config.settings.max_file_size_kb;
+            let local_sources = Arc::clone(&local_sources);
             async move {
                 sync_one_crate(
                     rust_output_dir,
                     rust_versions,
                     fetcher,
+                    local_sources,
                     crate_name,
                     crate_doc,
-                    force,
-                    max_file_size_kb,
+                    options,
                 )
                 .await
             }
@@ -260,6 +374,33 @@ async fn run_sync(
         }
     }
 
+    annotate_advisories(&config.settings, &rust_output_dir, &mut saved_crates);
+    write_upgrade_briefs(
+        &config,
+        &rust_output_dir,
+        &previous_sync,
+        &mut saved_crates,
+        &fetcher,
+    )
+    .await?;
+    let api_diffs = config.settings.api_diff && matches!(sync_mode, SyncMode::Hybrid);
+    if config.settings.check_updates || api_diffs {
+        let upstream = lookup_upstream(&config.settings, &saved_crates).await;
+        if config.settings.check_updates {
+            annotate_updates(&rust_output_dir, &saved_crates, &upstream);
+        }
+        if api_diffs {
+            write_api_diffs(
+                &config.settings,
+                &rust_output_dir,
+                &mut saved_crates,
+                &upstream,
+            )
+            .await;
+        }
+    }
+    annotate_usage(&local_sources, &rust_output_dir, &saved_crates);
+    budget::apply_total_budget(&config, &rust_output_dir, &saved_crates)?;
     index::generate_index(&rust_output_dir, &saved_crates)?;
 
     info!(
@@ -286,10 +427,15 @@ async fn run_sync_latest_docs(config: Config, force: bool) -> Result<()> {
 
     let rust_output_dir = storage::rust_output_dir(&config.settings.output_dir);
     let github_fetcher = Arc::new(GitHubFetcher::new());
-    let latest_fetcher = Arc::new(LatestDocsFetcher::new());
+    let latest_fetcher = Arc::new(LatestDocsFetcher::from_settings(&config.settings));
 
     let mut saved_crates = Vec::new();
     let mut stats = SyncStats::default();
+    let options = SyncOptions::from_settings(&config.settings, force);
+    let local_sources = load_local_sources().await;
+    // Only used to place `changelog_window = "newer"`; latest mode works without a lockfile.
+    let lock_versions =
+        resolver::resolve_cargo_versions(Path::new("Cargo.lock")).unwrap_or_default();
 
     let outcomes = run_orchestrated_sync(
         &config,
@@ -298,18 +444,18 @@ async fn run_sync_latest_docs(config: Config, force: bool) -> Result<()> {
             let rust_output_dir = rust_output_dir.clone();
             let github_fetcher = Arc::clone(&github_fetcher);
             let latest_fetcher = Arc::clone(&latest_fetcher);
-            let max_file_size_kb = config.settings.max_file_size_kb;
-            let ttl = config.settings.latest_ttl_hours;
+            let local_sources = Arc::clone(&local_sources);
+            let locked_version = lock_versions.get(&crate_name).cloned();
             async move {
                 sync_one_crate_latest(
                     rust_output_dir,
                     latest_fetcher,
                     github_fetcher,
+                    local_sources,
                     crate_name,
                     crate_doc,
-                    force,
-                    max_file_size_kb,
-                    ttl,
+                    locked_version,
+                    options,
                 )
                 .await
             }
@@ -334,6 +480,9 @@ async fn run_sync_latest_docs(config: Config, force: bool) -> Result<()> {
         }
     }
 
+    annotate_advisories(&config.settings, &rust_output_dir, &mut saved_crates);
+    annotate_usage(&local_sources, &rust_output_dir, &saved_crates);
+    budget::apply_total_budget(&config, &rust_output_dir, &saved_crates)?;
     index::generate_index(&rust_output_dir, &saved_crates)?;
     info!(
         "✅ Latest-docs sync complete: {} synced, {} cached, {} skipped, {} errors",
@@ -343,15 +492,16 @@ async fn run_sync_latest_docs(config: Config, force: bool) -> Result<()> {
     Ok(())
 }
 
+#[allow(clippy::too_many_arguments)]
 async fn sync_one_crate_latest(
     rust_output_dir: PathBuf,
     latest_fetcher: Arc<LatestDocsFetcher>,
     github_fetcher: Arc<GitHubFetcher>,
+    local_sources: Arc<LocalSources>,
     crate_name: String,
     crate_doc: crate::config::CrateDoc,
-    force: bool,
-    max_file_size_kb: usize,
-    latest_ttl_hours: usize,
+    locked_version: Option<String>,
+    options: SyncOptions,
 ) -> SyncOutcome {
     let version = match latest_fetcher.resolve_latest_version(&crate_name).await {
         Ok(v) => v,
@@ -361,9 +511,17 @@ async fn sync_one_crate_latest(
         }
     };
 
-    if !force && storage::is_cached(&rust_output_dir, &crate_name, &version, &crate_doc) {
+    if !options.force
+        && storage::is_cached(
+            &rust_output_dir,
+            &crate_name,
+            &version,
+            &crate_doc,
+            &options.content_settings(),
+        )
+    {
         if let Some(meta) = storage::read_meta(&rust_output_dir, &crate_name, &version) {
-            if is_latest_cache_fresh(&meta.fetched_at, latest_ttl_hours) {
+            if is_latest_cache_fresh(&meta.fetched_at, options.latest_ttl_hours) {
                 info!("  ⏭ {crate_name}@{version}: cached (TTL valid), skipping");
                 let cached =
                     storage::read_cached_info(&rust_output_dir, &crate_name, &version, &crate_doc);
@@ -373,26 +531,118 @@ async fn sync_one_crate_latest(
         }
     }
 
-    match latest_fetcher
-        .fetch_api_markdown(&crate_name, &version, max_file_size_kb)
-        .await
+    // docs.rs has no changelog; `changelog_window = "newer"` asks for the repository's.
+    let wants_changelog = crate_doc.changelog_window == ChangelogWindow::NewerThanLocked;
+    let repo_ref = match crate_doc
+        .github_repo()
+        .filter(|_| options.repo_examples || wants_changelog)
     {
-        Ok(artifact) => match storage::save_latest_api_markdown(
-            &rust_output_dir,
-            &crate_name,
-            &version,
-            &artifact.markdown,
-            &artifact.docsrs_input_url,
-            artifact.truncated,
-            max_file_size_kb,
-            &crate_doc,
-        ) {
-            Ok(saved) => SyncOutcome::Synced(saved),
+        Some(repo) => match github_fetcher
+            .resolve_ref(repo, &crate_name, &version)
+            .await
+        {
+            Ok(resolved) => Some((repo, resolved.git_ref)),
             Err(e) => {
-                warn!("  ✗ failed to save docs.rs artifact for {crate_name}@{version}: {e}");
-                SyncOutcome::Error(e.sync_kind())
+                warn!("  ⚠ could not resolve ref for {crate_name}@{version}: {e}");
+                None
             }
         },
+        None => None,
+    };
+    let repo_examples = match &repo_ref {
+        Some((repo, git_ref)) if options.repo_examples => {
+            load_repo_examples(&github_fetcher, repo, git_ref, &crate_doc).await
+        }
+        _ => Vec::new(),
+    };
+    let changelog = match &repo_ref {
+        Some((repo, git_ref)) if wants_changelog => {
+            load_repo_changelog(&github_fetcher, repo, git_ref, &crate_doc)
+                .await
+                .map(|file| storage::RepoChangelog {
+                    repo: repo.to_string(),
+                    git_ref: git_ref.clone(),
+                    file,
+                    locked_version: locked_version.clone(),
+                })
+        }
+        _ => None,
+    };
+    let features_file = fetch_features_file(
+        &latest_fetcher,
+        &local_sources,
+        &crate_name,
+        &version,
+        &crate_doc,
+    )
+    .await;
+    let crates_io = fetch_crates_io_metadata(&latest_fetcher, &crate_name, &version, options).await;
+    let repo_archived = fetch_repo_archived(&github_fetcher, &crate_doc, options).await;
+
+    let structured = fetch_structured_api(
+        &latest_fetcher,
+        &local_sources,
+        &crate_name,
+        &version,
+        &crate_doc,
+        options,
+        &repo_examples,
+    )
+    .await;
+    let fetched = match structured {
+        Some(Ok(artifact)) => Ok(artifact),
+        Some(Err(e)) => {
+            warn!(
+                "  ⚠ {} unavailable for {crate_name}@{version}: {e}; using docs.rs HTML",
+                options.api_source.as_str()
+            );
+            fetch_docsrs_html(
+                &latest_fetcher,
+                &local_sources,
+                &crate_name,
+                &version,
+                &crate_doc,
+                options,
+                &repo_examples,
+            )
+            .await
+        }
+        None => {
+            fetch_docsrs_html(
+                &latest_fetcher,
+                &local_sources,
+                &crate_name,
+                &version,
+                &crate_doc,
+                options,
+                &repo_examples,
+            )
+            .await
+        }
+    };
+
+    match fetched {
+        Ok(mut artifact) => {
+            attach_features_file(&mut artifact, features_file);
+            match storage::save_latest_api_markdown(
+                &rust_output_dir,
+                &crate_name,
+                &version,
+                &artifact,
+                &options.output_limits(),
+                &options.content_settings(),
+                &crate_doc,
+                crates_io.as_ref(),
+                repo_archived,
+                changelog.as_ref(),
+            ) {
+                Ok(saved) => SyncOutcome::Synced(saved),
+                Err(e) => {
+                    warn!("  ✗ failed to save docs.rs artifact for {crate_name}@{version}: {e}");
+                    SyncOutcome::Error(e.sync_kind())
+                }
+            }
+        }
         Err(e) if is_docsrs_fallback_eligible(&e) => {
             warn!(
                 "  ⚠ docs.rs unavailable for {crate_name}@{version}: {e}; trying GitHub fallback"
@@ -403,8 +653,14 @@ async fn sync_one_crate_latest(
                 crate_name,
                 crate_doc,
                 version,
-                max_file_size_kb,
-                Some("github_fallback"),
+                options,
+                Some(GitHubFallback {
+                    reason: docsrs_fallback_reason(&e),
+                    extra_files: features_file.into_iter().collect(),
+                    crates_io,
+                    repo_archived,
+                    locked_version,
+                }),
             )
             .await
         }
@@ -415,18 +671,488 @@ async fn sync_one_crate_latest(
     }
 }
 
+/// Match every synced crate version against the configured RustSec checkout
+/// and refresh its `ADVISORIES.md`; does nothing without `settings.advisory_db`.
+fn annotate_advisories(
+    settings: &crate::config::Settings,
+    rust_output_dir: &Path,
+    saved_crates: &mut [storage::SavedCrate],
+) {
+    let Some(db_path) = &settings.advisory_db else {
+        return;
+    };
+    let db = match AdvisoryDb::open(db_path) {
+        Ok(db) => db,
+        Err(e) => {
+            warn!("⚠ advisory annotations skipped: {e}");
+            return;
+        }
+    };
+
+    for saved in saved_crates {
+        let advisories = db.advisories_for(&saved.name, &saved.version);
+        if !advisories.is_empty() {
+            warn!(
+                "  ⚠ {}@{}: {} RustSec advisor{} apply",
+                saved.name,
+                saved.version,
+                advisories.len(),
+                if advisories.len() == 1 { "y" } else { "ies" }
+            );
+        }
+        if let Err(e) = storage::update_advisories(rust_output_dir, saved, &advisories, db.root()) {
+            warn!(
+                "  ⚠ failed to write advisories for {}@{}: {e}",
+                saved.name, saved.version
+            );
+        }
+    }
+}
+
+/// List the paths the project's source uses in each synced crate's `_SUMMARY.md`.
+fn annotate_usage(
+    local_sources: &LocalSources,
+    rust_output_dir: &Path,
+    saved_crates: &[storage::SavedCrate],
+) {
+    for saved in saved_crates {
+        let used = local_sources.usage().for_crate(&saved.name);
+        if let Err(e) = storage::update_usage_notice(rust_output_dir, saved, used) {
+            warn!(
+                "  ⚠ failed to note usage for {}@{}: {e}",
+                saved.name, saved.version
+            );
+        }
+    }
+}
+
+/// Write `UPGRADE.md` for every crate whose locked version moved since the
+/// previous sync, and `_UPGRADES.md` listing them. A crate folder re-saved
+/// after its move gets its brief back from the recorded origin. Release notes
+/// are fetched only for those crates; failures there leave the changelog
+/// entries alone. Records this sync's versions for the next one.
+async fn write_upgrade_briefs(
+    config: &Config,
+    rust_output_dir: &Path,
+    previous_sync: &storage::SyncState,
+    saved_crates: &mut [storage::SavedCrate],
+    github_fetcher: &GitHubFetcher,
+) -> Result<()> {
+    let mut next_sync = previous_sync.clone();
+    let mut briefs = Vec::new();
+    for saved in saved_crates.iter_mut() {
+        let origin = previous_sync.upgrade_origin(&saved.name, &saved.version);
+        next_sync.crates.insert(
+            saved.name.clone(),
+            storage::SyncedCrateState {
+                version: saved.version.clone(),
+                upgraded_from: origin.as_ref().map(|o| o.from.clone()),
+            },
+        );
+        let Some(origin) = origin else {
+            continue;
+        };
+        if !origin.moved_now && saved.files.iter().any(|f| f == UPGRADE_FILE) {
+            continue;
+        }
+        let previous = &origin.from;
+
+        let changelog = storage::read_changelog_entries(rust_output_dir, saved);
+        let releases = match config.crates.get(&saved.name).and_then(|c| c.github_repo()) {
+            Some(repo) => github_fetcher
+                .fetch_releases(repo)
+                .await
+                .unwrap_or_else(|e| {
+                    warn!("  ⚠ release notes unavailable for {}: {e}", saved.name);
+                    Vec::new()
+                }),
+            None => Vec::new(),
+        };
+
+        let brief = UpgradeBrief::new(&saved.name, previous, &saved.version, &changelog, &releases);
+        info!(
+            "  ⬆ {}: {previous} → {} ({} release entries)",
+            saved.name,
+            saved.version,
+            brief.entries.len()
+        );
+        storage::write_upgrade_brief(
+            rust_output_dir,
+            saved,
+            &render_upgrade_markdown(&brief),
+            config.settings.token_estimator,
+        )?;
+        if origin.moved_now {
+            briefs.push(brief);
+        }
+    }
+
+    let index = (!briefs.is_empty()).then(|| render_upgrades_index(&briefs));
+    storage::write_upgrades_index(rust_output_dir, index.as_deref())?;
+    storage::write_sync_state(rust_output_dir, &next_sync)
+}
+
+/// Upstream releases of each synced crate from one sparse-index request, shared
+/// by the update check and the API diff; `None` where the lookup failed.
+async fn lookup_upstream(
+    settings: &crate::config::Settings,
+    saved_crates: &[storage::SavedCrate],
+) -> Vec<Option<UpstreamVersions>> {
+    let registry = LatestDocsFetcher::from_settings(settings);
+    let mut upstream = Vec::with_capacity(saved_crates.len());
+    for saved in saved_crates {
+        upstream.push(match registry.fetch_index_versions(&saved.name).await {
+            Ok(versions) => Some(upstream_versions(&versions, &saved.version)),
+            Err(e) => {
+                warn!("  ⚠ sparse index lookup failed for {}: {e}", saved.name);
+                None
+            }
+        });
+    }
+    upstream
+}
+
+/// Note newer upstream releases in each synced crate's `_SUMMARY.md`
+/// (`settings.check_updates`); index lookups that fail leave the summary as is.
+fn annotate_updates(
+    rust_output_dir: &Path,
+    saved_crates: &[storage::SavedCrate],
+    upstream: &[Option<UpstreamVersions>],
+) {
+    for (saved, upstream) in saved_crates.iter().zip(upstream) {
+        let Some(upstream) = upstream else {
+            continue;
+        };
+        if let Some(newer) = upstream.newer_than(&saved.version) {
+            info!("  ⬆ {}@{}: {newer} is available", saved.name, saved.version);
+        }
+        if let Err(e) = storage::update_upstream_notice(rust_output_dir, saved, upstream) {
+            warn!(
+                "  ⚠ failed to write update notice for {}@{}: {e}",
+                saved.name, saved.version
+            );
+        }
+    }
+}
+
+/// Hybrid mode with `settings.api_diff`: write `API_DIFF.md` for locked crates
+/// that lag the latest release, comparing the rustdoc JSON docs.rs publishes for
+/// both versions. A diff for the same pair of versions is kept instead of being
+/// refetched.
+async fn write_api_diffs(
+    settings: &crate::config::Settings,
+    rust_output_dir: &Path,
+    saved_crates: &mut [storage::SavedCrate],
+    upstream: &[Option<UpstreamVersions>],
+) {
+    let registry = LatestDocsFetcher::from_settings(settings);
+    for (saved, upstream) in saved_crates.iter_mut().zip(upstream) {
+        let Some(upstream) = upstream else {
+            continue;
+        };
+        let Some(latest) = upstream.newer_than(&saved.version).map(str::to_string) else {
+            if let Err(e) = storage::update_api_diff(rust_output_dir, saved, None) {
+                warn!(
+                    "  ⚠ failed to remove stale API diff for {}: {e}",
+                    saved.name
+                );
+            }
+            continue;
+        };
+
+        let title = api_diff_title(&saved.name, &saved.version, &latest);
+        if storage::has_api_diff(rust_output_dir, saved, &title) {
+            continue;
+        }
+        let diff = match fetch_api_diff(&registry, &saved.name, &saved.version, &latest).await {
+            Ok(diff) => diff,
+            Err(e) => {
+                warn!(
+                    "  ⚠ API diff unavailable for {}@{} → {latest}: {e}",
+                    saved.name, saved.version
+                );
+                continue;
+            }
+        };
+        info!(
+            "  Δ {}@{} → {latest}: {} added, {} changed, {} deprecated, {} removed",
+            saved.name,
+            saved.version,
+            diff.added.len(),
+            diff.changed.len(),
+            diff.deprecated.len(),
+            diff.removed.len()
+        );
+        let markdown = render_api_diff_markdown(&saved.name, &saved.version, &latest, &diff);
+        let (markdown, _) = storage::truncate_if_needed(&markdown, settings.max_file_size_kb);
+        if let Err(e) = storage::update_api_diff(rust_output_dir, saved, Some(&markdown)) {
+            warn!("  ⚠ failed to write API diff for {}: {e}", saved.name);
+        }
+    }
+}
+
+async fn fetch_api_diff(
+    registry: &LatestDocsFetcher,
+    crate_name: &str,
+    locked: &str,
+    latest: &str,
+) -> Result<ApiDiff> {
+    let locked_doc = registry.fetch_rustdoc_json(crate_name, locked).await?;
+    let latest_doc = registry.fetch_rustdoc_json(crate_name, latest).await?;
+    Ok(diff_api(
+        &api_surface(&locked_doc)?,
+        &api_surface(&latest_doc)?,
+    ))
+}
+
+/// Local crate sources and the project's enabled features, shared by every crate in a sync.
+async fn load_local_sources() -> Arc<LocalSources> {
+    let project_dir = std::env::current_dir().unwrap_or_else(|_| PathBuf::from("."));
+    Arc::new(LocalSources::load(&project_dir).await)
+}
+
+/// `FEATURES.md` from the crate's own `Cargo.toml` when its source is on disk,
+/// otherwise from the docs.rs features page; `None` when neither is reachable.
+async fn fetch_features_file(
+    latest_fetcher: &LatestDocsFetcher,
+    local_sources: &LocalSources,
+    crate_name: &str,
+    version: &str,
+    crate_doc: &crate::config::CrateDoc,
+) -> Option<FetchedFile> {
+    let enabled = local_sources.enabled_features(crate_name, version);
+    let source_dir = match &crate_doc.source_dir {
+        Some(dir) => Some(local_sources.project_dir().join(dir)),
+        None => local_sources.find_source_dir(crate_name, version),
+    };
+    let local_manifest =
+        source_dir.and_then(|dir| read_original_manifest(&dir).map(|text| (dir, text)));
+
+    let (source_url, content) = match local_manifest {
+        Some((dir, manifest)) => {
+            let source_url = dir.display().to_string();
+            let content = render_features_markdown(
+                crate_name,
+                version,
+                &parse_manifest_features(&manifest),
+                FeatureSource::Manifest(&source_url),
+                enabled,
+            );
+            (source_url, content)
+        }
+        None => match latest_fetcher.fetch_features(crate_name, version).await {
+            Ok((url, features)) => {
+                let content = render_features_markdown(
+                    crate_name,
+                    version,
+                    &features,
+                    FeatureSource::DocsRs(&url),
+                    enabled,
+                );
+                (url, content)
+            }
+            Err(e) => {
+                warn!("  ⚠ feature flags unavailable for {crate_name}@{version}: {e}");
+                return None;
+            }
+        },
+    };
+
+    Some(FetchedFile {
+        path: FEATURES_FILE.to_string(),
+        source_url,
+        content,
+    })
+}
+
+/// crates.io facts for `_SUMMARY.md` unless `settings.crates_io_metadata` is
+/// off; a failure only drops that section.
+async fn fetch_crates_io_metadata(
+    latest_fetcher: &LatestDocsFetcher,
+    crate_name: &str,
+    version: &str,
+    options: SyncOptions,
+) -> Option<CratesIoMetadata> {
+    if !options.crates_io_metadata {
+        return None;
+    }
+    match latest_fetcher
+        .fetch_crate_metadata(crate_name, version)
+        .await
+    {
+        Ok(metadata) => Some(metadata),
+        Err(e) => {
+            warn!("  ⚠ crates.io metadata unavailable for {crate_name}@{version}: {e}");
+            None
+        }
+    }
+}
+
+/// GitHub `archived` flag of the configured repo; unknown or not checked
+/// (`settings.check_archived`) counts as not archived.
+async fn fetch_repo_archived(
+    github_fetcher: &GitHubFetcher,
+    crate_doc: &crate::config::CrateDoc,
+    options: SyncOptions,
+) -> bool {
+    let Some(repo) = crate_doc.github_repo().filter(|_| options.check_archived) else {
+        return false;
+    };
+    match github_fetcher.is_archived(repo).await {
+        Ok(archived) => archived,
+        Err(e) => {
+            warn!("  ⚠ could not read archived flag of {repo}: {e}");
+            false
+        }
+    }
+}
+
+/// Add `FEATURES.md` next to the `API.md` of a docs.rs / rustdoc artifact.
+fn attach_features_file(artifact: &mut DocsRsArtifact, features: Option<FetchedFile>) {
+    if let Some(file) = features {
+        artifact.module_files.push(ModuleFile {
+            path: file.path,
+            markdown: file.content,
+        });
+    }
+}
+
+/// The crate repository's changelog at `git_ref` (under `subpath` when set);
+/// a missing file or a failed fetch yields nothing.
+async fn load_repo_changelog(
+    github_fetcher: &GitHubFetcher,
+    repo: &str,
+    git_ref: &str,
+    crate_doc: &crate::config::CrateDoc,
+) -> Option<FetchedFile> {
+    let requests: Vec<_> = build_requests(crate_doc.subpath.as_deref(), None)
+        .into_iter()
+        .filter(|r| r.original_path.ends_with(storage::CHANGELOG_FILE))
+        .collect();
+    github_fetcher
+        .fetch_files(repo, git_ref, &requests)
+        .await
+        .pop()?
+        .ok()
+}
+
+/// `examples/*.rs` from the crate repository at `git_ref`; failures only cost
+/// the examples section.
+async fn load_repo_examples(
+    github_fetcher: &GitHubFetcher,
+    repo: &str,
+    git_ref: &str,
+    crate_doc: &crate::config::CrateDoc,
+) -> Vec<Example> {
+    match github_fetcher
+        .fetch_examples(repo, git_ref, crate_doc.subpath.as_deref())
+        .await
+    {
+        Ok(files) => files
+            .iter()
+            .map(|f| example_file(&f.path, &f.content))
+            .collect(),
+        Err(e) => {
+            warn!("  ⚠ could not load examples from {repo}@{git_ref}: {e}");
+            Vec::new()
+        }
+    }
+}
+
+/// Structured API reference for the configured `api_source`; `None` means the
+/// docs.rs HTML path should be used directly.
+async fn fetch_structured_api(
+    latest_fetcher: &LatestDocsFetcher,
+    local_sources: &Arc<LocalSources>,
+    crate_name: &str,
+    version: &str,
+    crate_doc: &crate::config::CrateDoc,
+    options: SyncOptions,
+    repo_examples: &[Example],
+) -> Option<Result<DocsRsArtifact>> {
+    let max_file_size_kb = options.max_file_size_kb;
+    match options.api_source {
+        ApiSource::DocsRsHtml => None,
+        ApiSource::RustdocJson => Some(
+            latest_fetcher
+                .fetch_rustdoc_api_markdown(
+                    crate_name,
+                    version,
+                    max_file_size_kb,
+                    crate_doc.rustdoc_json.as_deref(),
+                    repo_examples,
+                    &local_sources.usage().relative_paths(crate_name),
+                )
+                .await,
+        ),
+        ApiSource::LocalRustdoc => Some(
+            LocalRustdocBuilder::new(Arc::clone(local_sources))
+                .build_api_markdown(
+                    crate_name,
+                    version,
+                    max_file_size_kb,
+                    crate_doc.source_dir.as_deref(),
+                    repo_examples,
+                )
+                .await,
+        ),
+    }
+}
+
+async fn fetch_docsrs_html(
+    latest_fetcher: &LatestDocsFetcher,
+    local_sources: &LocalSources,
+    crate_name: &str,
+    version: &str,
+    crate_doc: &crate::config::CrateDoc,
+    options: SyncOptions,
+    repo_examples: &[Example],
+) -> Result<DocsRsArtifact> {
+    if options.docsrs_single_page {
+        return latest_fetcher
+            .fetch_api_markdown(crate_name, version, options.max_file_size_kb, repo_examples)
+            .await;
+    }
+
+    let crawl = CrawlOptions {
+        max_pages: options.docsrs_max_pages,
+        max_depth: options.docsrs_max_depth,
+        include_modules: crate_doc.docsrs_include_modules.clone(),
+        exclude_modules: crate_doc.docsrs_exclude_modules.clone(),
+        used_paths: local_sources.usage().relative_paths(crate_name),
+    };
+    latest_fetcher
+        .crawl_api_markdown(
+            crate_name,
+            version,
+            options.max_file_size_kb,
+            &crawl,
+            repo_examples,
+        )
+        .await
+}
+
+/// What the latest-docs path already knows when docs.rs fails and GitHub takes over.
+struct GitHubFallback {
+    reason: &'static str,
+    extra_files: Vec<FetchedFile>,
+    crates_io: Option<CratesIoMetadata>,
+    repo_archived: bool,
+    locked_version: Option<String>,
+}
+
 async fn sync_one_crate_from_github(
     rust_output_dir: PathBuf,
     fetcher: Arc<GitHubFetcher>,
     crate_name: String,
     crate_doc: crate::config::CrateDoc,
     version: String,
-    max_file_size_kb: usize,
-    source_kind_override: Option<&'static str>,
+    options: SyncOptions,
+    fallback: Option<GitHubFallback>,
 ) -> SyncOutcome {
     let Some(repo) = crate_doc.github_repo().map(str::to_string) else {
         warn!("Crate '{crate_name}' has no GitHub repo in config");
-        if source_kind_override.is_some() {
+        if fallback.is_some() {
             return SyncOutcome::Error(SyncErrorKind::Other);
         }
         return SyncOutcome::Skipped;
@@ -448,22 +1174,49 @@ async fn sync_one_crate_from_github(
         .fetch_files(&repo, &resolved.git_ref, &requests)
         .await;
 
-    let fetched_files = collect_fetched_files(results, &crate_name, &version);
+    let mut fetched_files = collect_fetched_files(results, &crate_name, &version);
+    let (source_kind, fallback_reason, crates_io, repo_archived, locked_version) = match fallback {
+        Some(fallback) => {
+            fetched_files.files.extend(fallback.extra_files);
+            (
+                "github_fallback",
+                Some(fallback.reason),
+                fallback.crates_io,
+                fallback.repo_archived,
+                fallback.locked_version,
+            )
+        }
+        None => (
+            "github",
+            None,
+            None,
+            fetch_repo_archived(&fetcher, &crate_doc, options).await,
+            None,
+        ),
+    };
+    if fetched_files.non_optional_errors > 0 {
+        warn!(
+            "  ⚠ {crate_name}@{version}: {} required file(s) could not be fetched",
+            fetched_files.non_optional_errors
+        );
+    }
     if fetched_files.files.is_empty() {
         warn!("  ✗ no files fetched for {crate_name}@{version}");
         return SyncOutcome::Error(SyncErrorKind::NotFound);
     }
 
-    let source_kind = source_kind_override.unwrap_or("github");
     let save_ctx = storage::SaveContext {
         repo: &repo,
         resolved: &resolved,
-        max_file_size_kb,
+        limits: options.output_limits(),
+        content: options.content_settings(),
         source_kind,
         artifact_path: None,
         docsrs_input_url: None,
         upstream_latest_version: Some(&version),
-        truncated: None,
+        fallback_reason,
+        crates_io: crates_io.as_ref(),
+        repo_archived,
     };
 
     let save_req = storage::SaveRequest {
@@ -471,6 +1224,7 @@ async fn sync_one_crate_from_github(
         version: &version,
         fetched_files: &fetched_files.files,
         crate_config: &crate_doc,
+        locked_version: locked_version.as_deref(),
     };
 
     match storage::save_crate_files(&rust_output_dir, &save_ctx, save_req) {
@@ -483,17 +1237,25 @@ async fn sync_one_crate(
     rust_output_dir: PathBuf,
     rust_versions: std::collections::HashMap<String, String>,
     fetcher: Arc<GitHubFetcher>,
+    local_sources: Arc<LocalSources>,
     crate_name: String,
     crate_doc: crate::config::CrateDoc,
-    force: bool,
-    max_file_size_kb: usize,
+    options: SyncOptions,
 ) -> SyncOutcome {
     let Some(version) = rust_versions.get(crate_name.as_str()).cloned() else {
         warn!("Crate '{crate_name}' not found in Cargo.lock, skipping");
         return SyncOutcome::Skipped;
     };
 
-    if !force && storage::is_cached(&rust_output_dir, &crate_name, &version, &crate_doc) {
+    if !options.force
+        && storage::is_cached(
+            &rust_output_dir,
+            &crate_name,
+            &version,
+            &crate_doc,
+            &options.content_settings(),
+        )
+    {
         info!("  ⏭ {crate_name}@{version}: cached, skipping");
         let cached = storage::read_cached_info(&rust_output_dir, &crate_name, &version, &crate_doc);
         return SyncOutcome::Cached(cached);
@@ -504,10 +1266,11 @@ async fn sync_one_crate(
     sync_one_crate_hybrid(
         rust_output_dir,
         fetcher,
+        local_sources,
         crate_name,
         crate_doc,
         version,
-        max_file_size_kb,
+        options,
     )
     .await
 }
@@ -515,41 +1278,111 @@ async fn sync_one_crate(
 async fn sync_one_crate_hybrid(
     rust_output_dir: PathBuf,
     github_fetcher: Arc<GitHubFetcher>,
+    local_sources: Arc<LocalSources>,
     crate_name: String,
     crate_doc: crate::config::CrateDoc,
     version: String,
-    max_file_size_kb: usize,
+    options: SyncOptions,
 ) -> SyncOutcome {
-    // 1. Try fetching from docs.rs first
+    let max_file_size_kb = options.max_file_size_kb;
     let latest_fetcher = LatestDocsFetcher::new();
-    let docsrs_readme = match latest_fetcher
-        .fetch_api_markdown(&crate_name, &version, max_file_size_kb)
-        .await 
-    {
-        Ok(artifact) => {
-            info!("  ✓ {crate_name}@{version}: description fetched from docs.rs");
+
+    // 1. Resolve GitHub Ref and load repository examples
+    let repo = crate_doc.github_repo().map(str::to_string);
+    let resolved = match &repo {
+        Some(repo) => match github_fetcher
+            .resolve_ref(repo, &crate_name, version.as_str())
+            .await
+        {
+            Ok(r) => Some(r),
+            Err(e) => {
+                warn!("  ✗ failed to resolve ref for {crate_name}@{version}: {e}");
+                return SyncOutcome::Error(e.sync_kind());
+            }
+        },
+        None => None,
+    };
+    let repo_examples = match (&repo, &resolved) {
+        (Some(repo), Some(resolved)) if options.repo_examples => {
+            load_repo_examples(&github_fetcher, repo, &resolved.git_ref, &crate_doc).await
+        }
+        _ => Vec::new(),
+    };
+    let features_file = fetch_features_file(
+        &latest_fetcher,
+        &local_sources,
+        &crate_name,
+        &version,
+        &crate_doc,
+    )
+    .await;
+    let crates_io = fetch_crates_io_metadata(&latest_fetcher, &crate_name, &version, options).await;
+    let repo_archived = fetch_repo_archived(&github_fetcher, &crate_doc, options).await;
+
+    // 2. Prefer a structured API reference from rustdoc JSON when configured
+    let structured = fetch_structured_api(
+        &latest_fetcher,
+        &local_sources,
+        &crate_name,
+        &version,
+        &crate_doc,
+        options,
+        &repo_examples,
+    )
+    .await;
+    let rustdoc_api = match structured {
+        Some(Ok(artifact)) => {
+            info!(
+                "  ✓ {crate_name}@{version}: API reference rendered via {}",
+                options.api_source.as_str()
+            );
             Some(artifact)
         }
-        Err(e) => {
-            warn!("  ⚠️ docs.rs fetch failed for {crate_name}@{version}: {e}; will use GitHub README");
+        Some(Err(e)) => {
+            warn!(
+                "  ⚠️ {} unavailable for {crate_name}@{version}: {e}; using docs.rs HTML",
+                options.api_source.as_str()
+            );
             None
         }
+        None => None,
     };
 
-    // 2. Resolve GitHub Ref
-    let Some(repo) = crate_doc.github_repo().map(str::to_string) else {
+    // 3. Otherwise try the docs.rs landing page
+    let docsrs_readme = if rustdoc_api.is_some() {
+        None
+    } else {
+        match latest_fetcher
+            .fetch_api_markdown(&crate_name, &version, max_file_size_kb, &repo_examples)
+            .await
+        {
+            Ok(artifact) => {
+                info!("  ✓ {crate_name}@{version}: description fetched from docs.rs");
+                Some(artifact)
+            }
+            Err(e) => {
+                warn!("  ⚠️ docs.rs fetch failed for {crate_name}@{version}: {e}; will use GitHub README");
+                None
+            }
+        }
+    };
+
+    let (Some(repo), Some(resolved)) = (repo, resolved) else {
         warn!("Crate '{crate_name}' has no GitHub repo in config");
         // Fallback: if we have docs.rs content, save it and consider it synced.
-        if let Some(art) = docsrs_readme {
-             match storage::save_latest_api_markdown(
+        if let Some(mut art) = rustdoc_api.or(docsrs_readme) {
+            attach_features_file(&mut art, features_file);
+            match storage::save_latest_api_markdown(
                 &rust_output_dir,
                 &crate_name,
                 &version,
-                &art.markdown,
-                &art.docsrs_input_url,
-                art.truncated,
-                max_file_size_kb,
+                &art,
+                &options.output_limits(),
+                &options.content_settings(),
                 &crate_doc,
+                crates_io.as_ref(),
+                repo_archived,
+                None,
             ) {
                 Ok(saved) => return SyncOutcome::Synced(saved),
                 Err(e) => return SyncOutcome::Error(e.sync_kind()),
@@ -558,18 +1391,7 @@ async fn sync_one_crate_hybrid(
         return SyncOutcome::Skipped;
     };
 
-    let resolved = match github_fetcher
-        .resolve_ref(&repo, &crate_name, version.as_str())
-        .await
-    {
-        Ok(r) => r,
-        Err(e) => {
-            warn!("  ✗ failed to resolve ref for {crate_name}@{version}: {e}");
-            return SyncOutcome::Error(e.sync_kind());
-        }
-    };
-
-    // 3. Build Requests
+    // 4. Build Requests
     let mut requests = build_requests(crate_doc.subpath.as_deref(), crate_doc.effective_files());
     
     // If we have docs.rs README, remove README from GitHub requests
@@ -577,14 +1399,21 @@ async fn sync_one_crate_hybrid(
         requests.retain(|r| !is_readme_request(&r.original_path));
     }
 
-    // 4. Fetch from GitHub
+    // 5. Fetch from GitHub
     let results = github_fetcher
         .fetch_files(&repo, &resolved.git_ref, &requests)
         .await;
 
     let mut fetch_collection = collect_fetched_files(results, &crate_name, &version);
 
-    // 5. Inject docs.rs README if available
+    // 6. Inject the rustdoc JSON API reference or docs.rs README if available
+    if let Some(art) = rustdoc_api {
+        fetch_collection.files.push(FetchedFile {
+            path: "API.md".to_string(),
+            source_url: art.docsrs_input_url.clone(),
+            content: art.markdown,
+        });
+    }
     if let Some(art) = docsrs_readme {
         fetch_collection.files.push(FetchedFile {
             path: "README.md".to_string(),
@@ -592,6 +1421,7 @@ async fn sync_one_crate_hybrid(
             content: art.markdown,
         });
     }
+    fetch_collection.files.extend(features_file);
 
     if fetch_collection.files.is_empty() {
         warn!("  ✗ no files fetched for {crate_name}@{version}");
@@ -601,12 +1431,15 @@ async fn sync_one_crate_hybrid(
     let save_ctx = storage::SaveContext {
         repo: &repo,
         resolved: &resolved,
-        max_file_size_kb,
+        limits: options.output_limits(),
+        content: options.content_settings(),
         source_kind: "hybrid_docsrs_github",
         artifact_path: None,
         docsrs_input_url: None, // We embedded it in the file source_url
         upstream_latest_version: Some(&version),
-        truncated: None,
+        fallback_reason: None,
+        crates_io: crates_io.as_ref(),
+        repo_archived,
     };
 
     let save_req = storage::SaveRequest {
@@ -614,6 +1447,7 @@ async fn sync_one_crate_hybrid(
         version: &version,
         fetched_files: &fetch_collection.files,
         crate_config: &crate_doc,
+        locked_version: None,
     };
 
     match storage::save_crate_files(&rust_output_dir, &save_ctx, save_req) {
@@ -745,15 +1579,16 @@ const fn should_emit_plain_check_errors(format: OutputFormat, github_actions: bo
     !github_actions && matches!(format, OutputFormat::Table)
 }
 
-fn emit_check_failures_for_ci(format: OutputFormat, statuses: &[crate::status::CrateStatus]) {
+fn emit_check_failures_for_ci(
+    format: OutputFormat,
+    statuses: &[crate::status::CrateStatus],
+    settings: &crate::config::Settings,
+) {
     let github_actions = std::env::var("GITHUB_ACTIONS")
         .ok()
         .is_some_and(|v| v == "true");
 
-    for status in statuses
-        .iter()
-        .filter(|s| !matches!(s.status, DocsStatus::Synced | DocsStatus::SyncedFallback))
-    {
+    for status in statuses.iter().filter(|s| s.status.fails_check(settings)) {
         if github_actions {
             eprintln!(
                 "::error title=ai-fdocs check::{} [{}] {}",
@@ -790,9 +1625,11 @@ async fn run_status(
     config_path: &Path,
     mode_override: Option<SyncModeArg>,
     format: OutputFormat,
+    check_updates: bool,
 ) -> Result<()> {
-    let config = Config::load(config_path)?;
+    let mut config = Config::load(config_path)?;
     info!("Loaded config from {}", config_path.display());
+    config.settings.check_updates |= check_updates;
     let rust_dir = storage::rust_output_dir(&config.settings.output_dir);
 
     let sync_mode = resolve_sync_mode(mode_override, config.settings.sync_mode);
@@ -801,10 +1638,11 @@ async fn run_status(
         SyncMode::Lockfile | SyncMode::Hybrid => {
             let rust_versions =
                 resolver::resolve_cargo_versions(PathBuf::from("Cargo.lock").as_path())?;
-            collect_status(&config, &rust_versions, &rust_dir).await
+            let registry = LatestDocsFetcher::from_settings(&config.settings);
+            collect_status(&config, &rust_versions, &rust_dir, Some(&registry)).await
         }
         SyncMode::LatestDocs => {
-            let fetcher = LatestDocsFetcher::new();
+            let fetcher = LatestDocsFetcher::from_settings(&config.settings);
             collect_status_latest(&config, &rust_dir, Some(&fetcher)).await
         }
     };
@@ -827,22 +1665,23 @@ async fn run_check(
         SyncMode::Lockfile | SyncMode::Hybrid => {
             let rust_versions =
                 resolver::resolve_cargo_versions(PathBuf::from("Cargo.lock").as_path())?;
-            collect_status(&config, &rust_versions, &rust_dir).await
+            let registry = LatestDocsFetcher::from_settings(&config.settings);
+            collect_status(&config, &rust_versions, &rust_dir, Some(&registry)).await
         }
         SyncMode::LatestDocs => {
-            let fetcher = LatestDocsFetcher::new();
+            let fetcher = LatestDocsFetcher::from_settings(&config.settings);
             collect_status_latest(&config, &rust_dir, Some(&fetcher)).await
         }
     };
     let failing = statuses
         .iter()
-        .any(|s| !matches!(s.status, DocsStatus::Synced | DocsStatus::SyncedFallback));
+        .any(|s| s.status.fails_check(&config.settings));
 
     if failing {
         print_statuses(format, &statuses)?;
-        emit_check_failures_for_ci(format, &statuses);
+        emit_check_failures_for_ci(format, &statuses, &config.settings);
         return Err(error::AiDocsError::Other(
-            "Documentation is outdated, missing, or corrupted. Run: cargo ai-fdocs sync"
+            "Documentation is outdated, missing, corrupted, or tracks a yanked/deprecated crate. Run: cargo ai-fdocs sync"
                 .to_string(),
         ));
     }
@@ -855,6 +1694,30 @@ async fn run_check(
     Ok(())
 }
 
+fn run_cost(
+    config_path: &Path,
+    budget: Option<usize>,
+    top: usize,
+    format: OutputFormat,
+) -> Result<()> {
+    let config = Config::load(config_path)?;
+    info!("Loaded config from {}", config_path.display());
+    let rust_dir = storage::rust_output_dir(&config.settings.output_dir);
+
+    let report = cost::collect_cost(&rust_dir, config.settings.token_estimator, top, budget);
+    match format {
+        OutputFormat::Table => print!("{}", cost::format_cost_table(&report)),
+        OutputFormat::Json => {
+            let json = serde_json::to_string_pretty(&report).map_err(|e| {
+                error::AiDocsError::Other(format!("failed to serialize cost JSON: {e}"))
+            })?;
+            println!("{json}");
+        }
+    }
+
+    Ok(())
+}
+
 #[cfg(test)]
 mod tests {
     use super::{
@@ -986,7 +1849,7 @@ mod tests {
         let mut command = super::Cli::command();
         command.build();
 
-        for sub in ["sync", "status", "check", "init"] {
+        for sub in ["sync", "status", "check", "cost", "init"] {
             let sub_cmd = command
                 .find_subcommand(sub)
                 .unwrap_or_else(|| panic!("missing subcommand: {sub}"));
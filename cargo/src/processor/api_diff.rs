@@ -0,0 +1,218 @@
+use std::collections::BTreeMap;
+
+use crate::processor::rustdoc_json::ApiItem;
+
+pub const API_DIFF_FILE: &str = "API_DIFF.md";
+
+/// Public API differences between the locked version of a crate and a newer release.
+#[derive(Debug, Default)]
+pub struct ApiDiff {
+    /// Items only the newer release has.
+    pub added: Vec<ApiItem>,
+    /// Items the newer release no longer has at the same path.
+    pub removed: Vec<ApiItem>,
+    /// `(locked, latest)` pairs whose declaration changed.
+    pub changed: Vec<(ApiItem, ApiItem)>,
+    /// Items deprecated in the newer release but not in the locked one.
+    pub deprecated: Vec<ApiItem>,
+}
+
+impl ApiDiff {
+    pub fn is_empty(&self) -> bool {
+        self.added.is_empty()
+            && self.removed.is_empty()
+            && self.changed.is_empty()
+            && self.deprecated.is_empty()
+    }
+}
+
+/// Compare two API surfaces item by item; items are matched on path and kind.
+pub fn diff_api(locked: &[ApiItem], latest: &[ApiItem]) -> ApiDiff {
+    let by_key = |items: &[ApiItem]| -> BTreeMap<(String, String), ApiItem> {
+        let mut map = BTreeMap::new();
+        for item in items {
+            map.entry((item.path.clone(), item.kind.clone()))
+                .or_insert_with(|| item.clone());
+        }
+        map
+    };
+    let locked = by_key(locked);
+    let latest = by_key(latest);
+
+    let mut diff = ApiDiff::default();
+    for (key, new) in &latest {
+        match locked.get(key) {
+            None => diff.added.push(new.clone()),
+            Some(old) => {
+                if old.signature != new.signature {
+                    diff.changed.push((old.clone(), new.clone()));
+                }
+                if new.deprecated && !old.deprecated {
+                    diff.deprecated.push(new.clone());
+                }
+            }
+        }
+    }
+    diff.removed = locked
+        .iter()
+        .filter(|(key, _)| !latest.contains_key(*key))
+        .map(|(_, item)| item.clone())
+        .collect();
+    diff
+}
+
+/// First line of `API_DIFF.md`; an existing file that starts with it is current.
+pub fn api_diff_title(crate_name: &str, locked: &str, latest: &str) -> String {
+    format!("# {crate_name}@{locked} — API changes up to {latest}")
+}
+
+/// `API_DIFF.md`: what the locked version lacks compared to `latest`, and what
+/// an upgrade would change or remove.
+pub fn render_api_diff_markdown(
+    crate_name: &str,
+    locked: &str,
+    latest: &str,
+    diff: &ApiDiff,
+) -> String {
+    let mut out = api_diff_title(crate_name, locked, latest);
+    out.push_str("\n\n");
+    out.push_str(&format!(
+        "Compared from the rustdoc JSON of both versions. This project is locked to `{locked}`: do not suggest items listed under \"Added\", and use the `{locked}` declarations below rather than the newer ones.\n\n"
+    ));
+
+    if diff.is_empty() {
+        out.push_str("No public API differences were found between these versions.\n");
+        return out;
+    }
+    out.push_str(&format!(
+        "- Added: {}\n- Changed: {}\n- Newly deprecated: {}\n- Removed: {}\n\n",
+        diff.added.len(),
+        diff.changed.len(),
+        diff.deprecated.len(),
+        diff.removed.len()
+    ));
+
+    if !diff.added.is_empty() {
+        out.push_str(&format!(
+            "## Added in {latest} (not available in {locked})\n\n"
+        ));
+        for item in &diff.added {
+            push_item(&mut out, item, &[("", &item.signature)], "rust");
+        }
+        out.push('\n');
+    }
+    if !diff.changed.is_empty() {
+        out.push_str(&format!("## Changed signatures ({locked} → {latest})\n\n"));
+        for (old, new) in &diff.changed {
+            push_item(
+                &mut out,
+                new,
+                &[("- ", &old.signature), ("+ ", &new.signature)],
+                "diff",
+            );
+        }
+        out.push('\n');
+    }
+    if !diff.deprecated.is_empty() {
+        out.push_str(&format!("## Deprecated in {latest}\n\n"));
+        for item in &diff.deprecated {
+            out.push_str(&format!("- `{}` ({})\n", item.path, item.kind));
+        }
+        out.push('\n');
+    }
+    if !diff.removed.is_empty() {
+        out.push_str(&format!("## Removed or moved in {latest}\n\n"));
+        for item in &diff.removed {
+            push_item(&mut out, item, &[("", &item.signature)], "rust");
+        }
+        out.push('\n');
+    }
+
+    out.trim_end().to_string() + "\n"
+}
+
+/// A list entry with the item path and its declaration(s) in an indented code block.
+fn push_item(out: &mut String, item: &ApiItem, signatures: &[(&str, &str)], lang: &str) {
+    out.push_str(&format!(
+        "- `{}` ({})\n\n  ```{lang}\n",
+        item.path, item.kind
+    ));
+    for (prefix, signature) in signatures {
+        for line in signature.lines() {
+            out.push_str(&format!("  {prefix}{line}\n"));
+        }
+    }
+    out.push_str("  ```\n");
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{api_diff_title, diff_api, render_api_diff_markdown};
+    use crate::processor::rustdoc_json::ApiItem;
+
+    fn item(path: &str, kind: &str, signature: &str, deprecated: bool) -> ApiItem {
+        ApiItem {
+            path: path.to_string(),
+            kind: kind.to_string(),
+            signature: signature.to_string(),
+            deprecated,
+        }
+    }
+
+    #[test]
+    fn diffs_added_removed_changed_and_deprecated_items() {
+        let locked = vec![
+            item(
+                "demo::parse",
+                "function",
+                "pub fn parse(input: &str) -> Counter",
+                false,
+            ),
+            item("demo::Counter::old", "method", "pub fn old(&self)", false),
+            item("demo::legacy", "function", "pub fn legacy()", false),
+            item("demo::Counter", "struct", "pub struct Counter {}", false),
+        ];
+        let latest = vec![
+            item(
+                "demo::parse",
+                "function",
+                "pub async fn parse(input: &str) -> Result<Counter, DemoError>",
+                false,
+            ),
+            item("demo::Counter::old", "method", "pub fn old(&self)", true),
+            item(
+                "demo::Counter::reset",
+                "method",
+                "pub fn reset(&mut self)",
+                false,
+            ),
+            item("demo::Counter", "struct", "pub struct Counter {}", false),
+        ];
+
+        let diff = diff_api(&locked, &latest);
+        assert_eq!(diff.added.len(), 1);
+        assert_eq!(diff.added[0].path, "demo::Counter::reset");
+        assert_eq!(diff.removed.len(), 1);
+        assert_eq!(diff.removed[0].path, "demo::legacy");
+        assert_eq!(diff.changed.len(), 1);
+        assert_eq!(diff.deprecated.len(), 1);
+        assert_eq!(diff.deprecated[0].path, "demo::Counter::old");
+
+        let md = render_api_diff_markdown("demo", "0.3.1", "0.4.0", &diff);
+        assert!(md.starts_with(&api_diff_title("demo", "0.3.1", "0.4.0")));
+        assert!(md.contains("## Added in 0.4.0 (not available in 0.3.1)\n\n- `demo::Counter::reset` (method)\n\n  ```rust\n  pub fn reset(&mut self)\n  ```\n"));
+        assert!(md.contains("  - pub fn parse(input: &str) -> Counter\n  + pub async fn parse(input: &str) -> Result<Counter, DemoError>\n"));
+        assert!(md.contains("## Deprecated in 0.4.0\n\n- `demo::Counter::old` (method)\n"));
+        assert!(md.contains("## Removed or moved in 0.4.0\n"));
+        assert!(!md.contains("demo::Counter` (struct)"));
+    }
+
+    #[test]
+    fn reports_identical_surfaces() {
+        let items = vec![item("demo::run", "function", "pub fn run()", false)];
+        let diff = diff_api(&items, &items);
+        assert!(diff.is_empty());
+        assert!(render_api_diff_markdown("demo", "1.0.0", "1.0.1", &diff)
+            .contains("No public API differences"));
+    }
+}
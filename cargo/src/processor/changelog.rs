@@ -1,74 +1,484 @@
+use std::collections::HashMap;
+
 use regex::Regex;
+use serde::{Deserialize, Serialize};
 use tracing::debug;
 
-/// Truncate changelog to entries around the current version:
-/// keep current section(s) + one previous minor series.
-pub fn truncate_changelog(content: &str, current_version: &str) -> String {
-    let heading_re = Regex::new(r"(?m)^#{1,3}\s+.*?\b?\[?v?(\d+\.\d+\.\d+(?:-[\w.]+)?)\]?\b")
-        .expect("valid changelog heading regex");
+use crate::config::ChangelogWindow;
+use crate::version::{self, minor_series, same_release};
 
-    let matches: Vec<(usize, String)> = heading_re
-        .captures_iter(content)
-        .filter_map(|cap| {
-            let version = cap.get(1)?.as_str().to_string();
-            let pos = cap.get(0)?.start();
-            Some((pos, version))
+/// One released version parsed from a changelog.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ChangelogEntry {
+    /// Version as written in the heading (`1.2.3`, `1.2`, `2.0.0-rc.1`).
+    pub version: String,
+    /// Release date when the heading carries an ISO `YYYY-MM-DD` date.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub date: Option<String>,
+    /// Compare/release link from a Keep-a-Changelog heading or link definition.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub url: Option<String>,
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub yanked: bool,
+    pub sections: Vec<ChangelogSection>,
+    /// Markdown under the version heading, link definitions removed.
+    pub body: String,
+}
+
+/// List items under one sub-heading of a version (`### Added`, `**Fixed**`).
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ChangelogSection {
+    pub kind: SectionKind,
+    /// Heading text as written; empty for items listed before any sub-heading.
+    pub title: String,
+    pub items: Vec<String>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SectionKind {
+    Breaking,
+    Added,
+    Changed,
+    Deprecated,
+    Removed,
+    Fixed,
+    Security,
+    Other,
+}
+
+impl SectionKind {
+    fn from_title(title: &str) -> Self {
+        let title = title.to_lowercase();
+        let has = |words: &[&str]| words.iter().any(|w| title.contains(w));
+        if has(&["breaking", "incompatible", "migration"]) {
+            Self::Breaking
+        } else if has(&["security"]) {
+            Self::Security
+        } else if has(&["deprecat"]) {
+            Self::Deprecated
+        } else if has(&["remov"]) {
+            Self::Removed
+        } else if has(&["fix", "bug"]) {
+            Self::Fixed
+        } else if has(&["add", "new", "feature"]) {
+            Self::Added
+        } else if has(&["change", "improv", "update", "perf"]) {
+            Self::Changed
+        } else {
+            Self::Other
+        }
+    }
+}
+
+/// A Markdown heading: ATX (`## 1.2.3`) or setext (text underlined with `===`/`---`).
+struct Heading {
+    start: usize,
+    end: usize,
+    level: usize,
+    text: String,
+}
+
+/// A heading that names a version, with what the heading text says about it.
+struct VersionHeading {
+    index: usize,
+    version: String,
+    date: Option<String>,
+    url: Option<String>,
+    label: String,
+    yanked: bool,
+}
+
+/// `CHANGELOG.json` written next to a synced changelog.
+#[derive(Debug, Serialize)]
+pub struct ChangelogFile<'a> {
+    #[serde(rename = "crate")]
+    pub crate_name: &'a str,
+    pub version: &'a str,
+    pub entries: &'a [ChangelogEntry],
+    /// Oldest entries left out to keep the file within `max_file_size_kb`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub omitted_entries: Option<usize>,
+}
+
+/// Parse a changelog into one entry per version heading, newest first as written.
+///
+/// Version headings are the shallowest headings naming a version; headings
+/// below them become sections. Unversioned headings at that level
+/// (`## [Unreleased]`) end the previous entry without starting a new one.
+pub fn parse_changelog(content: &str) -> Vec<ChangelogEntry> {
+    let headings = headings(content);
+    let versions = version_headings(&headings);
+    let Some(level) = versions.iter().map(|v| headings[v.index].level).min() else {
+        return Vec::new();
+    };
+    let links = link_definitions(content);
+
+    versions
+        .iter()
+        .filter(|v| headings[v.index].level == level)
+        .map(|v| {
+            let heading = &headings[v.index];
+            let body_end = headings[v.index + 1..]
+                .iter()
+                .find(|h| h.level <= level)
+                .map_or(content.len(), |h| h.start);
+            let body = strip_link_definitions(&content[heading.end..body_end]);
+            let url = v.url.clone().or_else(|| {
+                [v.label.as_str(), v.version.as_str()]
+                    .iter()
+                    .find_map(|key| links.get(&key.to_lowercase()).cloned())
+            });
+
+            ChangelogEntry {
+                version: v.version.clone(),
+                date: v.date.clone(),
+                url,
+                yanked: v.yanked,
+                sections: parse_sections(&body),
+                body,
+            }
         })
+        .collect()
+}
+
+/// Entry for a release whose notes live outside the changelog (GitHub releases);
+/// the Markdown notes are split into sections like a changelog body.
+pub fn entry_from_notes(
+    version: &str,
+    date: Option<String>,
+    url: Option<String>,
+    notes: &str,
+) -> ChangelogEntry {
+    let body = notes.replace("\r\n", "\n").trim().to_string();
+    ChangelogEntry {
+        version: version.to_string(),
+        date,
+        url,
+        yanked: false,
+        sections: parse_sections(&body),
+        body,
+    }
+}
+
+/// Changelog Markdown cut down to a window of versions.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TruncatedChangelog {
+    pub content: String,
+    /// Versions whose entries were kept, in document order.
+    pub kept_versions: Vec<String>,
+    /// Number of version entries cut off.
+    pub omitted_versions: usize,
+}
+
+/// Truncate a changelog to the entries `window` selects around the synced
+/// `current_version`; `locked_version` is the `Cargo.lock` version, which
+/// differs from the synced one in latest-docs mode. Everything above the first
+/// cut-off version heading is kept, so unreleased notes survive.
+pub fn truncate_changelog(
+    content: &str,
+    current_version: &str,
+    window: &ChangelogWindow,
+    locked_version: &str,
+) -> TruncatedChangelog {
+    let headings = headings(content);
+    let versions = version_headings(&headings);
+    let level = versions.iter().map(|v| headings[v.index].level).min();
+    let matches: Vec<(usize, &str)> = versions
+        .iter()
+        .filter(|v| Some(headings[v.index].level) == level)
+        .map(|v| (headings[v.index].start, v.version.as_str()))
         .collect();
 
     if matches.is_empty() {
         debug!("No version headings found in CHANGELOG, returning as-is.");
-        return content.to_string();
+        return TruncatedChangelog {
+            content: content.to_string(),
+            kept_versions: Vec::new(),
+            omitted_versions: 0,
+        };
+    }
+
+    let current = matches
+        .iter()
+        .position(|(_, ver)| same_release(ver, current_version));
+    let cut = match window {
+        ChangelogWindow::PreviousMinor => previous_minor_cut(&matches, current, current_version),
+        // Without the current version, the first entries stand in for it.
+        ChangelogWindow::Previous(n) => Some(current.unwrap_or(0) + n + 1),
+        ChangelogWindow::Since(since) => matches
+            .iter()
+            .position(|(_, ver)| version::compare(ver, since).is_lt()),
+        ChangelogWindow::NewerThanLocked => matches
+            .iter()
+            .position(|(_, ver)| version::compare(ver, locked_version).is_lt()),
+    }
+    .filter(|cut| *cut < matches.len());
+
+    let kept = cut.unwrap_or(matches.len());
+    let kept_versions = matches[..kept]
+        .iter()
+        .map(|(_, ver)| ver.to_string())
+        .collect();
+    let content = match cut {
+        Some(cut) => format!(
+            "{}\n---\n\n*[Earlier entries truncated by ai-fdocs]*\n",
+            content[..matches[cut].0].trim_end()
+        ),
+        None => content.to_string(),
+    };
+    TruncatedChangelog {
+        content,
+        kept_versions,
+        omitted_versions: matches.len() - kept,
+    }
+}
+
+/// Index of the first entry below the minor series preceding the current
+/// version; without the current version, the first two entries are kept.
+fn previous_minor_cut(
+    matches: &[(usize, &str)],
+    current: Option<usize>,
+    current_version: &str,
+) -> Option<usize> {
+    let Some(current) = current else {
+        return Some(2);
+    };
+    let current_minor = minor_series(current_version);
+    let mut previous_minor = None;
+    for (i, (_, ver)) in matches.iter().enumerate().skip(current + 1) {
+        if same_release(ver, current_version) {
+            continue;
+        }
+        let ver_minor = minor_series(ver);
+        match previous_minor {
+            None if ver_minor != current_minor || current_minor.is_none() => {
+                previous_minor = Some(ver_minor);
+            }
+            Some(_) => return Some(i),
+            None => {}
+        }
     }
+    None
+}
+
+/// Headings outside fenced code blocks, in document order.
+fn headings(content: &str) -> Vec<Heading> {
+    let atx_re = Regex::new(r"^ {0,3}(#{1,6})\s+(.*?)(?:\s+#+)?\s*$").expect("valid ATX regex");
+    let setext_re = Regex::new(r"^ {0,3}(=+|-{3,})\s*$").expect("valid setext regex");
 
-    let current_minor = parse_minor(current_version);
-    let mut found_current = false;
-    let mut found_previous_minor = false;
-    let mut cut_position: Option<usize> = None;
+    let mut lines = Vec::new();
+    let mut offset = 0;
+    for raw in content.split_inclusive('\n') {
+        lines.push((offset, raw));
+        offset += raw.len();
+    }
 
-    for (pos, ver) in &matches {
-        let ver_minor = parse_minor(ver);
+    let mut out = Vec::new();
+    let mut in_fence = false;
+    let mut prev_blank = true;
+    let mut i = 0;
+    while i < lines.len() {
+        let (start, raw) = lines[i];
+        let line = raw.trim_end();
+        let trimmed = line.trim_start();
+        i += 1;
 
-        if ver == current_version {
-            found_current = true;
+        if trimmed.starts_with("```") || trimmed.starts_with("~~~") {
+            in_fence = !in_fence;
+            prev_blank = false;
+            continue;
+        }
+        if in_fence {
             continue;
         }
 
-        if found_current && !found_previous_minor {
-            if ver_minor != current_minor || current_minor.is_none() {
-                found_previous_minor = true;
+        if let Some(caps) = atx_re.captures(line) {
+            out.push(Heading {
+                start,
+                end: start + raw.len(),
+                level: caps[1].len(),
+                text: caps[2].to_string(),
+            });
+            prev_blank = true;
+            continue;
+        }
+
+        let paragraph_line = !trimmed.is_empty()
+            && !line.starts_with("    ")
+            && !is_list_item(trimmed)
+            && !trimmed.starts_with(['>', '|', '<']);
+        if prev_blank && paragraph_line {
+            if let Some(caps) = lines.get(i).and_then(|(_, next)| setext_re.captures(next)) {
+                out.push(Heading {
+                    start,
+                    end: lines[i].0 + lines[i].1.len(),
+                    level: if caps[1].starts_with('=') { 1 } else { 2 },
+                    text: trimmed.to_string(),
+                });
+                i += 1;
+                prev_blank = true;
                 continue;
             }
+        }
+        prev_blank = trimmed.is_empty();
+    }
+    out
+}
+
+/// Headings that name a version: `1.2.3`, `v1.2.3 (2024-01-01)`, `Version 1.2`,
+/// `[1.2.3] - 2024-01-01`, `[1.2.3](https://...)`, `serde 1.0.0`.
+fn version_headings(headings: &[Heading]) -> Vec<VersionHeading> {
+    let link_re =
+        Regex::new(r"\[([^\]]+)\](?:\(([^)\s]+)[^)]*\)|\[[^\]]*\])?").expect("valid link regex");
+    let version_re = Regex::new(
+        r"^(?:[A-Za-z][\w.-]*[\s-]+){0,2}?v?(\d+\.\d+(?:\.\d+)?(?:-[0-9A-Za-z.-]+)?(?:\+[0-9A-Za-z.-]+)?)(?:$|[\s,:;()\]])",
+    )
+    .expect("valid version heading regex");
+    let date_re = Regex::new(r"\b(\d{4}-\d{2}-\d{2})\b").expect("valid date regex");
+
+    headings
+        .iter()
+        .enumerate()
+        .filter_map(|(index, heading)| {
+            let first_link = link_re.captures(&heading.text);
+            let url = first_link
+                .as_ref()
+                .and_then(|c| c.get(2))
+                .map(|m| m.as_str().to_string());
+            let label = first_link
+                .as_ref()
+                .map(|c| c[1].trim().to_string())
+                .unwrap_or_default();
+            let text = link_re.replace_all(&heading.text, "$1");
+            let text = text.replace(['*', '`'], "");
+            let text = text.trim();
+
+            let caps = version_re.captures(text)?;
+            let version = caps.get(1)?;
+            let rest = &text[version.end()..];
+            Some(VersionHeading {
+                index,
+                version: version.as_str().to_string(),
+                date: date_re.captures(rest).map(|c| c[1].to_string()),
+                url,
+                label,
+                yanked: rest.to_uppercase().contains("YANKED"),
+            })
+        })
+        .collect()
+}
+
+/// `[label]: url` reference definitions, keyed by lowercased label.
+fn link_definitions(content: &str) -> HashMap<String, String> {
+    let def_re =
+        Regex::new(r"(?m)^ {0,3}\[([^\]]+)\]:\s*(\S+)").expect("valid link definition regex");
+    def_re
+        .captures_iter(content)
+        .map(|c| (c[1].trim().to_lowercase(), c[2].to_string()))
+        .collect()
+}
+
+fn strip_link_definitions(body: &str) -> String {
+    let def_re = Regex::new(r"^ {0,3}\[[^\]]+\]:\s*\S+").expect("valid link definition regex");
+    body.lines()
+        .filter(|line| !def_re.is_match(line))
+        .collect::<Vec<_>>()
+        .join("\n")
+        .trim()
+        .to_string()
+}
+
+fn is_list_item(trimmed: &str) -> bool {
+    trimmed.starts_with("- ") || trimmed.starts_with("* ") || trimmed.starts_with("+ ")
+}
+
+/// Group the list items of an entry body by sub-heading. Items flagged
+/// `BREAKING` inside other sections are also listed under a breaking section.
+fn parse_sections(body: &str) -> Vec<ChangelogSection> {
+    let heading_re = Regex::new(r"^ {0,3}#{1,6}\s+(.*?)(?:\s+#+)?\s*$").expect("valid ATX regex");
+    let bold_re = Regex::new(r"^\*\*([^*]+?):?\*\*:?\s*$").expect("valid bold heading regex");
+    let breaking_re = Regex::new(r"(?i)^(?:\*\*|\[|`)?breaking\b").expect("valid breaking regex");
+
+    let mut sections: Vec<ChangelogSection> = Vec::new();
+    let mut in_fence = false;
+    let mut in_item = false;
+
+    for line in body.lines() {
+        let trimmed = line.trim();
+        if trimmed.starts_with("```") || trimmed.starts_with("~~~") {
+            in_fence = !in_fence;
+            in_item = false;
+            continue;
+        }
+        if in_fence {
+            continue;
+        }
+
+        let title = heading_re
+            .captures(line)
+            .or_else(|| bold_re.captures(trimmed))
+            .map(|c| c[1].trim().to_string());
+        if let Some(title) = title {
+            sections.push(ChangelogSection {
+                kind: SectionKind::from_title(&title),
+                title,
+                items: Vec::new(),
+            });
+            in_item = false;
             continue;
         }
 
-        if found_previous_minor {
-            cut_position = Some(*pos);
-            break;
+        if trimmed.is_empty() {
+            in_item = false;
+            continue;
         }
-    }
 
-    if !found_current && matches.len() > 2 {
-        cut_position = Some(matches[2].0);
+        let indent = line.len() - line.trim_start().len();
+        if indent < 2 && is_list_item(trimmed) {
+            if sections.is_empty() {
+                sections.push(ChangelogSection {
+                    kind: SectionKind::Other,
+                    title: String::new(),
+                    items: Vec::new(),
+                });
+            }
+            let section = sections.last_mut().expect("section exists");
+            section.items.push(trimmed[2..].trim().to_string());
+            in_item = true;
+        } else if in_item {
+            let item = sections
+                .last_mut()
+                .and_then(|s| s.items.last_mut())
+                .expect("item exists");
+            item.push(' ');
+            item.push_str(trimmed);
+        }
     }
 
-    match cut_position {
-        Some(pos) => {
-            let truncated = &content[..pos];
-            format!(
-                "{}\n---\n\n*[Earlier entries truncated by ai-fdocs]*\n",
-                truncated.trim_end()
-            )
+    let flagged: Vec<String> = sections
+        .iter()
+        .filter(|s| s.kind != SectionKind::Breaking)
+        .flat_map(|s| &s.items)
+        .filter(|item| breaking_re.is_match(item))
+        .cloned()
+        .collect();
+    if !flagged.is_empty() {
+        match sections
+            .iter_mut()
+            .find(|s| s.kind == SectionKind::Breaking)
+        {
+            Some(section) => section.items.extend(flagged),
+            None => sections.push(ChangelogSection {
+                kind: SectionKind::Breaking,
+                title: "Breaking".to_string(),
+                items: flagged,
+            }),
         }
-        None => content.to_string(),
     }
-}
 
-fn parse_minor(version: &str) -> Option<(u64, u64)> {
-    let mut parts = version.split('.');
-    let major = parts.next()?.parse::<u64>().ok()?;
-    let minor = parts.next()?.parse::<u64>().ok()?;
-    Some((major, minor))
+    sections.retain(|s| !s.items.is_empty());
+    sections
 }
 
 #[cfg(test)]
@@ -91,18 +501,191 @@ mod tests {
 ## 0.11.0 - 2023-11-01
 - Ancient feature
 "#;
-        let result = truncate_changelog(changelog, "0.13.1");
-        assert!(result.contains("0.13.1"));
-        assert!(result.contains("0.13.0"));
-        assert!(result.contains("0.12.0"));
-        assert!(!result.contains("0.11.0"));
-        assert!(result.contains("[Earlier entries truncated by ai-fdocs]"));
+        let result = truncate_changelog(changelog, "0.13.1", &ChangelogWindow::default(), "0.13.1");
+        assert!(result.content.contains("0.13.1"));
+        assert!(result.content.contains("0.13.0"));
+        assert!(result.content.contains("0.12.0"));
+        assert!(!result.content.contains("0.11.0"));
+        assert!(result
+            .content
+            .contains("[Earlier entries truncated by ai-fdocs]"));
+        assert_eq!(result.kept_versions, vec!["0.13.1", "0.13.0", "0.12.0"]);
+        assert_eq!(result.omitted_versions, 1);
+    }
+
+    #[test]
+    fn test_truncate_with_configured_windows() {
+        let changelog = r#"# Changelog
+
+## Unreleased
+- Upcoming
+
+## 2.1.0
+- Latest
+
+## 2.0.0
+- Major
+
+## 1.5.0
+- Locked
+
+## 1.4.0
+- Older
+
+## 1.3.0
+- Oldest
+"#;
+        let kept = |window: ChangelogWindow, current: &str, locked: &str| {
+            truncate_changelog(changelog, current, &window, locked).kept_versions
+        };
+
+        assert_eq!(
+            kept(ChangelogWindow::Previous(1), "2.0.0", "2.0.0"),
+            vec!["2.1.0", "2.0.0", "1.5.0"]
+        );
+        assert_eq!(
+            kept(ChangelogWindow::Previous(0), "9.9.9", "9.9.9"),
+            vec!["2.1.0"]
+        );
+        assert_eq!(
+            kept(ChangelogWindow::Since("1.4".to_string()), "2.1.0", "2.1.0"),
+            vec!["2.1.0", "2.0.0", "1.5.0", "1.4.0"]
+        );
+
+        let newer = truncate_changelog(
+            changelog,
+            "2.1.0",
+            &ChangelogWindow::NewerThanLocked,
+            "1.5.0",
+        );
+        assert_eq!(newer.kept_versions, vec!["2.1.0", "2.0.0", "1.5.0"]);
+        assert_eq!(newer.omitted_versions, 2);
+        assert!(newer.content.contains("- Upcoming"));
+        assert!(!newer.content.contains("1.4.0"));
+
+        let all = truncate_changelog(changelog, "2.1.0", &ChangelogWindow::Previous(10), "2.1.0");
+        assert_eq!(all.omitted_versions, 0);
+        assert_eq!(all.content, changelog);
+    }
+
+    #[test]
+    fn test_truncate_matches_prerelease_and_build_metadata_headings() {
+        let changelog = r#"# Changelog
+
+## [v1.0.0+build.7] - 2024-03-01
+- Release
+
+## 1.0.0-rc.10 - 2024-02-01
+- Release candidate
+
+## 0.9.0 - 2024-01-01
+- Previous minor
+
+## 0.8.0 - 2023-12-01
+- Ancient
+"#;
+        let result = truncate_changelog(changelog, "1.0.0", &ChangelogWindow::default(), "1.0.0");
+        assert!(result.content.contains("1.0.0-rc.10"));
+        assert!(result.content.contains("0.9.0"));
+        assert!(!result.content.contains("0.8.0"));
+    }
+
+    #[test]
+    fn test_parses_keep_a_changelog_entries() {
+        let changelog = r#"# Changelog
+
+## [Unreleased]
+- Not yet
+
+## [1.2.0] - 2024-03-01
+### Added
+- `Client::retry` builder
+  with a second line
+### Changed
+- **Breaking:** `connect` is now async
+### Fixed
+- Panic on empty input
+
+## [1.1.0] - 2024-01-15 [YANKED]
+### Removed
+- Old API
+
+[Unreleased]: https://example.com/compare/v1.2.0...HEAD
+[1.2.0]: https://example.com/compare/v1.1.0...v1.2.0
+"#;
+        let entries = parse_changelog(changelog);
+        assert_eq!(entries.len(), 2);
+
+        let latest = &entries[0];
+        assert_eq!(latest.version, "1.2.0");
+        assert_eq!(latest.date.as_deref(), Some("2024-03-01"));
+        assert_eq!(
+            latest.url.as_deref(),
+            Some("https://example.com/compare/v1.1.0...v1.2.0")
+        );
+        let kinds: Vec<SectionKind> = latest.sections.iter().map(|s| s.kind).collect();
+        assert_eq!(
+            kinds,
+            vec![
+                SectionKind::Added,
+                SectionKind::Changed,
+                SectionKind::Fixed,
+                SectionKind::Breaking
+            ]
+        );
+        assert_eq!(
+            latest.sections[0].items,
+            vec!["`Client::retry` builder with a second line"]
+        );
+        assert_eq!(
+            latest.sections[3].items,
+            vec!["**Breaking:** `connect` is now async"]
+        );
+
+        assert!(entries[1].yanked);
+        assert_eq!(entries[1].sections[0].kind, SectionKind::Removed);
+        assert!(!entries[1].body.contains("https://example.com"));
+    }
+
+    #[test]
+    fn test_parses_version_prefixed_and_setext_headings() {
+        let changelog = r#"Changelog
+=========
+
+v1.2.3 (2024-01-01)
+-------------------
+
+* Fix a leak
+
+```text
+## 9.9.9 inside a code block
+```
+
+Version 1.2
+-----------
+
+**Breaking changes**
+- Renamed `foo` to `bar`
+"#;
+        let entries = parse_changelog(changelog);
+        let versions: Vec<&str> = entries.iter().map(|e| e.version.as_str()).collect();
+        assert_eq!(versions, vec!["1.2.3", "1.2"]);
+        assert_eq!(entries[0].date.as_deref(), Some("2024-01-01"));
+        assert_eq!(entries[0].sections[0].kind, SectionKind::Other);
+        assert_eq!(entries[0].sections[0].items, vec!["Fix a leak"]);
+        assert_eq!(entries[1].sections[0].kind, SectionKind::Breaking);
+        assert_eq!(entries[1].sections[0].title, "Breaking changes");
+
+        let headed = parse_changelog("# tokio 1.38.0 (May 30th, 2024)\n\n- Stuff\n");
+        assert_eq!(headed[0].version, "1.38.0");
+        assert_eq!(headed[0].date, None);
     }
 
     #[test]
     fn test_no_version_headings_returns_as_is() {
         let content = "Just some text without versions.";
-        let result = truncate_changelog(content, "1.0.0");
-        assert_eq!(result, content);
+        let result = truncate_changelog(content, "1.0.0", &ChangelogWindow::default(), "1.0.0");
+        assert_eq!(result.content, content);
+        assert!(result.kept_versions.is_empty());
     }
 }
@@ -0,0 +1,226 @@
+use serde::Serialize;
+
+use crate::processor::truncate::{closing_fence, cut_position, layout, open_fence, Layout};
+use crate::tokens::TokenEstimator;
+
+pub const CHUNKS_MANIFEST_FILE: &str = "_CHUNKS.json";
+
+/// Room left in each chunk for the fence lines added where a code block is split.
+const FENCE_RESERVE_BYTES: usize = 64;
+
+/// One `*.part-NN.md` piece of a document split at heading boundaries.
+#[derive(Debug, Clone, Serialize)]
+pub struct Chunk {
+    pub file: String,
+    /// Headings the chunk starts under, outermost first.
+    pub heading_path: Vec<String>,
+    pub bytes: usize,
+    pub estimated_tokens: usize,
+    #[serde(skip)]
+    pub content: String,
+}
+
+/// A document and the chunks it was split into.
+#[derive(Debug, Clone, Serialize)]
+pub struct ChunkedFile {
+    pub source: String,
+    pub chunks: Vec<Chunk>,
+}
+
+/// `_CHUNKS.json` of a crate directory.
+#[derive(Debug, Serialize)]
+pub struct ChunkManifest<'a> {
+    #[serde(rename = "crate")]
+    pub crate_name: &'a str,
+    pub version: &'a str,
+    pub files: &'a [ChunkedFile],
+}
+
+/// Whether `content` exceeds the byte budget or the optional token budget.
+pub fn exceeds_budget(
+    content: &str,
+    max_bytes: usize,
+    max_tokens: Option<usize>,
+    tokens: &dyn TokenEstimator,
+) -> bool {
+    content.len() > max_bytes || max_tokens.is_some_and(|max| tokens.estimate(content) > max)
+}
+
+/// `API.md` → `API.part-01.md`.
+pub fn chunk_file_name(path: &str, index: usize) -> String {
+    match path.rsplit_once('.') {
+        Some((stem, ext)) => format!("{stem}.part-{index:02}.{ext}"),
+        None => format!("{path}.part-{index:02}"),
+    }
+}
+
+/// Split Markdown into chunks of at most `max_bytes` (and `max_tokens`, when set),
+/// preferring heading boundaries, then paragraphs, then lines. A code block split
+/// across chunks is closed at the end of one and reopened at the start of the next.
+pub fn split_markdown(
+    path: &str,
+    content: &str,
+    max_bytes: usize,
+    max_tokens: Option<usize>,
+    tokens: &dyn TokenEstimator,
+) -> ChunkedFile {
+    let layout = layout(content);
+    let budget = max_bytes.saturating_sub(FENCE_RESERVE_BYTES).max(1);
+    // Fence lines cost a few tokens too.
+    let max_tokens = max_tokens.map(|max| max.saturating_sub(FENCE_RESERVE_BYTES / 4).max(1));
+
+    let mut chunks = Vec::new();
+    let mut from = 0;
+    while from < content.len() {
+        let mut piece_budget = budget;
+        let mut cut = cut_position(content, &layout, from, piece_budget);
+        // Shrink the piece in proportion until it fits the token budget.
+        while let Some(max) = max_tokens {
+            let estimated = tokens.estimate(&content[from..cut]);
+            if estimated <= max || piece_budget <= 1 {
+                break;
+            }
+            piece_budget = (piece_budget * max / estimated)
+                .min(piece_budget - 1)
+                .max(1);
+            cut = cut_position(content, &layout, from, piece_budget);
+        }
+        let mut text = String::new();
+        if let Some(opening) = open_fence(&layout, from) {
+            text.push_str(&opening);
+            text.push('\n');
+        }
+        text.push_str(&content[from..cut]);
+        if cut < content.len() {
+            if let Some(opening) = open_fence(&layout, cut) {
+                if !text.ends_with('\n') {
+                    text.push('\n');
+                }
+                text.push_str(&closing_fence(&opening));
+                text.push('\n');
+            }
+        }
+        if !text.trim().is_empty() {
+            chunks.push(Chunk {
+                file: chunk_file_name(path, chunks.len() + 1),
+                heading_path: heading_path(&layout, from),
+                bytes: text.len(),
+                estimated_tokens: tokens.estimate(&text),
+                content: text,
+            });
+        }
+        from = cut;
+    }
+
+    ChunkedFile {
+        source: path.to_string(),
+        chunks,
+    }
+}
+
+/// Headings in effect at `pos`, including one starting exactly there.
+fn heading_path(layout: &Layout, pos: usize) -> Vec<String> {
+    let mut path: Vec<(usize, &str)> = Vec::new();
+    for heading in layout.headings.iter().take_while(|h| h.start <= pos) {
+        while path
+            .last()
+            .is_some_and(|(level, _)| *level >= heading.level)
+        {
+            path.pop();
+        }
+        path.push((heading.level, heading.text.as_str()));
+    }
+    path.into_iter().map(|(_, text)| text.to_string()).collect()
+}
+
+/// Written under the original file name so existing links lead to the parts.
+pub fn render_chunk_index(file: &ChunkedFile) -> String {
+    let mut out = format!(
+        "# {} (split into {} parts)\n\nThis document was larger than the configured limit and was split at heading boundaries. Read the parts in order:\n\n",
+        file.source,
+        file.chunks.len()
+    );
+    for chunk in &file.chunks {
+        let path = if chunk.heading_path.is_empty() {
+            "start".to_string()
+        } else {
+            chunk.heading_path.join(" › ")
+        };
+        out.push_str(&format!(
+            "- [{file}]({file}) — {path} ({:.1} KB, ~{} tokens)\n",
+            chunk.bytes as f64 / 1024.0,
+            chunk.estimated_tokens,
+            file = chunk.file,
+        ));
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{chunk_file_name, exceeds_budget, render_chunk_index, split_markdown};
+    use crate::tokens::Heuristic;
+
+    fn api_doc() -> String {
+        let mut doc = String::from("# demo\n\nCrate docs.\n\n## Structs\n\n");
+        for i in 0..20 {
+            doc.push_str(&format!("### Item{i}\n\nDescription of item {i}.\n\n"));
+        }
+        doc.push_str("## Functions\n\n```rust\n");
+        for i in 0..80 {
+            doc.push_str(&format!("pub fn function_{i}(input: &str) -> usize;\n"));
+        }
+        doc.push_str("```\n\n## Traits\n\nNone.\n");
+        doc
+    }
+
+    #[test]
+    fn splits_at_headings_without_losing_content() {
+        let doc = api_doc();
+        let split = split_markdown("API.md", &doc, 1024, None, &Heuristic);
+
+        assert!(split.chunks.len() > 3);
+        assert_eq!(split.chunks[0].file, "API.part-01.md");
+        assert_eq!(split.chunks[1].heading_path, ["demo", "Functions"]);
+        for chunk in &split.chunks {
+            assert!(
+                chunk.bytes <= 1024,
+                "{} is {} bytes",
+                chunk.file,
+                chunk.bytes
+            );
+            assert_eq!(chunk.content.matches("```").count() % 2, 0);
+        }
+
+        let joined: String = split.chunks.iter().map(|c| c.content.as_str()).collect();
+        for i in 0..80 {
+            assert!(joined.contains(&format!("pub fn function_{i}(")));
+        }
+        assert!(joined.contains("## Traits\n\nNone."));
+        let fn_chunk = split
+            .chunks
+            .iter()
+            .find(|c| c.content.starts_with("```rust\n"))
+            .expect("reopened code block");
+        assert_eq!(fn_chunk.heading_path, ["demo", "Functions"]);
+
+        let index = render_chunk_index(&split);
+        assert!(index.starts_with(&format!(
+            "# API.md (split into {} parts)\n",
+            split.chunks.len()
+        )));
+        assert!(index.contains("- [API.part-01.md](API.part-01.md) — demo ("));
+    }
+
+    #[test]
+    fn honours_token_budget_and_names_parts() {
+        let doc = api_doc();
+        assert!(exceeds_budget(&doc, 1 << 20, Some(100), &Heuristic));
+        assert!(!exceeds_budget(&doc, 1 << 20, None, &Heuristic));
+
+        let split = split_markdown("docs__guide.md", &doc, 1 << 20, Some(300), &Heuristic);
+        assert!(split.chunks.iter().all(|c| c.estimated_tokens <= 300));
+        assert_eq!(chunk_file_name("README", 3), "README.part-03");
+        assert_eq!(split.chunks[0].file, "docs__guide.part-01.md");
+    }
+}
@@ -0,0 +1,501 @@
+use std::collections::HashSet;
+
+use regex::{Captures, Regex};
+
+use super::truncate::{atx_heading, closing_fence, fence_marker};
+use crate::config::MarkdownCleanup;
+
+/// What one cleanup rule removed from a file.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Removal {
+    pub rule: &'static str,
+    /// Badges, lines, comments, sections or definitions removed.
+    pub items: usize,
+    pub bytes_before: usize,
+    pub bytes_after: usize,
+}
+
+type Pass = fn(&Patterns, &str) -> (String, usize);
+
+/// Strip README noise from Markdown outside code blocks, one enabled rule after
+/// the other: HTML comments, sponsor sections, badges, image-only lines, then
+/// unused link references. Files without noise come back unchanged.
+pub fn clean_markdown(content: &str, cleanup: &MarkdownCleanup) -> (String, Vec<Removal>) {
+    let mut text = content.to_string();
+    let mut removals = Vec::new();
+    if !cleanup.enabled {
+        return (text, removals);
+    }
+
+    let patterns = Patterns::new();
+    let passes: [(bool, &'static str, Pass); 5] = [
+        (cleanup.html_comments, "html_comments", strip_html_comments),
+        (
+            cleanup.sponsor_sections,
+            "sponsor_sections",
+            strip_sponsor_sections,
+        ),
+        (cleanup.badges, "badges", strip_badges),
+        (cleanup.images, "images", strip_image_lines),
+        (
+            cleanup.link_references,
+            "link_references",
+            strip_link_references,
+        ),
+    ];
+    for (enabled, rule, pass) in passes {
+        if !enabled {
+            continue;
+        }
+        let (cleaned, items) = pass(&patterns, &text);
+        if items == 0 {
+            continue;
+        }
+        let cleaned = tidy_blank_lines(&cleaned);
+        removals.push(Removal {
+            rule,
+            items,
+            bytes_before: text.len(),
+            bytes_after: cleaned.len(),
+        });
+        text = cleaned;
+    }
+    (text, removals)
+}
+
+struct Patterns {
+    /// Linked and bare images, Markdown or HTML; `url` or `ref` names the image.
+    images: Vec<Regex>,
+    badge_url: Regex,
+    /// HTML tags that only wrap or lay out images.
+    wrapper_tag: Regex,
+    reference_def: Regex,
+    sponsor_heading: Regex,
+    bracketed: Regex,
+    empty_link: Regex,
+}
+
+impl Patterns {
+    fn new() -> Self {
+        const IMAGE: &str =
+            r#"!\[(?P<alt>[^\]]*)\](?:\(\s*<?(?P<url>[^)\s>]+)[^)]*\)|\[(?P<ref>[^\]]*)\])"#;
+        let linked = format!(r"\[\s*{IMAGE}\s*\](?:\([^)]*\)|\[[^\]]*\])");
+        let images = [
+            linked.as_str(),
+            r#"(?i)<a\b[^>]*>\s*<img\b[^>]*\bsrc\s*=\s*["'](?P<url>[^"']+)["'][^>]*>\s*</a>"#,
+            IMAGE,
+            r#"(?i)<img\b[^>]*\bsrc\s*=\s*["'](?P<url>[^"']+)["'][^>]*>"#,
+        ]
+        .iter()
+        .map(|re| Regex::new(re).expect("valid image regex"))
+        .collect();
+
+        Self {
+            images,
+            badge_url: Regex::new(
+                r"(?i)shields\.io|badgen\.net|badge\.fury\.io|/badges?/|badge\.svg|/badge\b|travis-ci\.(?:org|com)|codecov\.io|coveralls\.io|deps\.rs|circleci\.com|ci\.appveyor\.com|/workflows/[^\s)]*\.svg",
+            )
+            .expect("valid badge url regex"),
+            wrapper_tag: Regex::new(r"(?i)</?(?:p|div|a|br|picture|source|span|center)\b[^>]*>")
+                .expect("valid wrapper tag regex"),
+            reference_def: Regex::new(r"^ {0,3}\[(?P<label>[^\]]+)\]:\s*<?(?P<url>[^\s>]+)")
+                .expect("valid reference definition regex"),
+            sponsor_heading: Regex::new(
+                r"(?i)\b(?:sponsors?|sponsorship|backers|supporters|contributors|donations?|donate|funding|patrons)\b",
+            )
+            .expect("valid sponsor heading regex"),
+            bracketed: Regex::new(r"\[([^\[\]]*)\]").expect("valid bracket regex"),
+            empty_link: Regex::new(r"(^|[^!\]])\[\s*\]\([^)]*\)").expect("valid empty link regex"),
+        }
+    }
+
+    /// Label → URL of every link reference definition outside code blocks.
+    fn references(&self, text: &str) -> Vec<(String, String)> {
+        mark_code(text)
+            .into_iter()
+            .filter(|(_, code)| !code)
+            .filter_map(|(line, _)| {
+                let caps = self.reference_def.captures(line)?;
+                Some((caps["label"].to_lowercase(), caps["url"].to_string()))
+            })
+            .collect()
+    }
+
+    /// Whether what is left of a line after removals carries no content.
+    fn is_residue(&self, line: &str) -> bool {
+        self.wrapper_tag
+            .replace_all(line, "")
+            .chars()
+            .all(|c| c.is_whitespace() || "|·•-–—,".contains(c))
+    }
+}
+
+/// Lines with whether each belongs to a fenced code block (fences included).
+pub(super) fn mark_code(text: &str) -> Vec<(&str, bool)> {
+    let mut fence: Option<String> = None;
+    text.split_inclusive('\n')
+        .map(|line| {
+            let trimmed = line.trim();
+            if let Some(open) = &fence {
+                let marker = closing_fence(open);
+                if trimmed.starts_with(marker.as_str())
+                    && trimmed.chars().all(|c| marker.starts_with(c))
+                {
+                    fence = None;
+                }
+                return (line, true);
+            }
+            if fence_marker(trimmed).is_some() {
+                fence = Some(trimmed.to_string());
+                return (line, true);
+            }
+            (line, false)
+        })
+        .collect()
+}
+
+/// Append `kept` for `line`, dropping lines that lost all their content.
+fn push_line(out: &mut String, line: &str, kept: &str, residue: bool) {
+    if residue && !line.trim().is_empty() {
+        return;
+    }
+    out.push_str(kept);
+    if line.ends_with('\n') && !kept.ends_with('\n') {
+        out.push('\n');
+    }
+}
+
+fn strip_html_comments(_: &Patterns, text: &str) -> (String, usize) {
+    let mut out = String::with_capacity(text.len());
+    let mut removed = 0;
+    let mut in_comment = false;
+    for (line, code) in mark_code(text) {
+        if code && !in_comment {
+            out.push_str(line);
+            continue;
+        }
+        let mut kept = String::new();
+        let mut rest = line;
+        let mut touched = in_comment;
+        loop {
+            if in_comment {
+                let Some(end) = rest.find("-->") else {
+                    break;
+                };
+                rest = &rest[end + 3..];
+                in_comment = false;
+            } else if let Some(start) = rest.find("<!--") {
+                kept.push_str(&rest[..start]);
+                rest = &rest[start + 4..];
+                in_comment = true;
+                touched = true;
+                removed += 1;
+            } else {
+                kept.push_str(rest);
+                break;
+            }
+        }
+        push_line(&mut out, line, &kept, touched && kept.trim().is_empty());
+    }
+    (out, removed)
+}
+
+fn strip_sponsor_sections(patterns: &Patterns, text: &str) -> (String, usize) {
+    let mut out = String::with_capacity(text.len());
+    let mut removed = 0;
+    let mut skipping: Option<usize> = None;
+    for (line, code) in mark_code(text) {
+        if let Some(heading) = (!code).then(|| atx_heading(line, 0)).flatten() {
+            if skipping.is_some_and(|level| heading.level <= level) {
+                skipping = None;
+            }
+            if skipping.is_none() && patterns.sponsor_heading.is_match(&heading.text) {
+                skipping = Some(heading.level);
+                removed += 1;
+            }
+        }
+        if skipping.is_none() {
+            out.push_str(line);
+        }
+    }
+    (out, removed)
+}
+
+fn strip_badges(patterns: &Patterns, text: &str) -> (String, usize) {
+    let references = patterns.references(text);
+    let url_of = |caps: &Captures<'_>| -> Option<String> {
+        if let Some(url) = caps.name("url") {
+            return Some(url.as_str().to_string());
+        }
+        let label = match caps.name("ref").map(|r| r.as_str().trim()) {
+            Some("") | None => caps.name("alt")?.as_str(),
+            Some(label) => label,
+        }
+        .to_lowercase();
+        references
+            .iter()
+            .find(|(l, _)| *l == label)
+            .map(|(_, url)| url.clone())
+    };
+
+    let mut out = String::with_capacity(text.len());
+    let mut removed = 0;
+    for (line, code) in mark_code(text) {
+        if code || patterns.reference_def.is_match(line) {
+            out.push_str(line);
+            continue;
+        }
+        let mut kept = line.to_string();
+        let mut badges = 0;
+        for re in &patterns.images {
+            kept = re
+                .replace_all(&kept, |caps: &Captures<'_>| {
+                    if url_of(caps).is_some_and(|url| patterns.badge_url.is_match(&url)) {
+                        badges += 1;
+                        String::new()
+                    } else {
+                        caps[0].to_string()
+                    }
+                })
+                .into_owned();
+        }
+        removed += badges;
+        push_line(
+            &mut out,
+            line,
+            &kept,
+            badges > 0 && patterns.is_residue(&kept),
+        );
+    }
+    (out, removed)
+}
+
+fn strip_image_lines(patterns: &Patterns, text: &str) -> (String, usize) {
+    let mut out = String::with_capacity(text.len());
+    let mut removed = 0;
+    for (line, code) in mark_code(text) {
+        if code || line.trim().is_empty() || patterns.reference_def.is_match(line) {
+            out.push_str(line);
+            continue;
+        }
+        let mut rest = line.to_string();
+        let mut images = 0;
+        for re in &patterns.images {
+            images += re.find_iter(&rest).count();
+            rest = re.replace_all(&rest, "").into_owned();
+        }
+        if (images > 0 || patterns.wrapper_tag.is_match(line)) && patterns.is_residue(&rest) {
+            removed += 1;
+            continue;
+        }
+        out.push_str(line);
+    }
+    (out, removed)
+}
+
+fn strip_link_references(patterns: &Patterns, text: &str) -> (String, usize) {
+    let lines = mark_code(text);
+    let used: HashSet<String> = lines
+        .iter()
+        .filter(|(line, code)| !code && !patterns.reference_def.is_match(line))
+        .flat_map(|(line, _)| patterns.bracketed.captures_iter(line))
+        .map(|caps| caps[1].trim().to_lowercase())
+        .collect();
+
+    let mut out = String::with_capacity(text.len());
+    let mut removed = 0;
+    for (line, code) in lines {
+        if code {
+            out.push_str(line);
+            continue;
+        }
+        if let Some(caps) = patterns.reference_def.captures(line) {
+            if !used.contains(&caps["label"].trim().to_lowercase()) {
+                removed += 1;
+                continue;
+            }
+            out.push_str(line);
+            continue;
+        }
+        let empty_links = patterns.empty_link.find_iter(line).count();
+        if empty_links == 0 {
+            out.push_str(line);
+            continue;
+        }
+        removed += empty_links;
+        let kept = patterns.empty_link.replace_all(line, "$1");
+        push_line(&mut out, line, &kept, patterns.is_residue(&kept));
+    }
+    (out, removed)
+}
+
+/// Collapse runs of blank lines left by removals (outside code blocks) and
+/// drop leading ones.
+fn tidy_blank_lines(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    let mut prev_blank = true;
+    for (line, code) in mark_code(text) {
+        let blank = !code && line.trim().is_empty();
+        if blank && prev_blank {
+            continue;
+        }
+        out.push_str(line);
+        prev_blank = blank;
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::clean_markdown;
+    use crate::config::MarkdownCleanup;
+
+    const README: &str = r#"<!-- cargo-rdme start -->
+<p align="center">
+  <img src="https://example.com/logo.png" width="200">
+</p>
+
+# demo
+
+[![Crates.io][crates-badge]][crates-url]
+[![Build](https://github.com/o/demo/actions/workflows/ci.yml/badge.svg)](https://github.com/o/demo/actions) [![docs](https://docs.rs/demo/badge.svg)](https://docs.rs/demo)
+
+[crates-badge]: https://img.shields.io/crates/v/demo.svg
+[crates-url]: https://crates.io/crates/demo
+[guide]: https://example.com/guide
+
+A fast demo crate. See the [docs][docs-url] and [](https://example.com).
+
+![screenshot](docs/screenshot.png)
+
+```rust
+// ![not an image](x.png) <!-- kept -->
+let x = 1;
+```
+
+## Sponsors
+
+<a href="https://opencollective.com/demo"><img src="https://opencollective.com/demo/sponsors.svg"></a>
+
+### Gold
+
+Thanks!
+
+## License
+
+MIT
+
+[docs-url]: https://docs.rs/demo
+"#;
+
+    const ALL: MarkdownCleanup = MarkdownCleanup {
+        enabled: true,
+        badges: true,
+        images: true,
+        html_comments: true,
+        sponsor_sections: true,
+        link_references: true,
+    };
+
+    /// Only `rule` enabled.
+    fn only(rule: &str) -> MarkdownCleanup {
+        MarkdownCleanup {
+            enabled: true,
+            badges: rule == "badges",
+            images: rule == "images",
+            html_comments: rule == "html_comments",
+            sponsor_sections: rule == "sponsor_sections",
+            link_references: rule == "link_references",
+        }
+    }
+
+    #[test]
+    fn strips_badges_images_comments_and_sponsors() {
+        let (cleaned, removals) = clean_markdown(README, &ALL);
+        assert_eq!(
+            cleaned,
+            "# demo\n\nA fast demo crate. See the [docs][docs-url] and .\n\n```rust\n// ![not an image](x.png) <!-- kept -->\nlet x = 1;\n```\n\n## License\n\nMIT\n\n[docs-url]: https://docs.rs/demo\n"
+        );
+        let rules: Vec<(&str, usize)> = removals.iter().map(|r| (r.rule, r.items)).collect();
+        assert_eq!(
+            rules,
+            [
+                ("html_comments", 1),
+                ("sponsor_sections", 1),
+                ("badges", 3),
+                ("images", 4),
+                ("link_references", 4),
+            ]
+        );
+        assert_eq!(removals[0].bytes_before, README.len());
+        assert_eq!(removals[4].bytes_after, cleaned.len());
+
+        let off = MarkdownCleanup {
+            enabled: false,
+            ..ALL
+        };
+        assert_eq!(
+            clean_markdown(README, &off),
+            (README.to_string(), Vec::new())
+        );
+    }
+
+    #[test]
+    fn default_keeps_images_and_sponsor_sections() {
+        let (cleaned, removals) = clean_markdown(README, &MarkdownCleanup::default());
+        let rules: Vec<&str> = removals.iter().map(|r| r.rule).collect();
+        assert_eq!(rules, ["html_comments", "badges", "link_references"]);
+        assert!(cleaned.contains("![screenshot](docs/screenshot.png)"));
+        assert!(cleaned.contains("## Sponsors\n"));
+    }
+
+    #[test]
+    fn html_comments_rule_removes_single_and_multiline_comments() {
+        let text = "a <!-- one --> b\n<!--\ntwo\n-->\nc\n```\n<!-- code -->\n```\n";
+        let (cleaned, removals) = clean_markdown(text, &only("html_comments"));
+        assert_eq!(cleaned, "a  b\nc\n```\n<!-- code -->\n```\n");
+        assert_eq!(removals.len(), 1);
+        assert_eq!(removals[0].items, 2);
+        assert_eq!(removals[0].bytes_before, text.len());
+        assert_eq!(removals[0].bytes_after, cleaned.len());
+    }
+
+    #[test]
+    fn sponsor_sections_rule_drops_nested_headings_up_to_the_next_sibling() {
+        let text = "# demo\n\n## Backers\n\nThanks\n\n### Gold\n\nAcme\n\n## Usage\n\nCall it.\n";
+        let (cleaned, removals) = clean_markdown(text, &only("sponsor_sections"));
+        assert_eq!(cleaned, "# demo\n\n## Usage\n\nCall it.\n");
+        assert_eq!(removals[0].rule, "sponsor_sections");
+        assert_eq!(removals[0].items, 1);
+    }
+
+    #[test]
+    fn badges_rule_keeps_other_images() {
+        let text = "[![CI](https://github.com/o/r/actions/workflows/ci.yml/badge.svg)](https://github.com/o/r/actions)\n![diagram](docs/arch.png)\n";
+        let (cleaned, removals) = clean_markdown(text, &only("badges"));
+        assert_eq!(cleaned, "![diagram](docs/arch.png)\n");
+        assert_eq!(removals[0].rule, "badges");
+        assert_eq!(removals[0].items, 1);
+    }
+
+    #[test]
+    fn images_rule_only_drops_image_only_lines() {
+        let text = "![logo](logo.png)\n\nSee ![icon](i.png) here.\n";
+        let (cleaned, removals) = clean_markdown(text, &only("images"));
+        assert_eq!(cleaned, "See ![icon](i.png) here.\n");
+        assert_eq!(removals[0].rule, "images");
+        assert_eq!(removals[0].items, 1);
+    }
+
+    #[test]
+    fn link_references_rule_keeps_used_definitions() {
+        let text = "Read the [guide][g] or [](https://example.com).\n\n[g]: https://example.com/guide\n[unused]: https://example.com/x\n";
+        let (cleaned, removals) = clean_markdown(text, &only("link_references"));
+        assert_eq!(
+            cleaned,
+            "Read the [guide][g] or .\n\n[g]: https://example.com/guide\n"
+        );
+        assert_eq!(removals[0].rule, "link_references");
+        assert_eq!(removals[0].items, 2);
+    }
+}
@@ -0,0 +1,188 @@
+use std::collections::HashSet;
+
+/// Examples emitted per `API.md`; more rarely adds signal for the model.
+const MAX_EXAMPLES: usize = 4;
+
+/// A code snippet and where it came from (crate docs, an item's docs, or an
+/// `examples/*.rs` file), already formatted for the `From ...:` label.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Example {
+    pub origin: String,
+    pub code: String,
+}
+
+/// Share of the file budget that the examples section may use.
+pub fn example_budget_bytes(max_file_size_kb: usize) -> usize {
+    max_file_size_kb * 1024 / 10
+}
+
+/// Rust code blocks from a doc comment or rendered Markdown page.
+///
+/// Untagged fences count as Rust (rustdoc's default); `compile_fail` and
+/// non-Rust languages are skipped. Hidden doctest lines (`# ...`) are dropped.
+pub fn doc_code_blocks(origin: &str, markdown: &str) -> Vec<Example> {
+    let mut blocks = Vec::new();
+    let mut current: Option<(String, Vec<&str>)> = None;
+
+    for line in markdown.lines() {
+        let trimmed = line.trim_start();
+        match current.as_mut() {
+            None => {
+                if let Some(info) = fence_info(trimmed) {
+                    current = Some((info.to_string(), Vec::new()));
+                }
+            }
+            Some((info, lines)) => {
+                if fence_info(trimmed).is_some_and(str::is_empty) {
+                    if is_rust_fence(info) {
+                        let code = strip_hidden_lines(lines);
+                        if !code.trim().is_empty() {
+                            blocks.push(Example {
+                                origin: origin.to_string(),
+                                code,
+                            });
+                        }
+                    }
+                    current = None;
+                } else {
+                    lines.push(line);
+                }
+            }
+        }
+    }
+
+    blocks
+}
+
+/// An `examples/*.rs` file from the repository.
+pub fn example_file(path: &str, content: &str) -> Example {
+    Example {
+        origin: format!("`{path}`"),
+        code: content.trim_end().to_string(),
+    }
+}
+
+/// Pick the examples that reference the most public items, preferring smaller
+/// snippets on ties, until the byte budget or `MAX_EXAMPLES` is reached.
+pub fn select_examples(
+    candidates: Vec<Example>,
+    public_items: &[String],
+    budget_bytes: usize,
+) -> Vec<Example> {
+    let items: HashSet<&str> = public_items.iter().map(String::as_str).collect();
+    let mut seen = HashSet::new();
+    let mut scored: Vec<(usize, Example)> = candidates
+        .into_iter()
+        .filter(|e| seen.insert(e.code.trim().to_string()))
+        .map(|e| (coverage(&e.code, &items), e))
+        .collect();
+    // Stable sort keeps source order among equally good snippets.
+    scored.sort_by(|(a_cov, a), (b_cov, b)| b_cov.cmp(a_cov).then(a.code.len().cmp(&b.code.len())));
+
+    let mut used = 0;
+    let mut selected = Vec::new();
+    for (_, example) in scored {
+        if selected.len() == MAX_EXAMPLES {
+            break;
+        }
+        if used + example.code.len() > budget_bytes {
+            continue;
+        }
+        used += example.code.len();
+        selected.push(example);
+    }
+    selected
+}
+
+/// `## Examples` section, or an empty string when nothing was selected.
+pub fn render_examples_section(examples: &[Example]) -> String {
+    if examples.is_empty() {
+        return String::new();
+    }
+
+    let mut out = String::from("## Examples\n\n");
+    for example in examples {
+        out.push_str(&format!("From {}:\n\n", example.origin));
+        out.push_str(&format!("```rust\n{}\n```\n\n", example.code));
+    }
+    out
+}
+
+fn fence_info(line: &str) -> Option<&str> {
+    line.strip_prefix("```")
+        .or_else(|| line.strip_prefix("~~~"))
+        .map(str::trim)
+}
+
+fn is_rust_fence(info: &str) -> bool {
+    info.split([',', ' ']).filter(|t| !t.is_empty()).all(|t| {
+        matches!(t, "rust" | "no_run" | "ignore" | "should_panic") || t.starts_with("edition")
+    })
+}
+
+fn strip_hidden_lines(lines: &[&str]) -> String {
+    lines
+        .iter()
+        .filter(|line| {
+            let t = line.trim_start();
+            t != "#" && !t.starts_with("# ")
+        })
+        .copied()
+        .collect::<Vec<_>>()
+        .join("\n")
+        .trim_matches('\n')
+        .to_string()
+}
+
+fn coverage(code: &str, items: &HashSet<&str>) -> usize {
+    code.split(|c: char| !(c.is_alphanumeric() || c == '_'))
+        .filter(|token| items.contains(token))
+        .collect::<HashSet<_>>()
+        .len()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{doc_code_blocks, example_file, render_examples_section, select_examples};
+
+    #[test]
+    fn extracts_rust_blocks_and_drops_hidden_lines() {
+        let docs = "Intro\n\n```\n# use demo::Counter;\nlet c = Counter::new(1);\n```\n\n```text\nnot code\n```\n\n```rust,compile_fail\nbad();\n```\n\n```rust,no_run\n#[derive(Debug)]\nstruct X;\n```";
+        let blocks = doc_code_blocks("crate docs", docs);
+        assert_eq!(blocks.len(), 2);
+        assert_eq!(blocks[0].code, "let c = Counter::new(1);");
+        assert_eq!(blocks[1].code, "#[derive(Debug)]\nstruct X;");
+    }
+
+    #[test]
+    fn prefers_coverage_then_size_within_budget() {
+        let items = vec![
+            "Counter".to_string(),
+            "share".to_string(),
+            "total".to_string(),
+        ];
+        let candidates = vec![
+            example_file("examples/big.rs", &"// filler\n".repeat(50)),
+            doc_code_blocks("crate docs", "```\nlet c = Counter::new(1);\n```").remove(0),
+            example_file(
+                "examples/basic.rs",
+                "fn main() { let c = Counter::new(1); share(c); total([]); }",
+            ),
+            doc_code_blocks("`Counter` docs", "```\nlet c = Counter::new(1);\n```").remove(0),
+        ];
+
+        let selected = select_examples(candidates, &items, 200);
+        let origins: Vec<&str> = selected.iter().map(|e| e.origin.as_str()).collect();
+        assert_eq!(origins, vec!["`examples/basic.rs`", "crate docs"]);
+    }
+
+    #[test]
+    fn renders_nothing_without_examples() {
+        assert_eq!(render_examples_section(&[]), "");
+        let section = render_examples_section(&[example_file("examples/a.rs", "fn main() {}\n")]);
+        assert_eq!(
+            section,
+            "## Examples\n\nFrom `examples/a.rs`:\n\n```rust\nfn main() {}\n```\n\n"
+        );
+    }
+}
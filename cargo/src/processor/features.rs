@@ -0,0 +1,378 @@
+use std::collections::HashSet;
+
+use scraper::{ElementRef, Html, Selector};
+
+/// One Cargo feature flag and what turning it on pulls in.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FeatureInfo {
+    pub name: String,
+    /// Features and `dep:` entries listed in `[features]`.
+    pub enables: Vec<String>,
+    /// Comment text written above the feature in `Cargo.toml`.
+    pub docs: String,
+}
+
+/// Where the feature list came from, for the `FEATURES.md` header.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FeatureSource<'a> {
+    Manifest(&'a str),
+    DocsRs(&'a str),
+}
+
+/// Features declared in a `Cargo.toml`, in declaration order, followed by the
+/// implicit features of optional dependencies that no feature refers to via `dep:`.
+///
+/// Comments directly above a feature (`#` or `document-features` style `##`)
+/// become its docs; a blank line ends a comment block.
+pub fn parse_manifest_features(manifest: &str) -> Vec<FeatureInfo> {
+    let Ok(value) = toml::from_str::<toml::Value>(manifest) else {
+        return Vec::new();
+    };
+    let table = value.get("features").and_then(toml::Value::as_table);
+
+    let mut features: Vec<FeatureInfo> = declared_feature_docs(manifest)
+        .into_iter()
+        .filter_map(|(name, docs)| {
+            let enables = table?
+                .get(&name)?
+                .as_array()?
+                .iter()
+                .filter_map(|v| v.as_str().map(str::to_string))
+                .collect();
+            Some(FeatureInfo {
+                name,
+                enables,
+                docs,
+            })
+        })
+        .collect();
+
+    // Keys the line scan could not attribute still belong in the list.
+    if let Some(table) = table {
+        for (name, enables) in table {
+            if features.iter().any(|f| &f.name == name) {
+                continue;
+            }
+            features.push(FeatureInfo {
+                name: name.clone(),
+                enables: enables
+                    .as_array()
+                    .map(|a| {
+                        a.iter()
+                            .filter_map(|v| v.as_str().map(str::to_string))
+                            .collect()
+                    })
+                    .unwrap_or_default(),
+                docs: String::new(),
+            });
+        }
+    }
+
+    let explicit_deps: HashSet<&str> = features
+        .iter()
+        .flat_map(|f| f.enables.iter())
+        .filter_map(|e| e.strip_prefix("dep:"))
+        .collect();
+    let implicit: Vec<FeatureInfo> = optional_dependencies(&value)
+        .into_iter()
+        .filter(|dep| !explicit_deps.contains(dep.as_str()))
+        .filter(|dep| !features.iter().any(|f| &f.name == dep))
+        .map(|dep| FeatureInfo {
+            enables: vec![format!("dep:{dep}")],
+            docs: format!("Implicit feature for the optional dependency `{dep}`."),
+            name: dep,
+        })
+        .collect();
+    features.extend(implicit);
+    features
+}
+
+/// Feature names from the docs.rs `/crate/{name}/{version}/features` page:
+/// each feature is an `h3` followed by a list of what it enables.
+pub fn parse_docsrs_features_page(html: &str) -> Vec<FeatureInfo> {
+    let document = Html::parse_document(html);
+    let heading = Selector::parse("h3[id]").expect("valid selector");
+    let item = Selector::parse("li").expect("valid selector");
+
+    document
+        .select(&heading)
+        .map(|h3| {
+            let name = h3.text().collect::<String>().trim().to_string();
+            let mut enables = Vec::new();
+            for sibling in h3.next_siblings().filter_map(ElementRef::wrap) {
+                let tag = sibling.value().name();
+                if tag.starts_with('h') && tag.len() == 2 {
+                    break;
+                }
+                enables.extend(
+                    sibling
+                        .select(&item)
+                        .map(|li| li.text().collect::<String>().trim().to_string())
+                        .filter(|text| !text.is_empty()),
+                );
+            }
+            FeatureInfo {
+                name,
+                enables,
+                docs: String::new(),
+            }
+        })
+        .filter(|f| !f.name.is_empty())
+        .collect()
+}
+
+/// `FEATURES.md`: every feature with what it enables, its docs, and whether the
+/// project build turns it on (`enabled` is `None` when the crate is not in `cargo metadata`).
+pub fn render_features_markdown(
+    crate_name: &str,
+    version: &str,
+    features: &[FeatureInfo],
+    source: FeatureSource<'_>,
+    enabled: Option<&[String]>,
+) -> String {
+    let mut out = format!("# {crate_name}@{version} — Feature flags\n\n");
+    match source {
+        FeatureSource::Manifest(path) => {
+            out.push_str(&format!("Source: `Cargo.toml` from `{path}`.\n"));
+        }
+        FeatureSource::DocsRs(url) => out.push_str(&format!("Source: {url}\n")),
+    }
+
+    let enabled: Option<HashSet<&str>> =
+        enabled.map(|list| list.iter().map(String::as_str).collect());
+    match &enabled {
+        Some(set) => {
+            let count = features
+                .iter()
+                .filter(|f| set.contains(f.name.as_str()))
+                .count();
+            out.push_str(&format!(
+                "Enabled for this project (from `cargo metadata`): {count} of {}.\n\n",
+                features.len()
+            ));
+        }
+        None => out.push_str(
+            "Enabled for this project: unknown (crate not found in `cargo metadata`).\n\n",
+        ),
+    }
+
+    if features.is_empty() {
+        out.push_str("This crate declares no feature flags.\n");
+        return out;
+    }
+
+    if let Some(default) = features.iter().find(|f| f.name == "default") {
+        out.push_str(&format!(
+            "Default features: {}\n\n",
+            code_list(&default.enables).unwrap_or_else(|| "none".to_string())
+        ));
+    }
+
+    for feature in features {
+        out.push_str(&format!("## `{}`\n\n", feature.name));
+        let status = match &enabled {
+            Some(set) if set.contains(feature.name.as_str()) => "yes",
+            Some(_) => "no",
+            None => "unknown",
+        };
+        out.push_str(&format!("- Enabled in this build: {status}\n"));
+        out.push_str(&format!(
+            "- Enables: {}\n",
+            code_list(&feature.enables).unwrap_or_else(|| "nothing else".to_string())
+        ));
+        if !feature.docs.is_empty() {
+            out.push('\n');
+            out.push_str(&feature.docs);
+            out.push('\n');
+        }
+        out.push('\n');
+    }
+    out
+}
+
+fn code_list(items: &[String]) -> Option<String> {
+    if items.is_empty() {
+        return None;
+    }
+    Some(
+        items
+            .iter()
+            .map(|i| format!("`{i}`"))
+            .collect::<Vec<_>>()
+            .join(", "),
+    )
+}
+
+/// `(feature, docs)` pairs from the raw `[features]` table text; `toml` drops comments.
+fn declared_feature_docs(manifest: &str) -> Vec<(String, String)> {
+    let mut out = Vec::new();
+    let mut in_features = false;
+    let mut depth = 0i32;
+    let mut comments: Vec<String> = Vec::new();
+
+    for line in manifest.lines() {
+        let trimmed = line.trim();
+        if depth == 0 && trimmed.starts_with('[') {
+            in_features = trimmed.trim_end_matches(|c: char| c != ']') == "[features]";
+            comments.clear();
+            continue;
+        }
+        if !in_features {
+            continue;
+        }
+
+        if depth == 0 {
+            if trimmed.is_empty() {
+                comments.clear();
+                continue;
+            }
+            if let Some(comment) = trimmed.strip_prefix('#') {
+                // `#!` is free-form text for document-features, not per-feature docs.
+                if !comment.starts_with('!') {
+                    comments.push(comment.trim_start_matches('#').trim().to_string());
+                }
+                continue;
+            }
+            if let Some((key, _)) = trimmed.split_once('=') {
+                let name = key.trim().trim_matches('"').to_string();
+                out.push((name, comments.join("\n").trim().to_string()));
+                comments.clear();
+            }
+        }
+        depth += bracket_delta(trimmed);
+    }
+    out
+}
+
+/// Net `[`/`]` count outside strings and comments, to follow multi-line arrays.
+fn bracket_delta(line: &str) -> i32 {
+    let mut delta = 0;
+    let mut in_string = false;
+    for c in line.chars() {
+        match c {
+            '"' => in_string = !in_string,
+            '#' if !in_string => break,
+            '[' if !in_string => delta += 1,
+            ']' if !in_string => delta -= 1,
+            _ => {}
+        }
+    }
+    delta
+}
+
+fn optional_dependencies(manifest: &toml::Value) -> Vec<String> {
+    let mut tables: Vec<&toml::value::Table> = ["dependencies", "build-dependencies"]
+        .iter()
+        .filter_map(|key| manifest.get(key)?.as_table())
+        .collect();
+    if let Some(targets) = manifest.get("target").and_then(toml::Value::as_table) {
+        for target in targets.values() {
+            tables.extend(
+                ["dependencies", "build-dependencies"]
+                    .iter()
+                    .filter_map(|key| target.get(key)?.as_table()),
+            );
+        }
+    }
+
+    let mut deps = Vec::new();
+    for table in tables {
+        for (name, spec) in table {
+            let optional = spec
+                .get("optional")
+                .and_then(toml::Value::as_bool)
+                .unwrap_or(false);
+            if optional && !deps.contains(name) {
+                deps.push(name.clone());
+            }
+        }
+    }
+    deps
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        parse_docsrs_features_page, parse_manifest_features, render_features_markdown,
+        FeatureSource,
+    };
+
+    const MANIFEST: &str = r#"[package]
+name = "demo"
+version = "0.3.1"
+
+[features]
+default = ["std"]
+
+## Use the standard library.
+std = []
+
+# Serde support for all public types.
+# Pulls in `serde` with derive.
+serde = ["dep:serde", "std"]
+full = [
+    "std",
+    "serde",
+]
+
+[dependencies]
+serde = { version = "1", optional = true }
+tracing = { version = "0.1", optional = true }
+memchr = "2"
+"#;
+
+    #[test]
+    fn parses_manifest_features_with_docs_and_implicit_deps() {
+        let features = parse_manifest_features(MANIFEST);
+        let names: Vec<&str> = features.iter().map(|f| f.name.as_str()).collect();
+        assert_eq!(names, vec!["default", "std", "serde", "full", "tracing"]);
+
+        assert_eq!(features[1].docs, "Use the standard library.");
+        assert_eq!(
+            features[2].docs,
+            "Serde support for all public types.\nPulls in `serde` with derive."
+        );
+        assert_eq!(features[2].enables, vec!["dep:serde", "std"]);
+        assert_eq!(features[3].enables, vec!["std", "serde"]);
+        assert!(features[3].docs.is_empty());
+        assert_eq!(features[4].enables, vec!["dep:tracing"]);
+    }
+
+    #[test]
+    fn parses_docsrs_features_page() {
+        let html = include_str!("../../tests/fixtures/docsrs/serde-1.0.210-features.html");
+        let features = parse_docsrs_features_page(html);
+        let names: Vec<&str> = features.iter().map(|f| f.name.as_str()).collect();
+        assert_eq!(names, vec!["default", "alloc", "derive", "std", "unstable"]);
+        assert_eq!(features[0].enables, vec!["std"]);
+        assert_eq!(features[2].enables, vec!["serde_derive"]);
+        assert!(features[3].enables.is_empty());
+    }
+
+    #[test]
+    fn renders_enabled_state_per_feature() {
+        let features = parse_manifest_features(MANIFEST);
+        let enabled = vec!["default".to_string(), "std".to_string()];
+        let markdown = render_features_markdown(
+            "demo",
+            "0.3.1",
+            &features,
+            FeatureSource::Manifest("vendor/demo"),
+            Some(&enabled),
+        );
+        assert!(markdown.starts_with("# demo@0.3.1 — Feature flags\n"));
+        assert!(markdown.contains("(from `cargo metadata`): 2 of 5."));
+        assert!(markdown.contains("Default features: `std`"));
+        assert!(markdown.contains("## `std`\n\n- Enabled in this build: yes\n- Enables: nothing else\n\nUse the standard library.\n"));
+        assert!(markdown.contains("## `serde`\n\n- Enabled in this build: no\n"));
+
+        let unknown = render_features_markdown(
+            "demo",
+            "0.3.1",
+            &features,
+            FeatureSource::DocsRs("https://docs.rs/crate/demo/0.3.1/features"),
+            None,
+        );
+        assert!(unknown.contains("- Enabled in this build: unknown"));
+    }
+}
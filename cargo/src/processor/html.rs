@@ -0,0 +1,525 @@
+use ego_tree::NodeRef;
+use reqwest::Url;
+use scraper::{ElementRef, Html, Node, Selector};
+
+/// Convert the first element matching one of `selectors` (tried in order) into Markdown.
+pub fn select_to_markdown(html: &str, selectors: &[&str], page_url: &str) -> Option<String> {
+    let document = Html::parse_document(html);
+    let renderer = Renderer::new(page_url);
+
+    selectors.iter().find_map(|raw| {
+        let selector = Selector::parse(raw).ok()?;
+        let element = document.select(&selector).next()?;
+        Some(renderer.render_container(element))
+    })
+}
+
+/// Resolve `href` against `page_url` into an absolute URL.
+///
+/// Fragment-only links and non-HTTP schemes are returned unchanged.
+pub fn absolutize_url(page_url: &str, href: &str) -> String {
+    let href = href.trim();
+    if href.is_empty() || href.starts_with('#') {
+        return href.to_string();
+    }
+
+    match Url::parse(page_url).and_then(|base| base.join(href)) {
+        Ok(url) => url.to_string(),
+        Err(_) => href.to_string(),
+    }
+}
+
+struct Renderer {
+    page_url: String,
+}
+
+impl Renderer {
+    fn new(page_url: &str) -> Self {
+        Self {
+            page_url: page_url.to_string(),
+        }
+    }
+
+    fn render_container(&self, element: ElementRef<'_>) -> String {
+        self.blocks(*element).join("\n\n").trim().to_string()
+    }
+
+    /// Render the children of `node` as a list of Markdown blocks.
+    ///
+    /// Consecutive inline children are gathered into a single paragraph;
+    /// block-level children flush that paragraph and render on their own.
+    fn blocks(&self, node: NodeRef<'_, Node>) -> Vec<String> {
+        let mut blocks = Vec::new();
+        let mut inline = String::new();
+
+        for child in node.children() {
+            match child.value() {
+                Node::Text(text) => inline.push_str(&text.replace('\n', " ")),
+                Node::Element(el) => {
+                    if is_skipped(el) {
+                        continue;
+                    }
+                    if is_block(el.name()) {
+                        push_paragraph(&mut blocks, &inline);
+                        inline.clear();
+                        blocks.extend(self.block(child, el));
+                    } else {
+                        inline.push_str(&self.inline(child));
+                    }
+                }
+                _ => {}
+            }
+        }
+
+        push_paragraph(&mut blocks, &inline);
+        blocks
+    }
+
+    fn block(&self, node: NodeRef<'_, Node>, el: &scraper::node::Element) -> Vec<String> {
+        match el.name() {
+            "h1" | "h2" | "h3" | "h4" | "h5" | "h6" => {
+                let level = usize::from(el.name().as_bytes()[1] - b'0');
+                let text = collapse_whitespace(&self.inline_children(node));
+                if text.is_empty() {
+                    Vec::new()
+                } else {
+                    vec![format!("{} {text}", "#".repeat(level))]
+                }
+            }
+            "pre" => vec![render_code_block(node, el)],
+            "ul" | "ol" => vec![self.list(node, el.name() == "ol")],
+            "dl" => vec![self.definition_list(node)],
+            "table" => self.table(node).into_iter().collect(),
+            "blockquote" => {
+                let inner = self.blocks(node).join("\n\n");
+                if inner.trim().is_empty() {
+                    Vec::new()
+                } else {
+                    vec![prefix_lines(&inner, "> ", "> ")]
+                }
+            }
+            "hr" => vec!["---".to_string()],
+            _ => self.blocks(node),
+        }
+    }
+
+    fn list(&self, node: NodeRef<'_, Node>, ordered: bool) -> String {
+        let mut items = Vec::new();
+        for child in node.children() {
+            let Some(el) = child.value().as_element() else {
+                continue;
+            };
+            if el.name() != "li" || is_skipped(el) {
+                continue;
+            }
+
+            let marker = if ordered {
+                format!("{}. ", items.len() + 1)
+            } else {
+                "- ".to_string()
+            };
+            let indent = " ".repeat(marker.len());
+            let body = self.blocks(child).join("\n");
+            items.push(prefix_lines(body.trim(), &marker, &indent));
+        }
+        items.join("\n")
+    }
+
+    fn definition_list(&self, node: NodeRef<'_, Node>) -> String {
+        let mut items: Vec<String> = Vec::new();
+        for child in node.children() {
+            let Some(el) = child.value().as_element() else {
+                continue;
+            };
+            let text = collapse_whitespace(&self.inline_children(child));
+            if text.is_empty() {
+                continue;
+            }
+            match el.name() {
+                "dt" => items.push(format!("- {text}")),
+                "dd" => match items.last_mut() {
+                    Some(last) => {
+                        last.push_str(" — ");
+                        last.push_str(&text);
+                    }
+                    None => items.push(format!("- {text}")),
+                },
+                _ => {}
+            }
+        }
+        items.join("\n")
+    }
+
+    fn table(&self, node: NodeRef<'_, Node>) -> Option<String> {
+        let mut rows: Vec<Vec<String>> = Vec::new();
+        collect_rows(node, &mut |row| {
+            let cells: Vec<String> = row
+                .children()
+                .filter(|c| {
+                    c.value()
+                        .as_element()
+                        .is_some_and(|e| matches!(e.name(), "td" | "th"))
+                })
+                .map(|c| collapse_whitespace(&self.inline_children(c)).replace('|', "\\|"))
+                .collect();
+            if !cells.is_empty() {
+                rows.push(cells);
+            }
+        });
+
+        let columns = rows.iter().map(Vec::len).max()?;
+        let mut out = Vec::with_capacity(rows.len() + 1);
+        for (idx, mut row) in rows.into_iter().enumerate() {
+            row.resize(columns, String::new());
+            out.push(format!("| {} |", row.join(" | ")));
+            if idx == 0 {
+                out.push(format!("|{}", " --- |".repeat(columns)));
+            }
+        }
+        Some(out.join("\n"))
+    }
+
+    fn inline_children(&self, node: NodeRef<'_, Node>) -> String {
+        node.children().map(|child| self.inline(child)).collect()
+    }
+
+    fn inline(&self, node: NodeRef<'_, Node>) -> String {
+        match node.value() {
+            Node::Text(text) => text.replace('\n', " "),
+            Node::Element(el) if is_skipped(el) => String::new(),
+            Node::Element(el) => match el.name() {
+                "br" => "\n".to_string(),
+                "code" | "kbd" | "samp" | "tt" => {
+                    inline_code(&collapse_whitespace(&text_content(node)))
+                }
+                "strong" | "b" => wrap_emphasis(&self.inline_children(node), "**"),
+                "em" | "i" => wrap_emphasis(&self.inline_children(node), "*"),
+                "del" | "s" => wrap_emphasis(&self.inline_children(node), "~~"),
+                "a" => self.link(node, el),
+                "img" => String::new(),
+                _ => self.inline_children(node),
+            },
+            _ => String::new(),
+        }
+    }
+
+    fn link(&self, node: NodeRef<'_, Node>, el: &scraper::node::Element) -> String {
+        let text = collapse_whitespace(&self.inline_children(node));
+        let Some(href) = el.attr("href") else {
+            return text;
+        };
+        if text.is_empty() || href.starts_with('#') || href.starts_with("javascript:") {
+            return text;
+        }
+        format!("[{text}]({})", absolutize_url(&self.page_url, href))
+    }
+}
+
+const BLOCK_ELEMENTS: &[&str] = &[
+    "address",
+    "article",
+    "aside",
+    "blockquote",
+    "details",
+    "div",
+    "dl",
+    "fieldset",
+    "figure",
+    "footer",
+    "form",
+    "h1",
+    "h2",
+    "h3",
+    "h4",
+    "h5",
+    "h6",
+    "header",
+    "hr",
+    "li",
+    "main",
+    "nav",
+    "ol",
+    "p",
+    "pre",
+    "section",
+    "summary",
+    "table",
+    "ul",
+];
+
+const SKIPPED_ELEMENTS: &[&str] = &[
+    "button", "head", "iframe", "noscript", "script", "style", "svg", "template",
+];
+
+/// rustdoc UI chrome that carries no documentation text.
+const SKIPPED_CLASSES: &[&str] = &["anchor", "doc-anchor", "hideme", "src", "sr-only"];
+
+fn is_block(name: &str) -> bool {
+    BLOCK_ELEMENTS.contains(&name)
+}
+
+fn is_skipped(el: &scraper::node::Element) -> bool {
+    SKIPPED_ELEMENTS.contains(&el.name()) || el.classes().any(|c| SKIPPED_CLASSES.contains(&c))
+}
+
+fn collect_rows<'a>(node: NodeRef<'a, Node>, visit: &mut dyn FnMut(NodeRef<'a, Node>)) {
+    for child in node.children() {
+        let Some(el) = child.value().as_element() else {
+            continue;
+        };
+        match el.name() {
+            "tr" => visit(child),
+            "thead" | "tbody" | "tfoot" => collect_rows(child, visit),
+            _ => {}
+        }
+    }
+}
+
+fn render_code_block(node: NodeRef<'_, Node>, el: &scraper::node::Element) -> String {
+    let code_el = node
+        .children()
+        .filter_map(ElementRef::wrap)
+        .find(|c| c.value().name() == "code");
+    let language = code_language(el)
+        .or_else(|| code_el.and_then(|c| code_language(c.value())))
+        .unwrap_or_default();
+
+    let body = text_content(node);
+    let body = body.trim_end_matches('\n');
+    let fence = if body.contains("```") { "~~~" } else { "```" };
+    format!("{fence}{language}\n{body}\n{fence}")
+}
+
+fn code_language(el: &scraper::node::Element) -> Option<String> {
+    let classes: Vec<&str> = el.classes().collect();
+    if let Some(lang) = classes.iter().find_map(|c| c.strip_prefix("language-")) {
+        return Some(lang.to_string());
+    }
+    if classes.iter().any(|c| c.starts_with("rust")) {
+        return Some("rust".to_string());
+    }
+    None
+}
+
+fn text_content(node: NodeRef<'_, Node>) -> String {
+    node.descendants()
+        .filter_map(|n| n.value().as_text().map(|t| t.to_string()))
+        .collect()
+}
+
+fn inline_code(text: &str) -> String {
+    if text.is_empty() {
+        return String::new();
+    }
+    if text.contains('`') {
+        format!("`` {text} ``")
+    } else {
+        format!("`{text}`")
+    }
+}
+
+fn wrap_emphasis(inner: &str, marker: &str) -> String {
+    let trimmed = inner.trim();
+    if trimmed.is_empty() {
+        return inner.to_string();
+    }
+    let leading = if inner.starts_with(char::is_whitespace) {
+        " "
+    } else {
+        ""
+    };
+    let trailing = if inner.ends_with(char::is_whitespace) {
+        " "
+    } else {
+        ""
+    };
+    format!("{leading}{marker}{trimmed}{marker}{trailing}")
+}
+
+/// Collapse whitespace runs into single spaces while keeping explicit `<br>` line breaks.
+fn collapse_whitespace(text: &str) -> String {
+    text.split('\n')
+        .map(|line| line.split_whitespace().collect::<Vec<_>>().join(" "))
+        .filter(|line| !line.is_empty())
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+fn push_paragraph(blocks: &mut Vec<String>, inline: &str) {
+    let paragraph = collapse_paragraph(inline);
+    if !paragraph.is_empty() {
+        blocks.push(paragraph);
+    }
+}
+
+/// Like [`collapse_whitespace`], but keeps `<br>`-produced newlines inside a paragraph.
+fn collapse_paragraph(text: &str) -> String {
+    let single_spaced: String = {
+        let mut out = String::with_capacity(text.len());
+        let mut last_space = false;
+        for ch in text.chars() {
+            if ch == '\n' {
+                out.push('\n');
+                last_space = false;
+            } else if ch.is_whitespace() {
+                if !last_space {
+                    out.push(' ');
+                }
+                last_space = true;
+            } else {
+                out.push(ch);
+                last_space = false;
+            }
+        }
+        out
+    };
+
+    single_spaced
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty())
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+fn prefix_lines(text: &str, first: &str, rest: &str) -> String {
+    text.lines()
+        .enumerate()
+        .map(|(idx, line)| {
+            let prefix = if idx == 0 { first } else { rest };
+            if line.is_empty() {
+                prefix.trim_end().to_string()
+            } else {
+                format!("{prefix}{line}")
+            }
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{absolutize_url, select_to_markdown, Renderer};
+    use scraper::Html;
+
+    const PAGE: &str = "https://docs.rs/serde/1.0.0/serde/";
+
+    fn html_to_markdown(html: &str, page_url: &str) -> String {
+        let fragment = Html::parse_fragment(html);
+        Renderer::new(page_url).render_container(fragment.root_element())
+    }
+
+    fn assert_snapshot(name: &str, actual: &str) {
+        let path = std::path::Path::new(env!("CARGO_MANIFEST_DIR"))
+            .join("tests/fixtures/docsrs")
+            .join(format!("{name}.md"));
+        if std::env::var_os("AIFD_UPDATE_SNAPSHOTS").is_some() {
+            std::fs::write(&path, actual).expect("write snapshot");
+            return;
+        }
+        let expected = std::fs::read_to_string(&path).expect("read snapshot");
+        assert_eq!(
+            actual,
+            expected.trim_end_matches('\n'),
+            "snapshot {name} differs"
+        );
+    }
+
+    #[test]
+    fn renders_heading_levels() {
+        let md = html_to_markdown(
+            "<h1>Top</h1><h3>Third <a class=\"doc-anchor\" href=\"#third\">§</a></h3>",
+            PAGE,
+        );
+        assert_eq!(md, "# Top\n\n### Third");
+    }
+
+    #[test]
+    fn rewrites_links_to_absolute_docs_rs_urls() {
+        let md = html_to_markdown(
+            r#"<p>See <a href="trait.Serialize.html">Serialize</a>, <a href="/tokio/latest/tokio/">tokio</a> and <a href="https://serde.rs">site</a>.</p>"#,
+            PAGE,
+        );
+        assert_eq!(
+            md,
+            "See [Serialize](https://docs.rs/serde/1.0.0/serde/trait.Serialize.html), [tokio](https://docs.rs/tokio/latest/tokio/) and [site](https://serde.rs/)."
+        );
+    }
+
+    #[test]
+    fn renders_inline_code_and_tagged_code_blocks() {
+        let md = html_to_markdown(
+            r#"<p>Call <code>to_string</code>.</p><pre class="language-toml"><code>[dependencies]
+serde = "1"
+</code></pre><pre class="rust rust-example-rendered"><code>let x = 1;</code></pre>"#,
+            PAGE,
+        );
+        assert_eq!(
+            md,
+            "Call `to_string`.\n\n```toml\n[dependencies]\nserde = \"1\"\n```\n\n```rust\nlet x = 1;\n```"
+        );
+    }
+
+    #[test]
+    fn renders_nested_lists() {
+        let md = html_to_markdown(
+            "<ul><li>One<ul><li>Inner</li></ul></li><li>Two</li></ul><ol><li>First</li><li>Second</li></ol>",
+            PAGE,
+        );
+        assert_eq!(md, "- One\n  - Inner\n- Two\n\n1. First\n2. Second");
+    }
+
+    #[test]
+    fn renders_tables_with_escaped_pipes() {
+        let md = html_to_markdown(
+            "<table><thead><tr><th>Feature</th><th>Default</th></tr></thead><tbody><tr><td><code>a|b</code></td><td>yes</td></tr></tbody></table>",
+            PAGE,
+        );
+        assert_eq!(
+            md,
+            "| Feature | Default |\n| --- | --- |\n| `a\\|b` | yes |"
+        );
+    }
+
+    #[test]
+    fn decodes_entities_and_keeps_utf8() {
+        let md = html_to_markdown(
+            "<p>Vec&lt;T&gt; &amp; Option&#x3C;T&#x3E; — naïve 日本語 &copy;</p>",
+            PAGE,
+        );
+        assert_eq!(md, "Vec<T> & Option<T> — naïve 日本語 ©");
+    }
+
+    #[test]
+    fn absolutize_keeps_fragments_and_resolves_parent_paths() {
+        assert_eq!(absolutize_url(PAGE, "#examples"), "#examples");
+        assert_eq!(
+            absolutize_url(PAGE, "../serde/de/index.html"),
+            "https://docs.rs/serde/1.0.0/serde/de/index.html"
+        );
+    }
+
+    #[test]
+    fn snapshot_rustdoc_crate_root() {
+        let html = include_str!("../../tests/fixtures/docsrs/serde-1.0.210-root.html");
+        let md = select_to_markdown(
+            html,
+            &["#main-content"],
+            "https://docs.rs/serde/1.0.210/serde/",
+        )
+        .expect("main content");
+        assert_snapshot("serde-1.0.210-root", &md);
+    }
+
+    #[test]
+    fn snapshot_rustdoc_module_page() {
+        let html = include_str!("../../tests/fixtures/docsrs/tokio-1.44.0-sync.html");
+        let md = select_to_markdown(
+            html,
+            &["#main-content"],
+            "https://docs.rs/tokio/1.44.0/tokio/sync/index.html",
+        )
+        .expect("main content");
+        assert_snapshot("tokio-1.44.0-sync", &md);
+    }
+}
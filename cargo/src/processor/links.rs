@@ -0,0 +1,170 @@
+use std::collections::HashMap;
+
+use regex::{Captures, Regex};
+
+use super::cleanup::mark_code;
+
+const IMAGE_EXTENSIONS: &[&str] = &["png", "jpg", "jpeg", "gif", "svg", "webp", "ico", "bmp"];
+
+/// Where relative links of one fetched file should point once it is flattened
+/// into the crate folder.
+pub struct LinkBase<'a> {
+    /// `owner/repo` on GitHub.
+    pub repo: &'a str,
+    pub git_ref: &'a str,
+    /// Path of the file in the repository, subpath included (`tokio/README.md`).
+    pub file_path: &'a str,
+    /// Repository path → saved file name, for files synced next to this one.
+    pub local_copies: &'a HashMap<String, String>,
+}
+
+/// Rewrite relative links, images and link reference definitions (Markdown and
+/// HTML `href`/`src`) outside code blocks: to the synced copy when the target
+/// was fetched too, else to GitHub pinned to `git_ref` (`raw.githubusercontent.com`
+/// for images). Returns the text and the number of links rewritten.
+pub fn rewrite_relative_links(content: &str, base: &LinkBase<'_>) -> (String, usize) {
+    let patterns = [
+        Regex::new(r"\]\(\s*<?(?P<url>[^)\s>]+)").expect("valid inline link regex"),
+        Regex::new(r"^ {0,3}\[[^\]]+\]:\s*<?(?P<url>[^\s>]+)").expect("valid reference regex"),
+        Regex::new(r#"(?i)\b(?:href|src)\s*=\s*["'](?P<url>[^"']+)["']"#)
+            .expect("valid html attribute regex"),
+    ];
+
+    let mut out = String::with_capacity(content.len());
+    let mut rewritten = 0;
+    for (line, code) in mark_code(content) {
+        if code {
+            out.push_str(line);
+            continue;
+        }
+        let mut line = line.to_string();
+        for re in &patterns {
+            line = re
+                .replace_all(&line, |caps: &Captures<'_>| {
+                    let whole = caps.get(0).expect("whole match");
+                    let url = caps.name("url").expect("url group");
+                    let Some(target) = resolve(url.as_str(), base) else {
+                        return whole.as_str().to_string();
+                    };
+                    rewritten += 1;
+                    let (start, end) = (url.start() - whole.start(), url.end() - whole.start());
+                    format!(
+                        "{}{target}{}",
+                        &whole.as_str()[..start],
+                        &whole.as_str()[end..]
+                    )
+                })
+                .into_owned();
+        }
+        out.push_str(&line);
+    }
+    (out, rewritten)
+}
+
+/// New target for a relative `url`, or `None` to leave it as is (absolute URLs,
+/// in-page anchors, paths escaping the repository).
+fn resolve(url: &str, base: &LinkBase<'_>) -> Option<String> {
+    let has_scheme = url.split_once(':').is_some_and(|(scheme, _)| {
+        scheme.starts_with(|c: char| c.is_ascii_alphabetic())
+            && scheme
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || "+-.".contains(c))
+    });
+    if url.is_empty() || has_scheme || url.starts_with('#') || url.starts_with("//") {
+        return None;
+    }
+
+    let split = url.find(['?', '#']).unwrap_or(url.len());
+    let (path, suffix) = url.split_at(split);
+    let dir = if path.starts_with('/') {
+        ""
+    } else {
+        base.file_path.rsplit_once('/').map_or("", |(dir, _)| dir)
+    };
+    let resolved = normalize(&format!("{dir}/{path}"))?;
+
+    if let Some(local) = base.local_copies.get(&resolved) {
+        let fragment = suffix.find('#').map_or("", |i| &suffix[i..]);
+        return Some(format!("{local}{fragment}"));
+    }
+    let is_image = resolved
+        .rsplit_once('.')
+        .is_some_and(|(_, ext)| IMAGE_EXTENSIONS.contains(&ext.to_ascii_lowercase().as_str()));
+    Some(if is_image {
+        format!(
+            "https://raw.githubusercontent.com/{}/{}/{resolved}{suffix}",
+            base.repo, base.git_ref
+        )
+    } else {
+        format!(
+            "https://github.com/{}/blob/{}/{resolved}{suffix}",
+            base.repo, base.git_ref
+        )
+    })
+}
+
+/// Resolve `.` and `..` segments; `None` when the path leaves the repository root.
+fn normalize(path: &str) -> Option<String> {
+    let mut segments: Vec<&str> = Vec::new();
+    for segment in path.split('/') {
+        match segment {
+            "" | "." => {}
+            ".." => {
+                segments.pop()?;
+            }
+            _ => segments.push(segment),
+        }
+    }
+    Some(segments.join("/"))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use super::{rewrite_relative_links, LinkBase};
+
+    #[test]
+    fn rewrites_relative_links_to_local_copies_or_pinned_urls() {
+        let local = HashMap::from([(
+            "tokio/CHANGELOG.md".to_string(),
+            "tokio__CHANGELOG.md".to_string(),
+        )]);
+        let base = LinkBase {
+            repo: "tokio-rs/tokio",
+            git_ref: "tokio-1.44.0",
+            file_path: "tokio/README.md",
+            local_copies: &local,
+        };
+        let readme = "See [the guide](docs/guide.md#setup), [changes](./CHANGELOG.md#100) and [examples](../examples/echo.rs).\n\
+![logo](../assets/logo.svg) [site](https://tokio.rs) [top](#top) [escape](../../x.md)\n\
+<img src=\"assets/banner.png\" width=\"200\">\n\
+\n\
+[contrib]: /CONTRIBUTING.md\n\
+\n\
+```md\n\
+[kept](docs/guide.md)\n\
+```\n";
+
+        let (out, rewritten) = rewrite_relative_links(readme, &base);
+        assert_eq!(rewritten, 6);
+        assert!(out.contains(
+            "[the guide](https://github.com/tokio-rs/tokio/blob/tokio-1.44.0/tokio/docs/guide.md#setup)"
+        ));
+        assert!(out.contains("[changes](tokio__CHANGELOG.md#100)"));
+        assert!(out.contains(
+            "[examples](https://github.com/tokio-rs/tokio/blob/tokio-1.44.0/examples/echo.rs)"
+        ));
+        assert!(out.contains(
+            "![logo](https://raw.githubusercontent.com/tokio-rs/tokio/tokio-1.44.0/assets/logo.svg)"
+        ));
+        assert!(out.contains(
+            "src=\"https://raw.githubusercontent.com/tokio-rs/tokio/tokio-1.44.0/tokio/assets/banner.png\""
+        ));
+        assert!(out.contains(
+            "[contrib]: https://github.com/tokio-rs/tokio/blob/tokio-1.44.0/CONTRIBUTING.md"
+        ));
+        assert!(out.contains("[site](https://tokio.rs) [top](#top) [escape](../../x.md)"));
+        assert!(out.contains("```md\n[kept](docs/guide.md)\n```"));
+    }
+}
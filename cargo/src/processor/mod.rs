@@ -1 +1,11 @@
+pub mod api_diff;
 pub mod changelog;
+pub mod chunk;
+pub mod cleanup;
+pub mod examples;
+pub mod features;
+pub mod html;
+pub mod links;
+pub mod rustdoc_json;
+pub mod sections;
+pub mod truncate;
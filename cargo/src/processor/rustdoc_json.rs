@@ -0,0 +1,1361 @@
+use std::collections::HashSet;
+
+use regex::Regex;
+use serde_json::{Map, Value};
+
+use crate::error::{AiDocsError, Result};
+use crate::processor::examples::{
+    doc_code_blocks, render_examples_section, select_examples, Example,
+};
+use crate::usage::covers;
+
+/// Item sections in the order they appear under each module.
+const SECTIONS: &[(&str, &str)] = &[
+    ("use", "Re-exports"),
+    ("module", "Modules"),
+    ("macro", "Macros"),
+    ("proc_macro", "Macros"),
+    ("struct", "Structs"),
+    ("enum", "Enums"),
+    ("union", "Unions"),
+    ("trait", "Traits"),
+    ("function", "Functions"),
+    ("type_alias", "Type aliases"),
+    ("constant", "Constants"),
+    ("static", "Statics"),
+];
+
+/// Render a rustdoc JSON document into a structured `API.md`.
+///
+/// Walks every public module from the crate root and emits signatures,
+/// generics, trait impls, feature gates and doc comments for each item.
+/// Code blocks from the crate root and top-level item docs compete with
+/// `repo_examples` for the `## Examples` section.
+pub fn render_api_markdown(
+    crate_name: &str,
+    version: &str,
+    doc: &Value,
+    repo_examples: &[Example],
+    example_budget_bytes: usize,
+    used_paths: &[String],
+) -> Result<String> {
+    let mut crate_doc = RustdocCrate::new(doc)?;
+    crate_doc.used_paths = used_paths.to_vec();
+    let root = crate_doc.root()?;
+
+    let mut out = String::new();
+    out.push_str(&format!("# {crate_name}@{version}\n\n"));
+    out.push_str("## Overview\n\n");
+    out.push_str(&format!(
+        "Generated from rustdoc JSON (format version {}) for `{crate_name}` `{version}`.\n\n",
+        crate_doc.format_version
+    ));
+    if let Some(docs) = docs_of(root) {
+        out.push_str(&demote_headings(docs, 2));
+        out.push_str("\n\n");
+    }
+
+    let mut candidates = docs_of(root)
+        .map(|docs| doc_code_blocks("crate docs", docs))
+        .unwrap_or_default();
+    for item in crate_doc.module_items(root) {
+        if let (Some(name), Some(docs)) = (item.get("name").and_then(Value::as_str), docs_of(item))
+        {
+            candidates.extend(doc_code_blocks(&format!("`{name}` docs"), docs));
+        }
+    }
+    candidates.extend(repo_examples.iter().cloned());
+    let examples = select_examples(
+        candidates,
+        &crate_doc.public_item_names(),
+        example_budget_bytes,
+    );
+    out.push_str(&render_examples_section(&examples));
+
+    let root_name = root
+        .get("name")
+        .and_then(Value::as_str)
+        .unwrap_or(crate_name)
+        .to_string();
+    let mut visited = HashSet::new();
+    crate_doc.render_module(&mut out, root, &root_name, true, &mut visited);
+
+    Ok(out.trim_end().to_string() + "\n")
+}
+
+/// A public item of the crate API with its rendered declaration.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ApiItem {
+    /// Path from the crate root, e.g. `demo::sync::Shared::new` for a method.
+    pub path: String,
+    pub kind: String,
+    pub signature: String,
+    pub deprecated: bool,
+}
+
+/// Every public item reachable from the crate root, with inherent methods
+/// listed under their type. Used to compare two versions of a crate.
+pub fn api_surface(doc: &Value) -> Result<Vec<ApiItem>> {
+    let crate_doc = RustdocCrate::new(doc)?;
+    let root = crate_doc.root()?;
+    let root_name = root
+        .get("name")
+        .and_then(Value::as_str)
+        .unwrap_or_default()
+        .to_string();
+
+    let mut items = Vec::new();
+    let mut visited = HashSet::new();
+    crate_doc.collect_surface(&mut items, root, &root_name, &mut visited);
+    Ok(items)
+}
+
+struct RustdocCrate<'a> {
+    root_id: String,
+    index: &'a Map<String, Value>,
+    format_version: u64,
+    feature_re: Regex,
+    /// Paths the project uses, relative to the crate root; rendered first.
+    used_paths: Vec<String>,
+}
+
+impl<'a> RustdocCrate<'a> {
+    fn new(doc: &'a Value) -> Result<Self> {
+        let index = doc
+            .get("index")
+            .and_then(Value::as_object)
+            .ok_or_else(|| AiDocsError::RustdocJson("missing `index` object".to_string()))?;
+        let root_id = doc
+            .get("root")
+            .map(id_key)
+            .ok_or_else(|| AiDocsError::RustdocJson("missing `root` id".to_string()))?;
+        let format_version = doc
+            .get("format_version")
+            .and_then(Value::as_u64)
+            .unwrap_or_default();
+
+        Ok(Self {
+            root_id,
+            index,
+            format_version,
+            feature_re: Regex::new(
+                r#"feature\s*=\s*"([^"]+)"|name:\s*"feature",\s*value:\s*Some\("([^"]+)"\)"#,
+            )
+            .expect("valid feature gate regex"),
+            used_paths: Vec::new(),
+        })
+    }
+
+    fn root(&self) -> Result<&'a Value> {
+        self.index
+            .get(&self.root_id)
+            .ok_or_else(|| AiDocsError::RustdocJson("root item is not in the index".to_string()))
+    }
+
+    fn item(&self, id: &Value) -> Option<&'a Value> {
+        self.index.get(&id_key(id))
+    }
+
+    /// Public items declared directly in `module`.
+    fn module_items(&self, module: &Value) -> Vec<&'a Value> {
+        module
+            .pointer("/inner/module/items")
+            .and_then(Value::as_array)
+            .map(|ids| {
+                ids.iter()
+                    .filter_map(|id| self.item(id))
+                    .filter(|i| is_public(i))
+                    .collect()
+            })
+            .unwrap_or_default()
+    }
+
+    /// Names of every public item defined in this crate, for example ranking.
+    fn public_item_names(&self) -> Vec<String> {
+        let mut names: Vec<String> = self
+            .index
+            .values()
+            .filter(|item| is_public(item) && item["crate_id"].as_u64() == Some(0))
+            .filter(|item| !matches!(item_kind(item), Some("use" | "impl")))
+            .filter_map(|item| item.get("name").and_then(Value::as_str))
+            .map(str::to_string)
+            .collect();
+        names.sort();
+        names.dedup();
+        names
+    }
+
+    fn render_module(
+        &self,
+        out: &mut String,
+        module: &'a Value,
+        path: &str,
+        is_root: bool,
+        visited: &mut HashSet<String>,
+    ) {
+        let Some(id) = module.get("id") else {
+            return;
+        };
+        if !visited.insert(id_key(id)) {
+            return;
+        }
+
+        out.push_str(&format!("## Module `{path}`\n\n"));
+        if !is_root {
+            self.push_item_notes(out, module);
+            if let Some(docs) = docs_of(module) {
+                out.push_str(&demote_headings(docs, 2));
+                out.push_str("\n\n");
+            }
+        }
+
+        let mut items = self.module_items(module);
+        // Items the project uses first; the sort is stable, so the rest keep their order.
+        let relative = path.split_once("::").map_or("", |(_, rest)| rest);
+        items.sort_by_key(|item| {
+            let name = item.get("name").and_then(Value::as_str).unwrap_or("_");
+            let item_path = if relative.is_empty() {
+                name.to_string()
+            } else {
+                format!("{relative}::{name}")
+            };
+            !covers(&self.used_paths, &item_path)
+        });
+
+        let mut rendered_sections = HashSet::new();
+        for (kind, title) in SECTIONS {
+            let of_kind: Vec<&Value> = items
+                .iter()
+                .copied()
+                .filter(|i| item_kind(i) == Some(kind))
+                .collect();
+            if of_kind.is_empty() {
+                continue;
+            }
+            if rendered_sections.insert(*title) {
+                out.push_str(&format!("### {title}\n\n"));
+            }
+            for item in of_kind {
+                self.render_item(out, item, kind, path);
+            }
+            if matches!(*kind, "use" | "module") {
+                out.push('\n');
+            }
+        }
+
+        for item in items.iter().filter(|i| item_kind(i) == Some("module")) {
+            let name = item.get("name").and_then(Value::as_str).unwrap_or("_");
+            self.render_module(out, item, &format!("{path}::{name}"), false, visited);
+        }
+    }
+
+    fn collect_surface(
+        &self,
+        out: &mut Vec<ApiItem>,
+        module: &'a Value,
+        path: &str,
+        visited: &mut HashSet<String>,
+    ) {
+        let Some(id) = module.get("id") else {
+            return;
+        };
+        if !visited.insert(id_key(id)) {
+            return;
+        }
+
+        for item in self.module_items(module) {
+            let Some(kind) = item_kind(item) else {
+                continue;
+            };
+            let name = item.get("name").and_then(Value::as_str).unwrap_or("_");
+            let inner = &item["inner"][kind];
+            let signature = match kind {
+                "use" => render_use(inner),
+                "module" => format!("pub mod {name};"),
+                _ => self.signature(name, inner, kind),
+            };
+            if signature.is_empty() {
+                continue;
+            }
+            // Glob re-exports have no name; their source tells them apart.
+            let item_path = if signature.ends_with("::*;") {
+                format!("{path}::{}::*", inner["source"].as_str().unwrap_or("_"))
+            } else {
+                format!("{path}::{name}")
+            };
+            out.push(ApiItem {
+                path: item_path.clone(),
+                kind: kind.to_string(),
+                signature,
+                deprecated: is_deprecated(item),
+            });
+
+            match kind {
+                "module" => self.collect_surface(out, item, &item_path, visited),
+                "struct" | "union" | "enum" => {
+                    for method in self.inherent_methods(&inner["impls"]) {
+                        let method_name = method.get("name").and_then(Value::as_str).unwrap_or("_");
+                        out.push(ApiItem {
+                            path: format!("{item_path}::{method_name}"),
+                            kind: "method".to_string(),
+                            signature: format!(
+                                "pub {}",
+                                render_fn(method_name, &method["inner"]["function"])
+                            ),
+                            deprecated: is_deprecated(method),
+                        });
+                    }
+                }
+                _ => {}
+            }
+        }
+    }
+
+    fn render_item(&self, out: &mut String, item: &Value, kind: &str, module_path: &str) {
+        let name = item.get("name").and_then(Value::as_str).unwrap_or("_");
+        let inner = &item["inner"][kind];
+
+        match kind {
+            "use" => {
+                out.push_str(&format!("- `{}`\n", render_use(inner)));
+                return;
+            }
+            "module" => {
+                let summary = docs_of(item).map(summary_line).unwrap_or_default();
+                let line = format!("- `{module_path}::{name}`");
+                out.push_str(&append_summary(&line, &summary));
+                out.push('\n');
+                return;
+            }
+            _ => {}
+        }
+
+        out.push_str(&format!("#### `{name}`\n\n"));
+        let signature = self.signature(name, inner, kind);
+        out.push_str(&format!("```rust\n{signature}\n```\n\n"));
+
+        self.push_item_notes(out, item);
+        if let Some(docs) = docs_of(item) {
+            out.push_str(&demote_headings(docs, 4));
+            out.push_str("\n\n");
+        }
+
+        match kind {
+            "struct" | "union" => {
+                self.push_field_docs(out, inner);
+                self.push_impls(out, &inner["impls"]);
+            }
+            "enum" => {
+                self.push_variant_docs(out, inner);
+                self.push_impls(out, &inner["impls"]);
+            }
+            "trait" => self.push_implementors(out, &inner["implementations"]),
+            _ => {}
+        }
+    }
+
+    /// Rendered declaration of a non-`use`, non-`module` item.
+    fn signature(&self, name: &str, inner: &Value, kind: &str) -> String {
+        match kind {
+            "struct" | "union" => self.render_struct(name, inner, kind),
+            "enum" => self.render_enum(name, inner),
+            "trait" => self.render_trait(name, inner),
+            "function" => format!("pub {}", render_fn(name, inner)),
+            "type_alias" => format!(
+                "pub type {name}{} = {};",
+                render_generic_params(&inner["generics"]),
+                render_type(&inner["type"])
+            ),
+            "constant" => {
+                let value = inner
+                    .pointer("/const/value")
+                    .and_then(Value::as_str)
+                    .or_else(|| inner.pointer("/const/expr").and_then(Value::as_str))
+                    .unwrap_or("_");
+                format!(
+                    "pub const {name}: {} = {value};",
+                    render_type(&inner["type"])
+                )
+            }
+            "static" => format!(
+                "pub static {}{name}: {};",
+                if inner["is_mutable"].as_bool() == Some(true) {
+                    "mut "
+                } else {
+                    ""
+                },
+                render_type(&inner["type"])
+            ),
+            "macro" => inner.as_str().unwrap_or_default().to_string(),
+            "proc_macro" => {
+                let macro_kind = inner["kind"].as_str().unwrap_or("bang");
+                match macro_kind {
+                    "derive" => format!("#[derive({name})]"),
+                    "attr" => format!("#[{name}]"),
+                    _ => format!("{name}!(...)"),
+                }
+            }
+            _ => String::new(),
+        }
+    }
+
+    fn render_struct(&self, name: &str, inner: &Value, keyword: &str) -> String {
+        let generics = render_generic_params(&inner["generics"]);
+        let where_clause = render_where(&inner["generics"]);
+
+        if let Some(fields) = inner.pointer("/kind/tuple").and_then(Value::as_array) {
+            let rendered: Vec<String> = fields
+                .iter()
+                .map(|id| match self.item(id) {
+                    Some(field) if is_public(field) => {
+                        format!("pub {}", render_type(&field["inner"]["struct_field"]))
+                    }
+                    _ => "_".to_string(),
+                })
+                .collect();
+            return format!(
+                "pub {keyword} {name}{generics}({}){where_clause};",
+                rendered.join(", ")
+            );
+        }
+
+        if inner["kind"].as_str() == Some("unit") {
+            return format!("pub {keyword} {name}{generics}{where_clause};");
+        }
+
+        let plain = inner.pointer("/kind/plain").unwrap_or(inner);
+        let mut body = String::new();
+        for field in self.public_fields(plain) {
+            let field_name = field.get("name").and_then(Value::as_str).unwrap_or("_");
+            body.push_str(&format!(
+                "    pub {field_name}: {},\n",
+                render_type(&field["inner"]["struct_field"])
+            ));
+        }
+        let stripped = plain["has_stripped_fields"].as_bool() == Some(true)
+            || plain["fields_stripped"].as_bool() == Some(true);
+        if stripped {
+            body.push_str("    /* private fields */\n");
+        }
+        if body.is_empty() {
+            format!("pub {keyword} {name}{generics}{where_clause} {{}}")
+        } else {
+            format!("pub {keyword} {name}{generics}{where_clause} {{\n{body}}}")
+        }
+    }
+
+    fn public_fields(&self, plain: &Value) -> Vec<&'a Value> {
+        plain["fields"]
+            .as_array()
+            .map(|ids| {
+                ids.iter()
+                    .filter_map(|id| self.item(id))
+                    .filter(|f| is_public(f))
+                    .collect()
+            })
+            .unwrap_or_default()
+    }
+
+    fn render_enum(&self, name: &str, inner: &Value) -> String {
+        let generics = render_generic_params(&inner["generics"]);
+        let where_clause = render_where(&inner["generics"]);
+        let mut body = String::new();
+        for variant in self.variants(inner) {
+            body.push_str(&format!("    {},\n", self.render_variant(variant)));
+        }
+        if inner["has_stripped_variants"].as_bool() == Some(true) {
+            body.push_str("    // some variants omitted\n");
+        }
+        format!("pub enum {name}{generics}{where_clause} {{\n{body}}}")
+    }
+
+    fn variants(&self, inner: &Value) -> Vec<&'a Value> {
+        inner["variants"]
+            .as_array()
+            .map(|ids| ids.iter().filter_map(|id| self.item(id)).collect())
+            .unwrap_or_default()
+    }
+
+    fn render_variant(&self, variant: &Value) -> String {
+        let name = variant.get("name").and_then(Value::as_str).unwrap_or("_");
+        let inner = &variant["inner"]["variant"];
+        let kind = &inner["kind"];
+        let mut out = name.to_string();
+
+        if let Some(fields) = kind.get("tuple").and_then(Value::as_array) {
+            let types: Vec<String> = fields
+                .iter()
+                .map(|id| {
+                    self.item(id)
+                        .map(|f| render_type(&f["inner"]["struct_field"]))
+                        .unwrap_or_else(|| "_".to_string())
+                })
+                .collect();
+            out.push_str(&format!("({})", types.join(", ")));
+        } else if let Some(fields) = kind.pointer("/struct/fields").and_then(Value::as_array) {
+            let named: Vec<String> = fields
+                .iter()
+                .filter_map(|id| self.item(id))
+                .map(|f| {
+                    format!(
+                        "{}: {}",
+                        f.get("name").and_then(Value::as_str).unwrap_or("_"),
+                        render_type(&f["inner"]["struct_field"])
+                    )
+                })
+                .collect();
+            out.push_str(&format!(" {{ {} }}", named.join(", ")));
+        }
+
+        if let Some(discriminant) = inner.pointer("/discriminant/expr").and_then(Value::as_str) {
+            out.push_str(&format!(" = {discriminant}"));
+        }
+        out
+    }
+
+    fn render_trait(&self, name: &str, inner: &Value) -> String {
+        let mut header = String::from("pub ");
+        if inner["is_unsafe"].as_bool() == Some(true) {
+            header.push_str("unsafe ");
+        }
+        if inner["is_auto"].as_bool() == Some(true) {
+            header.push_str("auto ");
+        }
+        header.push_str(&format!(
+            "trait {name}{}",
+            render_generic_params(&inner["generics"])
+        ));
+        let bounds = render_bounds(&inner["bounds"]);
+        if !bounds.is_empty() {
+            header.push_str(&format!(": {bounds}"));
+        }
+        header.push_str(&render_where(&inner["generics"]));
+
+        let mut body = String::new();
+        for item in inner["items"]
+            .as_array()
+            .into_iter()
+            .flatten()
+            .filter_map(|id| self.item(id))
+        {
+            let item_name = item.get("name").and_then(Value::as_str).unwrap_or("_");
+            let line = match item_kind(item) {
+                Some("function") => {
+                    let f = &item["inner"]["function"];
+                    let has_body = f["has_body"].as_bool().unwrap_or(false);
+                    format!(
+                        "{}{}",
+                        render_fn(item_name, f),
+                        if has_body { " { ... }" } else { ";" }
+                    )
+                }
+                Some("assoc_type") => {
+                    let t = &item["inner"]["assoc_type"];
+                    let bounds = render_bounds(&t["bounds"]);
+                    let mut line =
+                        format!("type {item_name}{}", render_generic_params(&t["generics"]));
+                    if !bounds.is_empty() {
+                        line.push_str(&format!(": {bounds}"));
+                    }
+                    if !t["type"].is_null() {
+                        line.push_str(&format!(" = {}", render_type(&t["type"])));
+                    }
+                    line.push(';');
+                    line
+                }
+                Some("assoc_const") => {
+                    let c = &item["inner"]["assoc_const"];
+                    let mut line = format!("const {item_name}: {}", render_type(&c["type"]));
+                    if let Some(value) = c["value"].as_str() {
+                        line.push_str(&format!(" = {value}"));
+                    }
+                    line.push(';');
+                    line
+                }
+                _ => continue,
+            };
+            body.push_str(&format!("    {line}\n"));
+        }
+
+        if body.is_empty() {
+            format!("{header} {{}}")
+        } else {
+            format!("{header} {{\n{body}}}")
+        }
+    }
+
+    fn push_item_notes(&self, out: &mut String, item: &Value) {
+        if let Some(deprecation) = item.get("deprecation").filter(|d| !d.is_null()) {
+            let mut line = String::from("> **Deprecated**");
+            if let Some(since) = deprecation["since"].as_str() {
+                line.push_str(&format!(" since `{since}`"));
+            }
+            if let Some(note) = deprecation["note"].as_str() {
+                line.push_str(&format!(": {note}"));
+            }
+            out.push_str(&line);
+            out.push_str("\n\n");
+        }
+
+        let features = self.feature_gates(item);
+        if !features.is_empty() {
+            let list: Vec<String> = features.iter().map(|f| format!("`{f}`")).collect();
+            out.push_str(&format!(
+                "> Available on crate feature{} {} only.\n\n",
+                if list.len() > 1 { "s" } else { "" },
+                list.join(", ")
+            ));
+        }
+    }
+
+    fn feature_gates(&self, item: &Value) -> Vec<String> {
+        let mut features = Vec::new();
+        for attr in item["attrs"].as_array().into_iter().flatten() {
+            let text = match attr {
+                Value::String(s) => s.clone(),
+                other => other.to_string().replace("\\\"", "\""),
+            };
+            for cap in self.feature_re.captures_iter(&text) {
+                if let Some(feature) = cap.get(1).or_else(|| cap.get(2)) {
+                    let feature = feature.as_str().to_string();
+                    if !features.contains(&feature) {
+                        features.push(feature);
+                    }
+                }
+            }
+        }
+        features
+    }
+
+    fn push_field_docs(&self, out: &mut String, inner: &Value) {
+        let plain = inner.pointer("/kind/plain").unwrap_or(inner);
+        let lines: Vec<String> = self
+            .public_fields(plain)
+            .into_iter()
+            .filter_map(|field| {
+                let summary = summary_line(docs_of(field)?);
+                let name = field.get("name").and_then(Value::as_str)?;
+                Some(format!("- `{name}` — {summary}"))
+            })
+            .collect();
+        if !lines.is_empty() {
+            out.push_str(&format!("**Fields**\n\n{}\n\n", lines.join("\n")));
+        }
+    }
+
+    fn push_variant_docs(&self, out: &mut String, inner: &Value) {
+        let lines: Vec<String> = self
+            .variants(inner)
+            .into_iter()
+            .filter_map(|variant| {
+                let summary = summary_line(docs_of(variant)?);
+                let name = variant.get("name").and_then(Value::as_str)?;
+                Some(format!("- `{name}` — {summary}"))
+            })
+            .collect();
+        if !lines.is_empty() {
+            out.push_str(&format!("**Variants**\n\n{}\n\n", lines.join("\n")));
+        }
+    }
+
+    /// Impl blocks written for a type, without auto-trait and blanket impls.
+    fn written_impls(&self, impl_ids: &Value) -> Vec<&'a Value> {
+        impl_ids
+            .as_array()
+            .into_iter()
+            .flatten()
+            .filter_map(|id| self.item(id))
+            .map(|imp| &imp["inner"]["impl"])
+            .filter(|inner| {
+                inner["is_synthetic"].as_bool() != Some(true) && inner["blanket_impl"].is_null()
+            })
+            .collect()
+    }
+
+    /// Public methods from the inherent impls of a type.
+    fn inherent_methods(&self, impl_ids: &Value) -> Vec<&'a Value> {
+        self.written_impls(impl_ids)
+            .into_iter()
+            .filter(|inner| inner["trait"].is_null())
+            .flat_map(|inner| inner["items"].as_array().into_iter().flatten())
+            .filter_map(|id| self.item(id))
+            .filter(|m| is_public(m) && item_kind(m) == Some("function"))
+            .collect()
+    }
+
+    fn push_impls(&self, out: &mut String, impl_ids: &Value) {
+        let mut methods = Vec::new();
+        for method in self.inherent_methods(impl_ids) {
+            let name = method.get("name").and_then(Value::as_str).unwrap_or("_");
+            let mut line = format!("- `pub {}`", render_fn(name, &method["inner"]["function"]));
+            if is_deprecated(method) {
+                line.push_str(" *(deprecated)*");
+            }
+            let summary = docs_of(method).map(summary_line).unwrap_or_default();
+            methods.push(append_summary(&line, &summary));
+        }
+
+        let mut traits = Vec::new();
+        for inner in self.written_impls(impl_ids) {
+            if inner["trait"].is_null() {
+                continue;
+            }
+            let negative = if inner["is_negative"].as_bool() == Some(true) {
+                "!"
+            } else {
+                ""
+            };
+            let name = format!("{negative}{}", render_path(&inner["trait"]));
+            if !traits.contains(&name) {
+                traits.push(name);
+            }
+        }
+
+        if !methods.is_empty() {
+            out.push_str(&format!("**Methods**\n\n{}\n\n", methods.join("\n")));
+        }
+        if !traits.is_empty() {
+            let list: Vec<String> = traits.iter().map(|t| format!("`{t}`")).collect();
+            out.push_str(&format!(
+                "**Trait implementations:** {}\n\n",
+                list.join(", ")
+            ));
+        }
+    }
+
+    fn push_implementors(&self, out: &mut String, impl_ids: &Value) {
+        let implementors: Vec<String> = impl_ids
+            .as_array()
+            .into_iter()
+            .flatten()
+            .filter_map(|id| self.item(id))
+            .map(|imp| format!("`{}`", render_type(&imp["inner"]["impl"]["for"])))
+            .collect();
+        if !implementors.is_empty() {
+            out.push_str(&format!(
+                "**Implementors:** {}\n\n",
+                implementors.join(", ")
+            ));
+        }
+    }
+}
+
+/// Index keys are strings; ids are integers in current formats and strings in older ones.
+fn id_key(id: &Value) -> String {
+    match id {
+        Value::String(s) => s.clone(),
+        other => other.to_string(),
+    }
+}
+
+fn item_kind(item: &Value) -> Option<&str> {
+    let kind = item.get("inner")?.as_object()?.keys().next()?.as_str();
+    // Older formats call re-exports `import` and aliases `typedef`.
+    Some(match kind {
+        "import" => "use",
+        "typedef" => "type_alias",
+        other => other,
+    })
+}
+
+fn is_public(item: &Value) -> bool {
+    item.get("visibility").and_then(Value::as_str) == Some("public")
+}
+
+fn is_deprecated(item: &Value) -> bool {
+    item.get("deprecation").is_some_and(|d| !d.is_null())
+}
+
+fn docs_of(item: &Value) -> Option<&str> {
+    item.get("docs")
+        .and_then(Value::as_str)
+        .map(str::trim)
+        .filter(|d| !d.is_empty())
+}
+
+/// First paragraph of a doc comment, joined into one line.
+fn summary_line(docs: &str) -> String {
+    docs.split("\n\n")
+        .next()
+        .unwrap_or_default()
+        .lines()
+        .map(str::trim)
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+fn append_summary(line: &str, summary: &str) -> String {
+    if summary.is_empty() {
+        line.to_string()
+    } else {
+        format!("{line} — {summary}")
+    }
+}
+
+/// Push doc-comment headings below the surrounding outline, leaving code fences untouched.
+fn demote_headings(docs: &str, by: usize) -> String {
+    let mut in_fence = false;
+    docs.lines()
+        .map(|line| {
+            let trimmed = line.trim_start();
+            if trimmed.starts_with("```") || trimmed.starts_with("~~~") {
+                in_fence = !in_fence;
+                return line.to_string();
+            }
+            if in_fence {
+                return line.to_string();
+            }
+            let hashes = line.chars().take_while(|c| *c == '#').count();
+            if hashes > 0 && line[hashes..].starts_with(' ') {
+                let level = (hashes + by).min(6);
+                format!("{}{}", "#".repeat(level), &line[hashes..])
+            } else {
+                line.to_string()
+            }
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+fn render_use(inner: &Value) -> String {
+    let source = inner["source"].as_str().unwrap_or("_");
+    let name = inner["name"].as_str().unwrap_or_default();
+    if inner["is_glob"].as_bool() == Some(true) || inner["glob"].as_bool() == Some(true) {
+        return format!("pub use {source}::*;");
+    }
+    if source.rsplit("::").next() == Some(name) || name.is_empty() {
+        format!("pub use {source};")
+    } else {
+        format!("pub use {source} as {name};")
+    }
+}
+
+fn render_fn(name: &str, inner: &Value) -> String {
+    let header = &inner["header"];
+    let flag = |new: &str, old: &str| {
+        header[new].as_bool().or_else(|| header[old].as_bool()) == Some(true)
+    };
+
+    let mut out = String::new();
+    if flag("is_const", "const") {
+        out.push_str("const ");
+    }
+    if flag("is_async", "async") {
+        out.push_str("async ");
+    }
+    if flag("is_unsafe", "unsafe") {
+        out.push_str("unsafe ");
+    }
+    match &header["abi"] {
+        Value::String(abi) if abi == "Rust" => {}
+        Value::String(abi) => out.push_str(&format!("extern \"{abi}\" ")),
+        Value::Object(abi) => {
+            if let Some(key) = abi.keys().next() {
+                out.push_str(&format!("extern \"{key}\" "));
+            }
+        }
+        _ => {}
+    }
+
+    let sig = inner
+        .get("sig")
+        .or_else(|| inner.get("decl"))
+        .unwrap_or(&Value::Null);
+    let inputs: Vec<String> = sig["inputs"]
+        .as_array()
+        .into_iter()
+        .flatten()
+        .map(|input| {
+            let arg = input[0].as_str().unwrap_or("_");
+            let ty = &input[1];
+            if arg == "self" {
+                render_self_param(ty)
+            } else {
+                format!("{arg}: {}", render_type(ty))
+            }
+        })
+        .collect();
+
+    out.push_str(&format!(
+        "fn {name}{}({})",
+        render_generic_params(&inner["generics"]),
+        inputs.join(", ")
+    ));
+    if !sig["output"].is_null() {
+        out.push_str(&format!(" -> {}", render_type(&sig["output"])));
+    }
+    out.push_str(&render_where(&inner["generics"]));
+    out
+}
+
+fn render_self_param(ty: &Value) -> String {
+    if ty.get("generic").and_then(Value::as_str) == Some("Self") {
+        return "self".to_string();
+    }
+    if let Some(reference) = ty.get("borrowed_ref") {
+        if reference["type"].get("generic").and_then(Value::as_str) == Some("Self") {
+            let lifetime = reference["lifetime"]
+                .as_str()
+                .map(|l| format!("{l} "))
+                .unwrap_or_default();
+            let mutability = if reference["is_mutable"].as_bool() == Some(true) {
+                "mut "
+            } else {
+                ""
+            };
+            return format!("&{lifetime}{mutability}self");
+        }
+    }
+    format!("self: {}", render_type(ty))
+}
+
+fn render_generic_params(generics: &Value) -> String {
+    let params: Vec<String> = generics["params"]
+        .as_array()
+        .into_iter()
+        .flatten()
+        .filter_map(|param| {
+            let name = param["name"].as_str()?;
+            let kind = &param["kind"];
+            if let Some(lifetime) = kind.get("lifetime") {
+                let outlives: Vec<&str> = lifetime["outlives"]
+                    .as_array()
+                    .into_iter()
+                    .flatten()
+                    .filter_map(Value::as_str)
+                    .collect();
+                return Some(if outlives.is_empty() {
+                    name.to_string()
+                } else {
+                    format!("{name}: {}", outlives.join(" + "))
+                });
+            }
+            if let Some(ty) = kind.get("type") {
+                if ty["is_synthetic"].as_bool() == Some(true)
+                    || ty["synthetic"].as_bool() == Some(true)
+                {
+                    return None;
+                }
+                let mut out = name.to_string();
+                let bounds = render_bounds(&ty["bounds"]);
+                if !bounds.is_empty() {
+                    out.push_str(&format!(": {bounds}"));
+                }
+                if !ty["default"].is_null() {
+                    out.push_str(&format!(" = {}", render_type(&ty["default"])));
+                }
+                return Some(out);
+            }
+            if let Some(constant) = kind.get("const") {
+                return Some(format!("const {name}: {}", render_type(&constant["type"])));
+            }
+            None
+        })
+        .collect();
+
+    if params.is_empty() {
+        String::new()
+    } else {
+        format!("<{}>", params.join(", "))
+    }
+}
+
+fn render_where(generics: &Value) -> String {
+    let predicates: Vec<String> = generics["where_predicates"]
+        .as_array()
+        .into_iter()
+        .flatten()
+        .filter_map(|predicate| {
+            if let Some(bound) = predicate.get("bound_predicate") {
+                return Some(format!(
+                    "{}{}: {}",
+                    render_hrtb(&bound["generic_params"]),
+                    render_type(&bound["type"]),
+                    render_bounds(&bound["bounds"])
+                ));
+            }
+            if let Some(lifetime) = predicate.get("lifetime_predicate") {
+                let outlives: Vec<&str> = lifetime["outlives"]
+                    .as_array()
+                    .into_iter()
+                    .flatten()
+                    .filter_map(Value::as_str)
+                    .collect();
+                return Some(format!(
+                    "{}: {}",
+                    lifetime["lifetime"].as_str().unwrap_or("'_"),
+                    outlives.join(" + ")
+                ));
+            }
+            if let Some(eq) = predicate.get("eq_predicate") {
+                return Some(format!(
+                    "{} = {}",
+                    render_type(&eq["lhs"]),
+                    render_term(&eq["rhs"])
+                ));
+            }
+            None
+        })
+        .collect();
+
+    if predicates.is_empty() {
+        String::new()
+    } else {
+        format!("\nwhere\n    {},", predicates.join(",\n    "))
+    }
+}
+
+fn render_hrtb(params: &Value) -> String {
+    let names: Vec<&str> = params
+        .as_array()
+        .into_iter()
+        .flatten()
+        .filter_map(|p| p["name"].as_str())
+        .collect();
+    if names.is_empty() {
+        String::new()
+    } else {
+        format!("for<{}> ", names.join(", "))
+    }
+}
+
+fn render_bounds(bounds: &Value) -> String {
+    bounds
+        .as_array()
+        .into_iter()
+        .flatten()
+        .map(render_bound)
+        .collect::<Vec<_>>()
+        .join(" + ")
+}
+
+fn render_bound(bound: &Value) -> String {
+    if let Some(trait_bound) = bound.get("trait_bound") {
+        let modifier = match trait_bound["modifier"].as_str() {
+            Some("maybe") => "?",
+            Some("maybe_const") => "~const ",
+            _ => "",
+        };
+        return format!(
+            "{}{modifier}{}",
+            render_hrtb(&trait_bound["generic_params"]),
+            render_path(&trait_bound["trait"])
+        );
+    }
+    if let Some(lifetime) = bound.get("outlives").and_then(Value::as_str) {
+        return lifetime.to_string();
+    }
+    if let Some(args) = bound.get("use").and_then(Value::as_array) {
+        let names: Vec<String> = args
+            .iter()
+            .map(|a| match a {
+                Value::String(s) => s.clone(),
+                other => other
+                    .as_object()
+                    .and_then(|o| o.values().next())
+                    .and_then(Value::as_str)
+                    .unwrap_or("_")
+                    .to_string(),
+            })
+            .collect();
+        return format!("use<{}>", names.join(", "));
+    }
+    "_".to_string()
+}
+
+fn render_path(path: &Value) -> String {
+    let name = path
+        .get("path")
+        .or_else(|| path.get("name"))
+        .and_then(Value::as_str)
+        .unwrap_or("_");
+    let name = name.strip_prefix("$crate::").unwrap_or(name);
+    format!("{name}{}", render_generic_args(&path["args"]))
+}
+
+fn render_generic_args(args: &Value) -> String {
+    if let Some(angle) = args.get("angle_bracketed") {
+        let mut parts: Vec<String> = angle["args"]
+            .as_array()
+            .into_iter()
+            .flatten()
+            .map(|arg| {
+                if let Some(ty) = arg.get("type") {
+                    render_type(ty)
+                } else if let Some(lifetime) = arg.get("lifetime").and_then(Value::as_str) {
+                    lifetime.to_string()
+                } else if let Some(constant) = arg.get("const") {
+                    constant["expr"]
+                        .as_str()
+                        .or_else(|| constant["value"].as_str())
+                        .unwrap_or("_")
+                        .to_string()
+                } else {
+                    "_".to_string()
+                }
+            })
+            .collect();
+
+        let constraints = angle
+            .get("constraints")
+            .or_else(|| angle.get("bindings"))
+            .and_then(Value::as_array);
+        for constraint in constraints.into_iter().flatten() {
+            let name = constraint["name"].as_str().unwrap_or("_");
+            let binding = &constraint["binding"];
+            if let Some(term) = binding.get("equality") {
+                parts.push(format!("{name} = {}", render_term(term)));
+            } else if let Some(bounds) = binding.get("constraint") {
+                parts.push(format!("{name}: {}", render_bounds(bounds)));
+            }
+        }
+
+        return if parts.is_empty() {
+            String::new()
+        } else {
+            format!("<{}>", parts.join(", "))
+        };
+    }
+
+    if let Some(paren) = args.get("parenthesized") {
+        let inputs: Vec<String> = paren["inputs"]
+            .as_array()
+            .into_iter()
+            .flatten()
+            .map(render_type)
+            .collect();
+        let mut out = format!("({})", inputs.join(", "));
+        if !paren["output"].is_null() {
+            out.push_str(&format!(" -> {}", render_type(&paren["output"])));
+        }
+        return out;
+    }
+
+    String::new()
+}
+
+fn render_term(term: &Value) -> String {
+    if let Some(ty) = term.get("type") {
+        return render_type(ty);
+    }
+    term.pointer("/constant/expr")
+        .and_then(Value::as_str)
+        .unwrap_or("_")
+        .to_string()
+}
+
+fn render_type(ty: &Value) -> String {
+    if let Some(s) = ty.get("primitive").and_then(Value::as_str) {
+        return s.to_string();
+    }
+    if let Some(s) = ty.get("generic").and_then(Value::as_str) {
+        return s.to_string();
+    }
+    if let Some(path) = ty.get("resolved_path") {
+        return render_path(path);
+    }
+    if let Some(reference) = ty.get("borrowed_ref") {
+        let lifetime = reference["lifetime"]
+            .as_str()
+            .map(|l| format!("{l} "))
+            .unwrap_or_default();
+        let mutability = if reference["is_mutable"].as_bool() == Some(true)
+            || reference["mutable"].as_bool() == Some(true)
+        {
+            "mut "
+        } else {
+            ""
+        };
+        return format!("&{lifetime}{mutability}{}", render_type(&reference["type"]));
+    }
+    if let Some(pointer) = ty.get("raw_pointer") {
+        let mutability = if pointer["is_mutable"].as_bool() == Some(true)
+            || pointer["mutable"].as_bool() == Some(true)
+        {
+            "mut"
+        } else {
+            "const"
+        };
+        return format!("*{mutability} {}", render_type(&pointer["type"]));
+    }
+    if let Some(items) = ty.get("tuple").and_then(Value::as_array) {
+        let parts: Vec<String> = items.iter().map(render_type).collect();
+        return if parts.len() == 1 {
+            format!("({},)", parts[0])
+        } else {
+            format!("({})", parts.join(", "))
+        };
+    }
+    if let Some(inner) = ty.get("slice") {
+        return format!("[{}]", render_type(inner));
+    }
+    if let Some(array) = ty.get("array") {
+        return format!(
+            "[{}; {}]",
+            render_type(&array["type"]),
+            array["len"].as_str().unwrap_or("_")
+        );
+    }
+    if let Some(pat) = ty.get("pat") {
+        return render_type(&pat["type"]);
+    }
+    if let Some(bounds) = ty.get("impl_trait") {
+        return format!("impl {}", render_bounds(bounds));
+    }
+    if let Some(dyn_trait) = ty.get("dyn_trait") {
+        let mut parts: Vec<String> = dyn_trait["traits"]
+            .as_array()
+            .into_iter()
+            .flatten()
+            .map(|poly| {
+                format!(
+                    "{}{}",
+                    render_hrtb(&poly["generic_params"]),
+                    render_path(&poly["trait"])
+                )
+            })
+            .collect();
+        if let Some(lifetime) = dyn_trait["lifetime"].as_str() {
+            parts.push(lifetime.to_string());
+        }
+        return format!("dyn {}", parts.join(" + "));
+    }
+    if let Some(qualified) = ty.get("qualified_path") {
+        let name = qualified["name"].as_str().unwrap_or("_");
+        let self_type = render_type(&qualified["self_type"]);
+        let args = render_generic_args(&qualified["args"]);
+        return if qualified["trait"].is_null() {
+            format!("{self_type}::{name}{args}")
+        } else {
+            format!(
+                "<{self_type} as {}>::{name}{args}",
+                render_path(&qualified["trait"])
+            )
+        };
+    }
+    if let Some(pointer) = ty.get("function_pointer") {
+        let sig = pointer
+            .get("sig")
+            .or_else(|| pointer.get("decl"))
+            .unwrap_or(&Value::Null);
+        let inputs: Vec<String> = sig["inputs"]
+            .as_array()
+            .into_iter()
+            .flatten()
+            .map(|input| render_type(&input[1]))
+            .collect();
+        let mut out = format!(
+            "{}fn({})",
+            render_hrtb(&pointer["generic_params"]),
+            inputs.join(", ")
+        );
+        if !sig["output"].is_null() {
+            out.push_str(&format!(" -> {}", render_type(&sig["output"])));
+        }
+        return out;
+    }
+    if ty.as_str() == Some("infer") {
+        return "_".to_string();
+    }
+    "_".to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{api_surface, demote_headings, render_api_markdown, render_type};
+    use serde_json::json;
+
+    fn fixture() -> serde_json::Value {
+        serde_json::from_str(include_str!(
+            "../../tests/fixtures/rustdoc/demo_fix-0.3.1.json"
+        ))
+        .expect("fixture json")
+    }
+
+    #[test]
+    fn snapshot_demo_crate_api() {
+        let md = render_api_markdown("demo_fix", "0.3.1", &fixture(), &[], 20 * 1024, &[])
+            .expect("render");
+        let path = std::path::Path::new(env!("CARGO_MANIFEST_DIR"))
+            .join("tests/fixtures/rustdoc/demo_fix-0.3.1.API.md");
+        if std::env::var_os("AIFD_UPDATE_SNAPSHOTS").is_some() {
+            std::fs::write(&path, &md).expect("write snapshot");
+            return;
+        }
+        let expected = std::fs::read_to_string(&path).expect("read snapshot");
+        assert_eq!(md, expected);
+    }
+
+    #[test]
+    fn renders_signatures_generics_and_where_clauses() {
+        let md = render_api_markdown("demo_fix", "0.3.1", &fixture(), &[], 20 * 1024, &[])
+            .expect("render");
+        assert!(md.contains("pub fn share<T: Send + Sync + 'static>(value: T) -> Shared<T>"));
+        assert!(md.contains("pub struct Shared<T: ?Sized + Send>(pub std::sync::Arc<T>);"));
+        assert!(md.contains(
+            "pub fn total<'a, I>(items: I) -> u64\nwhere\n    I: IntoIterator<Item = &'a Counter>,"
+        ));
+        assert!(md.contains("pub async fn parse(input: &str) -> Result<Counter, DemoError>"));
+        assert!(md.contains("- `pub fn bump(&mut self) -> &mut Self`"));
+    }
+
+    #[test]
+    fn renders_trait_impls_feature_gates_and_deprecations() {
+        let md = render_api_markdown("demo_fix", "0.3.1", &fixture(), &[], 20 * 1024, &[])
+            .expect("render");
+        assert!(md.contains("**Trait implementations:** "));
+        assert!(md.contains("`Display`"));
+        assert!(md.contains("`Reset`"));
+        assert!(md.contains("> Available on crate feature `extra` only."));
+        assert!(md.contains("*(deprecated)*"));
+        assert!(md.contains("pub use sync::share;"));
+    }
+
+    #[test]
+    fn collects_api_surface_with_methods_and_deprecations() {
+        let items = api_surface(&fixture()).expect("surface");
+        let find = |path: &str| items.iter().find(|i| i.path == path).expect(path);
+
+        assert_eq!(find("demo_fix::Counter").kind, "struct");
+        assert_eq!(
+            find("demo_fix::Counter::bump").signature,
+            "pub fn bump(&mut self) -> &mut Self"
+        );
+        assert!(find("demo_fix::Counter::incr").deprecated);
+        assert!(!find("demo_fix::Counter::new").deprecated);
+        assert_eq!(find("demo_fix::sync").signature, "pub mod sync;");
+        assert_eq!(find("demo_fix::sync::share").kind, "function");
+        assert_eq!(find("demo_fix::extra::double").kind, "function");
+    }
+
+    #[test]
+    fn rejects_documents_without_index() {
+        assert!(render_api_markdown("x", "1.0.0", &json!({"root": 0}), &[], 0, &[]).is_err());
+    }
+
+    #[test]
+    fn renders_used_modules_and_items_first() {
+        let used = vec!["extra::double".to_string(), "parse".to_string()];
+        let md = render_api_markdown("demo_fix", "0.3.1", &fixture(), &[], 20 * 1024, &used)
+            .expect("render");
+        let pos = |needle: &str| md.find(needle).expect(needle);
+        assert!(pos("## Module `demo_fix::extra`") < pos("## Module `demo_fix::sync`"));
+        assert!(pos("#### `parse`") < pos("#### `total`"));
+        assert!(pos("- `demo_fix::extra`") < pos("- `demo_fix::sync`"));
+    }
+
+    #[test]
+    fn renders_references_slices_and_dyn_traits() {
+        let ty = json!({"borrowed_ref": {"lifetime": "'a", "is_mutable": true, "type": {"slice": {"dyn_trait": {"traits": [{"trait": {"path": "Fn", "id": 1, "args": {"parenthesized": {"inputs": [{"primitive": "u8"}], "output": null}}}, "generic_params": []}], "lifetime": null}}}}});
+        assert_eq!(render_type(&ty), "&'a mut [dyn Fn(u8)]");
+    }
+
+    #[test]
+    fn demotes_doc_headings_outside_code_fences() {
+        let docs = "# Examples\n\n```\n# hidden line\n```\n## Notes";
+        assert_eq!(
+            demote_headings(docs, 2),
+            "### Examples\n\n```\n# hidden line\n```\n#### Notes"
+        );
+    }
+}
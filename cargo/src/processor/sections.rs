@@ -0,0 +1,136 @@
+use regex::Regex;
+
+use super::truncate::layout;
+
+/// Keep or drop Markdown sections by heading (`include_sections` /
+/// `exclude_sections`). A section runs from its heading to the next heading of
+/// the same or a higher level. Includes only apply to files where some heading
+/// matches: those keep the matching sections and the headings above them, and
+/// lose the rest. Excludes win. Returns the text and the number of sections dropped.
+pub fn filter_sections(content: &str, include: &[String], exclude: &[String]) -> (String, usize) {
+    let headings = layout(content).headings;
+    if headings.is_empty() || (include.is_empty() && exclude.is_empty()) {
+        return (content.to_string(), 0);
+    }
+    let include = compile_heading_patterns(include);
+    let exclude = compile_heading_patterns(exclude);
+    let matches = |patterns: &[Regex], text: &str| {
+        let text = text.replace('`', "");
+        patterns.iter().any(|re| re.is_match(text.trim()))
+    };
+
+    let mut parents: Vec<Option<usize>> = Vec::with_capacity(headings.len());
+    let mut stack: Vec<usize> = Vec::new();
+    for (i, heading) in headings.iter().enumerate() {
+        while stack
+            .last()
+            .is_some_and(|&open| headings[open].level >= heading.level)
+        {
+            stack.pop();
+        }
+        parents.push(stack.last().copied());
+        stack.push(i);
+    }
+
+    // Matches are inherited by subsections; parents come before their children.
+    let mut excluded = vec![false; headings.len()];
+    let mut included = vec![false; headings.len()];
+    for (i, heading) in headings.iter().enumerate() {
+        let parent = parents[i];
+        excluded[i] = parent.is_some_and(|p| excluded[p]) || matches(&exclude, &heading.text);
+        included[i] = parent.is_some_and(|p| included[p]) || matches(&include, &heading.text);
+    }
+    let include_active = included.iter().any(|&i| i);
+    let mut leads_to_included = vec![false; headings.len()];
+    for i in (0..headings.len()).rev() {
+        if included[i] && !excluded[i] {
+            let mut parent = parents[i];
+            while let Some(p) = parent {
+                leads_to_included[p] = true;
+                parent = parents[p];
+            }
+        }
+    }
+
+    let mut out = String::with_capacity(content.len());
+    if !include_active {
+        out.push_str(&content[..headings[0].start]);
+    }
+    let mut dropped = vec![false; headings.len()];
+    for (i, heading) in headings.iter().enumerate() {
+        let end = headings.get(i + 1).map_or(content.len(), |h| h.start);
+        let segment = &content[heading.start..end];
+        if excluded[i] {
+            dropped[i] = true;
+        } else if !include_active || included[i] {
+            out.push_str(segment);
+        } else if leads_to_included[i] {
+            let line = segment.split_inclusive('\n').next().unwrap_or(segment);
+            out.push_str(line.trim_end());
+            out.push_str("\n\n");
+        } else {
+            dropped[i] = true;
+        }
+    }
+
+    let removed = (0..headings.len())
+        .filter(|&i| dropped[i] && !parents[i].is_some_and(|p| dropped[p]))
+        .count();
+    if removed == 0 && !include_active {
+        return (content.to_string(), 0);
+    }
+    let mut out = out.trim_end().to_string();
+    out.push('\n');
+    (out, removed)
+}
+
+/// Case-insensitive heading patterns where `*` matches anything.
+fn compile_heading_patterns(patterns: &[String]) -> Vec<Regex> {
+    patterns
+        .iter()
+        .filter_map(|pattern| {
+            let escaped = regex::escape(pattern.trim()).replace(r"\*", ".*");
+            Regex::new(&format!("(?i)^{escaped}$")).ok()
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::filter_sections;
+
+    const README: &str = "# demo\n\nIntro.\n\n## Installation\n\nAdd it.\n\n## Usage\n\nCall it.\n\n### Async usage\n\nAwait it.\n\n### Unstable\n\nMaybe.\n\n## Examples\n\n```sh\n# not a heading\n```\n\n## Contributing\n\nPRs welcome.\n\n## License\n\nMIT\n";
+
+    fn strings(items: &[&str]) -> Vec<String> {
+        items.iter().map(|s| s.to_string()).collect()
+    }
+
+    #[test]
+    fn keeps_included_sections_under_their_parent_headings() {
+        let (out, removed) = filter_sections(
+            README,
+            &strings(&["usage", "Example*"]),
+            &strings(&["Unstable"]),
+        );
+        assert_eq!(
+            out,
+            "# demo\n\n## Usage\n\nCall it.\n\n### Async usage\n\nAwait it.\n\n## Examples\n\n```sh\n# not a heading\n```\n"
+        );
+        assert_eq!(removed, 4);
+    }
+
+    #[test]
+    fn drops_excluded_sections_and_ignores_unmatched_includes() {
+        let (out, removed) = filter_sections(
+            README,
+            &strings(&["Quick start"]),
+            &strings(&["Contributing", "License"]),
+        );
+        assert!(out.starts_with("# demo\n\nIntro.\n\n## Installation"));
+        assert!(out.ends_with("# not a heading\n```\n"));
+        assert_eq!(removed, 2);
+
+        let (out, removed) = filter_sections("No headings here.\n", &strings(&["Usage"]), &[]);
+        assert_eq!((out.as_str(), removed), ("No headings here.\n", 0));
+    }
+}
@@ -0,0 +1,287 @@
+use crate::utils::floor_char_boundary;
+
+/// Omitted sections listed after the marker; the rest are only counted.
+const MAX_OMITTED_ENTRIES: usize = 30;
+
+/// An ATX heading outside code fences.
+pub(super) struct Heading {
+    pub(super) start: usize,
+    pub(super) level: usize,
+    pub(super) text: String,
+}
+
+/// Where a cut may go without breaking Markdown structure.
+#[derive(Default)]
+pub(super) struct Layout {
+    pub(super) headings: Vec<Heading>,
+    /// Starts of blocks that follow a blank line outside code fences.
+    paragraphs: Vec<usize>,
+    /// Line starts with the opening line of the code fence open at that point, if any.
+    lines: Vec<(usize, Option<String>)>,
+}
+
+/// Cut Markdown to at most `max_bytes` (plus a closing fence) at a block boundary:
+/// the last heading or, failing that, paragraph within budget, as long as it keeps
+/// at least half of it; otherwise the last line, closing a code fence cut open.
+/// `marker` follows the kept part, then a list of the omitted sections with their sizes.
+pub fn truncate_markdown(content: &str, max_bytes: usize, marker: &str) -> String {
+    let layout = layout(content);
+    let mut budget = max_bytes.saturating_sub(marker.len() + 4);
+    let mut cut = cut_position(content, &layout, 0, budget);
+    let mut omitted = omitted_sections(content, &layout, cut);
+    // A long list of omitted sections eats into the budget; cut once more before it.
+    if cut + omitted.len() > budget {
+        budget = budget.saturating_sub(omitted.len());
+        cut = cut_position(content, &layout, 0, budget);
+        omitted = omitted_sections(content, &layout, cut);
+    }
+
+    let mut out = content[..cut].trim_end().to_string();
+    if let Some(fence) = open_fence(&layout, cut) {
+        out.push('\n');
+        out.push_str(&closing_fence(&fence));
+    }
+    out.push_str("\n\n");
+    out.push_str(marker);
+    out.push('\n');
+    if !omitted.is_empty() {
+        out.push('\n');
+        out.push_str(&omitted);
+    }
+    out
+}
+
+pub(super) fn layout(content: &str) -> Layout {
+    let mut layout = Layout::default();
+    let mut fence: Option<String> = None;
+    let mut prev_blank = false;
+    let mut offset = 0;
+    for raw in content.split_inclusive('\n') {
+        let start = offset;
+        offset += raw.len();
+        layout.lines.push((start, fence.clone()));
+
+        let trimmed = raw.trim();
+        if let Some(open) = &fence {
+            let marker = closing_fence(open);
+            let closes = trimmed.starts_with(marker.as_str())
+                && trimmed.chars().all(|c| marker.starts_with(c));
+            if closes {
+                fence = None;
+            }
+            prev_blank = false;
+            continue;
+        }
+        if fence_marker(trimmed).is_some() {
+            if prev_blank {
+                layout.paragraphs.push(start);
+            }
+            fence = Some(trimmed.to_string());
+            prev_blank = false;
+            continue;
+        }
+        if trimmed.is_empty() {
+            prev_blank = true;
+            continue;
+        }
+        if let Some(heading) = atx_heading(raw, start) {
+            layout.headings.push(heading);
+        } else if prev_blank {
+            layout.paragraphs.push(start);
+        }
+        prev_blank = false;
+    }
+    layout
+}
+
+/// Line that closes the code block opened by `opening` (e.g. `` ```rust ``).
+pub(super) fn closing_fence(opening: &str) -> String {
+    fence_marker(opening).unwrap_or_else(|| "```".to_string())
+}
+
+/// Opening fence (three or more backticks or tildes) starting a line.
+pub(super) fn fence_marker(trimmed: &str) -> Option<String> {
+    let ch = trimmed.chars().next().filter(|c| *c == '`' || *c == '~')?;
+    let len = trimmed.chars().take_while(|c| *c == ch).count();
+    (len >= 3).then(|| ch.to_string().repeat(len))
+}
+
+pub(super) fn atx_heading(raw: &str, start: usize) -> Option<Heading> {
+    let line = raw.trim_end();
+    if line.len() - line.trim_start().len() > 3 {
+        return None;
+    }
+    let line = line.trim_start();
+    let level = line.chars().take_while(|c| *c == '#').count();
+    if !(1..=6).contains(&level) {
+        return None;
+    }
+    let rest = &line[level..];
+    if !rest.is_empty() && !rest.starts_with([' ', '\t']) {
+        return None;
+    }
+    Some(Heading {
+        start,
+        level,
+        text: rest.trim().trim_end_matches('#').trim_end().to_string(),
+    })
+}
+
+/// End of a piece starting at `from` and spanning at most `budget` bytes: the last
+/// heading, else paragraph, that keeps at least half the budget, else the last line.
+/// Always past `from` unless `from` is the end of `content`.
+pub(super) fn cut_position(content: &str, layout: &Layout, from: usize, budget: usize) -> usize {
+    let end = from + budget;
+    if end >= content.len() {
+        return content.len();
+    }
+    let last_within = |starts: &[usize]| {
+        starts
+            .iter()
+            .copied()
+            .rev()
+            .find(|s| *s > from && *s <= end)
+    };
+    let heading_starts: Vec<usize> = layout.headings.iter().map(|h| h.start).collect();
+    let heading = last_within(&heading_starts);
+    let paragraph = last_within(&layout.paragraphs);
+    let min = from + budget / 2;
+    if let Some(start) = heading
+        .filter(|start| *start >= min)
+        .or(paragraph.filter(|start| *start >= min))
+    {
+        return start;
+    }
+    let line_starts: Vec<usize> = layout.lines.iter().map(|(start, _)| *start).collect();
+    if let Some(start) = last_within(&line_starts) {
+        return start;
+    }
+    let cut = floor_char_boundary(content, end);
+    if cut > from {
+        return cut;
+    }
+    content[from..]
+        .char_indices()
+        .nth(1)
+        .map_or(content.len(), |(i, _)| from + i)
+}
+
+/// Opening line of the code block a cut at `cut` falls inside.
+pub(super) fn open_fence(layout: &Layout, cut: usize) -> Option<String> {
+    layout
+        .lines
+        .iter()
+        .take_while(|(start, _)| *start <= cut)
+        .last()
+        .and_then(|(_, fence)| fence.clone())
+}
+
+/// `Omitted sections` list for everything after `cut`.
+fn omitted_sections(content: &str, layout: &Layout, cut: usize) -> String {
+    let mut entries = Vec::new();
+    let next_heading = layout
+        .headings
+        .iter()
+        .position(|h| h.start >= cut)
+        .unwrap_or(layout.headings.len());
+    let rest_end = layout
+        .headings
+        .get(next_heading)
+        .map_or(content.len(), |h| h.start);
+    if rest_end > cut && !content[cut..rest_end].trim().is_empty() {
+        entries.push(format!(
+            "- (rest of the section above) — {}",
+            format_size(rest_end - cut)
+        ));
+    }
+
+    let headings = &layout.headings[next_heading..];
+    let min_level = headings.iter().map(|h| h.level).min().unwrap_or(1);
+    for (i, heading) in headings.iter().enumerate() {
+        let end = headings.get(i + 1).map_or(content.len(), |h| h.start);
+        entries.push(format!(
+            "{}- {} — {}",
+            "  ".repeat(heading.level - min_level),
+            heading.text,
+            format_size(end - heading.start)
+        ));
+    }
+    if entries.is_empty() {
+        return String::new();
+    }
+
+    let mut out = String::from("Omitted sections:\n\n");
+    for entry in entries.iter().take(MAX_OMITTED_ENTRIES) {
+        out.push_str(entry);
+        out.push('\n');
+    }
+    if entries.len() > MAX_OMITTED_ENTRIES {
+        out.push_str(&format!(
+            "- … and {} more\n",
+            entries.len() - MAX_OMITTED_ENTRIES
+        ));
+    }
+    out
+}
+
+fn format_size(bytes: usize) -> String {
+    if bytes < 1024 {
+        format!("{bytes} B")
+    } else {
+        format!("{:.1} KB", bytes as f64 / 1024.0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::truncate_markdown;
+
+    const MARKER: &str = "[TRUNCATED by ai-fdocs at 1KB]";
+
+    #[test]
+    fn cuts_at_last_heading_and_lists_omitted_sections() {
+        let mut content = String::from("# Guide\n\nIntro paragraph.\n\n## Setup\n\n");
+        content.push_str(&"Setup text. ".repeat(40));
+        content.push_str("\n\n## Usage\n\n");
+        content.push_str(&"Usage text. ".repeat(60));
+        content.push_str("\n\n### Advanced\n\nMore.\n");
+
+        let out = truncate_markdown(&content, 1024, MARKER);
+        assert!(out.len() <= 1024);
+        assert!(out.contains("## Setup"));
+        assert!(!out.contains("## Usage"));
+        assert!(out.contains(&format!("Setup text.\n\n{MARKER}\n\nOmitted sections:\n\n")));
+        assert!(out.contains("\n- Usage — "));
+        assert!(out.contains("\n  - Advanced — 20 B\n"));
+    }
+
+    #[test]
+    fn closes_code_fence_cut_in_half() {
+        let mut content = String::from("# API\n\n```rust\n");
+        for i in 0..200 {
+            content.push_str(&format!("pub fn item_{i}() {{}}\n"));
+        }
+        content.push_str("```\n\n## Next\n\nText.\n");
+
+        let out = truncate_markdown(&content, 1024, MARKER);
+        let kept = &out[..out.find(MARKER).expect("marker")];
+        assert_eq!(kept.matches("```").count(), 2);
+        assert!(kept.trim_end().ends_with("```"));
+        assert!(out.contains("- (rest of the section above) — "));
+        assert!(out.contains("- Next — 15 B"));
+        assert_eq!(out, truncate_markdown(&content, 1024, MARKER));
+    }
+
+    #[test]
+    fn falls_back_to_paragraphs_in_one_long_section() {
+        let content = format!(
+            "# Only\n\n{}\n\n{}\n",
+            "First paragraph. ".repeat(40),
+            "Second paragraph. ".repeat(40)
+        );
+        let out = truncate_markdown(&content, 1024, MARKER);
+        assert!(out.contains("First paragraph."));
+        assert!(!out.contains("Second paragraph."));
+        assert!(out.contains("- (rest of the section above) — 721 B"));
+    }
+}
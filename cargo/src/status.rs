@@ -4,7 +4,11 @@ use std::path::{Path, PathBuf};
 
 use serde::Serialize;
 
-use crate::config::Config;
+use crate::advisory::AdvisoryDb;
+use crate::config::{Config, Settings};
+use crate::fetcher::crates_io::CratesIoMetadata;
+use crate::fetcher::latest::LatestDocsFetcher;
+use crate::fetcher::sparse_index::{upstream_versions, IndexVersion, UpstreamVersions};
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
 pub enum DocsStatus {
@@ -13,6 +17,14 @@ pub enum DocsStatus {
     Outdated,
     Missing,
     Corrupted,
+    /// Docs are synced, but the version is yanked on crates.io.
+    Yanked,
+    /// Docs are synced, but the crate is deprecated or its repository archived.
+    Deprecated,
+    /// Docs are synced, but a RustSec vulnerability affects the version.
+    Vulnerable,
+    /// Docs match the lockfile, but a newer release is published.
+    UpdateAvailable,
 }
 
 impl DocsStatus {
@@ -23,11 +35,29 @@ impl DocsStatus {
             Self::Outdated => "Outdated",
             Self::Missing => "Missing",
             Self::Corrupted => "Corrupted",
+            Self::Yanked => "Yanked",
+            Self::Deprecated => "Deprecated",
+            Self::Vulnerable => "Vulnerable",
+            Self::UpdateAvailable => "UpdateAvailable",
         }
     }
 
     fn is_problem(self) -> bool {
-        matches!(self, Self::Outdated | Self::Missing | Self::Corrupted)
+        !matches!(
+            self,
+            Self::Synced | Self::SyncedFallback | Self::UpdateAvailable
+        )
+    }
+
+    /// Whether `cargo ai-fdocs check` fails on this status under the `check_fail_on_*` policy.
+    pub fn fails_check(self, settings: &Settings) -> bool {
+        match self {
+            Self::Synced | Self::SyncedFallback | Self::UpdateAvailable => false,
+            Self::Outdated | Self::Missing | Self::Corrupted => true,
+            Self::Yanked => settings.check_fail_on_yanked,
+            Self::Deprecated => settings.check_fail_on_deprecated,
+            Self::Vulnerable => settings.check_fail_on_advisory,
+        }
     }
 }
 
@@ -58,10 +88,17 @@ pub struct CrateStatus {
     pub mode: String,
     pub source_kind: Option<String>,
     pub is_fallback: bool,
+    /// Newer upstream releases; only set when `settings.check_updates` is on.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub upstream: Option<UpstreamVersions>,
+    /// Changelog entries kept by the last sync, when the changelog was cut.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub changelog: Option<ChangelogMeta>,
 }
 
-use crate::storage::{CrateMeta, META_SCHEMA_VERSION};
+use crate::storage::{ChangelogMeta, CrateMeta, META_SCHEMA_VERSION};
 
+#[allow(clippy::too_many_arguments)]
 fn crate_status(
     crate_name: String,
     lock_version: Option<String>,
@@ -83,6 +120,8 @@ fn crate_status(
         mode: mode.as_str().to_string(),
         source_kind,
         is_fallback,
+        upstream: None,
+        changelog: None,
     }
 }
 
@@ -90,15 +129,17 @@ pub async fn collect_status(
     config: &Config,
     lock_versions: &HashMap<String, String>,
     output_dir: &Path,
+    registry: Option<&LatestDocsFetcher>,
 ) -> Vec<CrateStatus> {
     let mut crate_names: Vec<_> = config.crates.keys().cloned().collect();
     crate_names.sort();
 
     let existing_map = scan_existing_dirs(output_dir);
+    let advisory_db = open_advisory_db(config);
 
     let mut results = Vec::new();
     for crate_name in crate_names {
-        let status = if let Some(lock_version) = lock_versions.get(&crate_name).cloned() {
+        let mut status = if let Some(lock_version) = lock_versions.get(&crate_name).cloned() {
             let expected_dir = output_dir.join(format!("{crate_name}@{lock_version}"));
             if !expected_dir.is_dir() {
                 let docs_version = existing_map.get(&crate_name).map(|(v, _)| v.clone());
@@ -153,7 +194,7 @@ pub async fn collect_status(
                                     )
                             } else {
                                 let docs_version = meta.version.clone();
-                                if docs_version != lock_version {
+                                if !crate::version::same_release(&docs_version, &lock_version) {
                                     crate_status(
                                             crate_name,
                                             Some(lock_version.clone()),
@@ -230,6 +271,14 @@ pub async fn collect_status(
                 false,
             )
         };
+        let index = match registry.filter(|_| config.settings.check_updates) {
+            Some(registry) => fetch_index(&status, registry).await,
+            None => None,
+        };
+        apply_upstream_warning(&mut status, output_dir, index.as_deref());
+        apply_changelog_window(&mut status, output_dir);
+        apply_advisories(&mut status, advisory_db.as_ref());
+        apply_update_check(&mut status, index.as_deref());
         results.push(status);
     }
     results
@@ -238,16 +287,17 @@ pub async fn collect_status(
 pub async fn collect_status_latest(
     config: &Config,
     output_dir: &Path,
-    fetcher: Option<&crate::fetcher::latest::LatestDocsFetcher>,
+    fetcher: Option<&LatestDocsFetcher>,
 ) -> Vec<CrateStatus> {
     let mut crate_names: Vec<_> = config.crates.keys().cloned().collect();
     crate_names.sort();
 
     let existing_map = scan_existing_dirs(output_dir);
+    let advisory_db = open_advisory_db(config);
 
     let mut results = Vec::new();
     for crate_name in crate_names {
-        let status = if let Some((docs_version, crate_dir)) = existing_map.get(&crate_name) {
+        let mut status = if let Some((docs_version, crate_dir)) = existing_map.get(&crate_name) {
             let docs_version = docs_version.clone();
             let meta_path = crate_dir.join(".aifd-meta.toml");
             match std::fs::read_to_string(&meta_path) {
@@ -278,25 +328,20 @@ pub async fn collect_status_latest(
                                     .unwrap_or_else(|| "docsrs".to_string());
                                 let is_fallback =
                                     meta.is_fallback || source_kind == "github_fallback";
+                                let build_failed_page = source_kind == "docsrs"
+                                    && saved_docsrs_build_problem(crate_dir, &meta).is_some();
 
                                 // Check freshness if fetcher is provided
-                                let mut status = if is_fallback {
-                                    DocsStatus::SyncedFallback
-                                } else {
-                                    DocsStatus::Synced
-                                };
-                                let mut reason = if is_fallback {
-                                    "latest-docs synced via GitHub fallback".to_string()
-                                } else {
-                                    "latest-docs up to date".to_string()
-                                };
-                                let mut reason_code = if is_fallback {
-                                    "fallback_used".to_string()
-                                } else {
-                                    "latest_ok_docsrs".to_string()
-                                };
+                                let (mut status, mut reason, mut reason_code) =
+                                    latest_synced_status(is_fallback, &meta);
 
-                                if let Some(f) = fetcher {
+                                if build_failed_page {
+                                    status = DocsStatus::Corrupted;
+                                    reason =
+                                        "API.md is a docs.rs build-failure page, not documentation"
+                                            .to_string();
+                                    reason_code = "docsrs_build_failed".to_string();
+                                } else if let Some(f) = fetcher {
                                     let mut needs_check = true;
                                     if let Some(checked_at) = &meta.upstream_checked_at {
                                         if crate::utils::is_latest_cache_fresh(
@@ -318,26 +363,13 @@ pub async fn collect_status_latest(
                                         if let Ok(latest) =
                                             f.resolve_latest_version(&crate_name).await
                                         {
-                                            if latest != docs_version {
+                                            if !crate::version::same_release(&latest, &docs_version)
+                                            {
                                                 reason = format!("latest version {latest} is newer than cached {docs_version}");
                                                 reason_code = "upstream_changed".to_string();
                                             } else {
-                                                status = if is_fallback {
-                                                    DocsStatus::SyncedFallback
-                                                } else {
-                                                    DocsStatus::Synced
-                                                };
-                                                reason = if is_fallback {
-                                                    "latest-docs synced via GitHub fallback"
-                                                        .to_string()
-                                                } else {
-                                                    "latest-docs up to date".to_string()
-                                                };
-                                                reason_code = if is_fallback {
-                                                    "fallback_used".to_string()
-                                                } else {
-                                                    "latest_ok_docsrs".to_string()
-                                                };
+                                                (status, reason, reason_code) =
+                                                    latest_synced_status(is_fallback, &meta);
                                             }
                                         }
                                     }
@@ -394,11 +426,248 @@ pub async fn collect_status_latest(
                 false,
             )
         };
+        let index = match fetcher.filter(|_| config.settings.check_updates) {
+            Some(fetcher) => fetch_index(&status, fetcher).await,
+            None => None,
+        };
+        apply_upstream_warning(&mut status, output_dir, index.as_deref());
+        apply_changelog_window(&mut status, output_dir);
+        apply_advisories(&mut status, advisory_db.as_ref());
         results.push(status);
     }
     results
 }
 
+/// Status, reason and reason code of an up-to-date latest-docs artifact.
+fn latest_synced_status(is_fallback: bool, meta: &CrateMeta) -> (DocsStatus, String, String) {
+    if !is_fallback {
+        return (
+            DocsStatus::Synced,
+            "latest-docs up to date".to_string(),
+            "latest_ok_docsrs".to_string(),
+        );
+    }
+
+    match meta.fallback_reason.as_deref() {
+        Some("docsrs_build_failed") => (
+            DocsStatus::SyncedFallback,
+            "docs.rs has no build for this version; synced via GitHub fallback".to_string(),
+            "docsrs_build_failed".to_string(),
+        ),
+        _ => (
+            DocsStatus::SyncedFallback,
+            "latest-docs synced via GitHub fallback".to_string(),
+            "fallback_used".to_string(),
+        ),
+    }
+}
+
+/// Sparse-index versions of a synced crate, fetched once for both the yanked
+/// and the update check; failures leave the recorded state in charge.
+async fn fetch_index(
+    status: &CrateStatus,
+    registry: &LatestDocsFetcher,
+) -> Option<Vec<IndexVersion>> {
+    if !matches!(
+        status.status,
+        DocsStatus::Synced | DocsStatus::SyncedFallback
+    ) {
+        return None;
+    }
+    match registry.fetch_index_versions(&status.crate_name).await {
+        Ok(versions) => Some(versions),
+        Err(e) => {
+            tracing::warn!("index lookup skipped for {}: {e}", status.crate_name);
+            None
+        }
+    }
+}
+
+/// Turn synced docs into `Yanked` / `Deprecated` when upstream warns against the
+/// crate. The yanked flag comes from the sparse index when `index` is given
+/// (`check_updates`), else from what sync recorded; deprecated and the GitHub
+/// archived flag are recorded at sync time.
+fn apply_upstream_warning(
+    status: &mut CrateStatus,
+    output_dir: &Path,
+    index: Option<&[IndexVersion]>,
+) {
+    if !matches!(
+        status.status,
+        DocsStatus::Synced | DocsStatus::SyncedFallback
+    ) {
+        return;
+    }
+    let Some(version) = status.docs_version.clone() else {
+        return;
+    };
+    let Some(meta) = crate::storage::read_meta(output_dir, &status.crate_name, &version) else {
+        return;
+    };
+
+    let live_yanked = index
+        .and_then(|versions| versions.iter().find(|v| v.version == version))
+        .map(|v| v.yanked);
+    let crates_io = match live_yanked {
+        Some(yanked) => Some(CratesIoMetadata {
+            yanked,
+            ..meta.crates_io.clone().unwrap_or_default()
+        }),
+        None => meta.crates_io.clone(),
+    };
+
+    if let Some((docs_status, reason, reason_code)) =
+        upstream_warning(&version, crates_io.as_ref(), meta.repo_archived)
+    {
+        status.status = docs_status;
+        status.reason = reason;
+        status.reason_code = reason_code.to_string();
+    }
+}
+
+/// Attach the changelog window recorded at sync time when it dropped entries.
+fn apply_changelog_window(status: &mut CrateStatus, output_dir: &Path) {
+    let Some(version) = status.docs_version.as_deref() else {
+        return;
+    };
+    status.changelog = crate::storage::read_meta(output_dir, &status.crate_name, version)
+        .and_then(|meta| meta.changelog)
+        .filter(|changelog| changelog.omitted_versions > 0);
+}
+
+fn changelog_note(changelog: &ChangelogMeta) -> String {
+    let kept = match changelog.kept_versions.as_slice() {
+        [] => "no versions".to_string(),
+        [only] => format!("1 version ({only})"),
+        [first, .., last] => format!(
+            "{} versions ({first} … {last})",
+            changelog.kept_versions.len()
+        ),
+    };
+    format!(
+        "changelog: kept {kept}, {} older omitted (window `{}`)",
+        changelog.omitted_versions, changelog.window
+    )
+}
+
+/// Record the newest upstream releases for a synced lockfile crate and mark it
+/// `UpdateAvailable` when one of them is newer than the locked version.
+fn apply_update_check(status: &mut CrateStatus, index: Option<&[IndexVersion]>) {
+    let Some(versions) = index else {
+        return;
+    };
+    if !matches!(
+        status.status,
+        DocsStatus::Synced | DocsStatus::SyncedFallback
+    ) {
+        return;
+    }
+    let Some(locked) = status.lock_version.clone() else {
+        return;
+    };
+    let upstream = upstream_versions(versions, &locked);
+    if let Some(newer) = upstream.newer_than(&locked) {
+        status.status = DocsStatus::UpdateAvailable;
+        status.reason = update_reason(&locked, newer, &upstream);
+        status.reason_code = "update_available".to_string();
+    }
+    status.upstream = Some(upstream);
+}
+
+fn update_reason(locked: &str, newer: &str, upstream: &UpstreamVersions) -> String {
+    let mut reason = format!("{newer} is available (locked {locked})");
+    if let Some(compatible) = upstream
+        .latest_compatible
+        .as_deref()
+        .filter(|c| *c != newer && *c != locked)
+    {
+        reason.push_str(&format!("; latest compatible {compatible}"));
+    }
+    reason
+}
+
+fn open_advisory_db(config: &Config) -> Option<AdvisoryDb> {
+    let path = config.settings.advisory_db.as_deref()?;
+    match AdvisoryDb::open(path) {
+        Ok(db) => Some(db),
+        Err(e) => {
+            tracing::warn!("advisory checks skipped: {e}");
+            None
+        }
+    }
+}
+
+/// Mark synced docs `Vulnerable` when a RustSec vulnerability (not an
+/// informational advisory) affects the docs version.
+fn apply_advisories(status: &mut CrateStatus, db: Option<&AdvisoryDb>) {
+    let Some(db) = db else {
+        return;
+    };
+    if matches!(
+        status.status,
+        DocsStatus::Outdated | DocsStatus::Missing | DocsStatus::Corrupted
+    ) {
+        return;
+    }
+    let Some(version) = status.docs_version.as_deref() else {
+        return;
+    };
+
+    let ids: Vec<String> = db
+        .advisories_for(&status.crate_name, version)
+        .into_iter()
+        .filter(|a| a.informational.is_none())
+        .map(|a| a.id)
+        .collect();
+    if ids.is_empty() {
+        return;
+    }
+    status.status = DocsStatus::Vulnerable;
+    status.reason = format!(
+        "RustSec advisories affect {version}: {} (see ADVISORIES.md)",
+        ids.join(", ")
+    );
+    status.reason_code = "advisory".to_string();
+}
+
+/// A yanked version wins over a deprecated crate or archived repository.
+fn upstream_warning(
+    version: &str,
+    crates_io: Option<&CratesIoMetadata>,
+    repo_archived: bool,
+) -> Option<(DocsStatus, String, &'static str)> {
+    if crates_io.is_some_and(|m| m.yanked) {
+        return Some((
+            DocsStatus::Yanked,
+            format!("version {version} is yanked on crates.io"),
+            "version_yanked",
+        ));
+    }
+    if crates_io.is_some_and(|m| m.deprecated) {
+        return Some((
+            DocsStatus::Deprecated,
+            "crate is marked deprecated on crates.io".to_string(),
+            "crate_deprecated",
+        ));
+    }
+    if repo_archived {
+        return Some((
+            DocsStatus::Deprecated,
+            "upstream GitHub repository is archived".to_string(),
+            "repo_archived",
+        ));
+    }
+    None
+}
+
+/// Artifacts saved before build-failure detection may hold docs.rs's
+/// "failed to build" page instead of documentation.
+fn saved_docsrs_build_problem(crate_dir: &Path, meta: &CrateMeta) -> Option<&'static str> {
+    let artifact = meta.artifact_path.as_deref().unwrap_or("API.md");
+    let content = std::fs::read_to_string(crate_dir.join(artifact)).ok()?;
+    crate::fetcher::latest::saved_build_problem(&content)
+}
+
 fn scan_existing_dirs(output_dir: &Path) -> HashMap<String, (String, PathBuf)> {
     let mut map: HashMap<String, (String, PathBuf)> = HashMap::new();
 
@@ -414,7 +683,7 @@ fn scan_existing_dirs(output_dir: &Path) -> HashMap<String, (String, PathBuf)> {
         let dir_name = entry.file_name();
         let dir_name = dir_name.to_string_lossy();
 
-        if let Some((name, version)) = split_crate_version(&dir_name) {
+        if let Some((name, version)) = crate::storage::split_name_version(&dir_name) {
             let entry_v = version.to_string();
             let entry_p = entry.path();
 
@@ -430,10 +699,6 @@ fn scan_existing_dirs(output_dir: &Path) -> HashMap<String, (String, PathBuf)> {
     map
 }
 
-fn split_crate_version(dir_name: &str) -> Option<(&str, &str)> {
-    dir_name.rsplit_once('@')
-}
-
 pub fn print_status_table(statuses: &[CrateStatus]) {
     print!("{}", format_status_table(statuses));
 }
@@ -494,16 +759,34 @@ fn format_status_table(statuses: &[CrateStatus]) -> String {
             if item.is_fallback { "yes" } else { "no" },
         );
         let _ = writeln!(output, "  ↳ {}", item.reason);
+        if let Some(changelog) = &item.changelog {
+            let _ = writeln!(output, "  ↳ {}", changelog_note(changelog));
+        }
     }
 
     let summary = summarize(statuses);
     let _ = writeln!(output);
     let _ = writeln!(
         output,
-        "Total: {} | Synced: {} | Missing: {} | Outdated: {} | Corrupted: {}",
-        summary.total, summary.synced, summary.missing, summary.outdated, summary.corrupted
+        "Total: {} | Synced: {} | Missing: {} | Outdated: {} | Corrupted: {} | Yanked: {} | Deprecated: {} | Vulnerable: {} | Updates: {}",
+        summary.total,
+        summary.synced,
+        summary.missing,
+        summary.outdated,
+        summary.corrupted,
+        summary.yanked,
+        summary.deprecated,
+        summary.vulnerable,
+        summary.update_available
     );
 
+    if summary.update_available > 0 {
+        let _ = writeln!(
+            output,
+            "Hint: newer releases exist; `cargo update -p <crate>` (or bump Cargo.toml for a new major), then sync"
+        );
+    }
+
     if summary.has_problems() {
         let _ = writeln!(
             output,
@@ -513,6 +796,12 @@ fn format_status_table(statuses: &[CrateStatus]) -> String {
             output,
             "CI hint: run `cargo ai-fdocs check` to fail on stale docs"
         );
+        if summary.yanked > 0 {
+            let _ = writeln!(
+                output,
+                "Hint: move off yanked versions with `cargo update -p <crate>`, then sync"
+            );
+        }
 
         let _ = writeln!(output, "\nProblem details:");
         for item in statuses.iter().filter(|s| s.status.is_problem()) {
@@ -536,11 +825,20 @@ pub struct StatusSummary {
     missing: usize,
     outdated: usize,
     corrupted: usize,
+    yanked: usize,
+    deprecated: usize,
+    vulnerable: usize,
+    update_available: usize,
 }
 
 impl StatusSummary {
     fn has_problems(&self) -> bool {
-        self.missing > 0 || self.outdated > 0 || self.corrupted > 0
+        self.missing > 0
+            || self.outdated > 0
+            || self.corrupted > 0
+            || self.yanked > 0
+            || self.deprecated > 0
+            || self.vulnerable > 0
     }
 }
 
@@ -556,6 +854,10 @@ pub fn summarize(statuses: &[CrateStatus]) -> StatusSummary {
             DocsStatus::Missing => summary.missing += 1,
             DocsStatus::Outdated => summary.outdated += 1,
             DocsStatus::Corrupted => summary.corrupted += 1,
+            DocsStatus::Yanked => summary.yanked += 1,
+            DocsStatus::Deprecated => summary.deprecated += 1,
+            DocsStatus::Vulnerable => summary.vulnerable += 1,
+            DocsStatus::UpdateAvailable => summary.update_available += 1,
         }
     }
 
@@ -565,8 +867,9 @@ pub fn summarize(statuses: &[CrateStatus]) -> StatusSummary {
 #[cfg(test)]
 mod tests {
     use super::{
-        collect_status_latest, format_status_json, format_status_table, CrateStatus, DocsStatus,
-        StatusMode,
+        apply_upstream_warning, collect_status, collect_status_latest, crate_status,
+        format_status_json, format_status_table, update_reason, CrateStatus, DocsStatus,
+        IndexVersion, StatusMode, UpstreamVersions,
     };
     use crate::config::{Config, CrateDoc, Settings};
     use std::collections::HashMap;
@@ -600,6 +903,8 @@ mod tests {
             mode: StatusMode::Lockfile.as_str().to_string(),
             source_kind: Some("github".to_string()),
             is_fallback: false,
+            upstream: None,
+            changelog: None,
         }];
 
         let json = format_status_json(&statuses).expect("json serialization");
@@ -625,6 +930,8 @@ mod tests {
             mode: StatusMode::Lockfile.as_str().to_string(),
             source_kind: None,
             is_fallback: false,
+            upstream: None,
+            changelog: None,
         }];
 
         let table = format_status_table(&statuses);
@@ -637,6 +944,40 @@ mod tests {
         assert!(table.contains("Problem details:"));
     }
 
+    #[test]
+    fn formats_update_available_with_upstream_versions() {
+        let upstream = UpstreamVersions {
+            latest_stable: Some("1.44.0".to_string()),
+            latest_compatible: Some("1.44.0".to_string()),
+            latest: Some("1.44.0".to_string()),
+        };
+        let statuses = vec![CrateStatus {
+            crate_name: "tokio".to_string(),
+            lock_version: Some("1.38.0".to_string()),
+            docs_version: Some("1.38.0".to_string()),
+            status: DocsStatus::UpdateAvailable,
+            reason: update_reason("1.38.0", "1.44.0", &upstream),
+            reason_code: "update_available".to_string(),
+            mode: StatusMode::Lockfile.as_str().to_string(),
+            source_kind: Some("github".to_string()),
+            is_fallback: false,
+            upstream: Some(upstream),
+            changelog: None,
+        }];
+
+        let json = format_status_json(&statuses).expect("json serialization");
+        assert!(json.contains("\"status\": \"UpdateAvailable\""));
+        assert!(json.contains("\"latest_compatible\": \"1.44.0\""));
+        assert!(json.contains("\"update_available\": 1"));
+
+        let table = format_status_table(&statuses);
+        assert!(table.contains("1.44.0 is available (locked 1.38.0)"));
+        assert!(table.contains("| Updates: 1"));
+        assert!(table.contains("Hint: newer releases exist"));
+        assert!(!table.contains("Problem details:"));
+        assert!(!DocsStatus::UpdateAvailable.fails_check(&Settings::default()));
+    }
+
     #[tokio::test]
     async fn collect_status_latest_marks_github_fallback_as_synced_fallback() {
         let tmp = std::env::temp_dir().join(format!("aifd-status-latest-{}", std::process::id()));
@@ -653,10 +994,7 @@ mod tests {
             "serde".to_string(),
             CrateDoc {
                 repo: Some("serde-rs/serde".to_string()),
-                subpath: None,
-                files: None,
-                sources: None,
-                ai_notes: String::new(),
+                ..CrateDoc::default()
             },
         );
 
@@ -675,4 +1013,210 @@ mod tests {
 
         let _ = fs::remove_dir_all(&tmp);
     }
+
+    #[tokio::test]
+    async fn collect_status_explains_changelog_truncation() {
+        let tmp =
+            std::env::temp_dir().join(format!("aifd-status-changelog-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&tmp);
+        fs::create_dir_all(tmp.join("tokio@1.44.0")).expect("create crate dir");
+        fs::write(
+            tmp.join("tokio@1.44.0/.aifd-meta.toml"),
+            "schema_version = 1\nversion = \"1.44.0\"\nsource_kind = \"docsrs\"\n\n[changelog]\nwindow = \"newer\"\nkept_versions = [\"1.44.0\", \"1.43.0\", \"1.42.0\"]\nomitted_versions = 40\n",
+        )
+        .expect("write meta");
+
+        let mut crates = HashMap::new();
+        crates.insert("tokio".to_string(), CrateDoc::default());
+        let config = Config {
+            settings: Settings::default(),
+            crates,
+        };
+
+        let statuses = collect_status_latest(&config, tmp.as_path(), None).await;
+        let changelog = statuses[0].changelog.as_ref().expect("changelog window");
+        assert_eq!(changelog.window, "newer");
+        assert_eq!(changelog.omitted_versions, 40);
+
+        let table = format_status_table(&statuses);
+        assert!(table.contains(
+            "  ↳ changelog: kept 3 versions (1.44.0 … 1.42.0), 40 older omitted (window `newer`)\n"
+        ));
+        let json = format_status_json(&statuses).expect("json serialization");
+        assert!(json.contains("\"kept_versions\""));
+
+        let _ = fs::remove_dir_all(&tmp);
+    }
+
+    #[tokio::test]
+    async fn collect_status_latest_reports_docsrs_build_failures() {
+        let tmp =
+            std::env::temp_dir().join(format!("aifd-status-build-failed-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&tmp);
+        fs::create_dir_all(tmp.join("serde@1.0.0")).expect("create crate dir");
+        fs::write(
+            tmp.join("serde@1.0.0/.aifd-meta.toml"),
+            "schema_version = 1\nversion = \"1.0.0\"\nsource_kind = \"github_fallback\"\nfallback_reason = \"docsrs_build_failed\"\n",
+        )
+        .expect("write meta");
+        fs::create_dir_all(tmp.join("tokio@1.0.0")).expect("create crate dir");
+        fs::write(
+            tmp.join("tokio@1.0.0/.aifd-meta.toml"),
+            "schema_version = 1\nversion = \"1.0.0\"\nsource_kind = \"docsrs\"\nartifact_path = \"API.md\"\n",
+        )
+        .expect("write meta");
+        fs::write(
+            tmp.join("tokio@1.0.0/API.md"),
+            "# tokio@1.0.0\n\ndocs.rs failed to build tokio-1.0.0\n",
+        )
+        .expect("write api");
+
+        let mut crates = HashMap::new();
+        crates.insert("serde".to_string(), CrateDoc::default());
+        crates.insert("tokio".to_string(), CrateDoc::default());
+        let config = Config {
+            settings: Settings::default(),
+            crates,
+        };
+
+        let statuses = collect_status_latest(&config, tmp.as_path(), None).await;
+        assert_eq!(statuses[0].crate_name, "serde");
+        assert_eq!(statuses[0].status, DocsStatus::SyncedFallback);
+        assert_eq!(statuses[0].reason_code, "docsrs_build_failed");
+        assert_eq!(statuses[1].crate_name, "tokio");
+        assert_eq!(statuses[1].status, DocsStatus::Corrupted);
+        assert_eq!(statuses[1].reason_code, "docsrs_build_failed");
+
+        let _ = fs::remove_dir_all(&tmp);
+    }
+
+    #[tokio::test]
+    async fn collect_status_reports_yanked_and_archived_crates() {
+        let tmp = std::env::temp_dir().join(format!("aifd-status-yanked-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&tmp);
+        fs::create_dir_all(tmp.join("serde@1.0.0")).expect("create crate dir");
+        fs::write(
+            tmp.join("serde@1.0.0/.aifd-meta.toml"),
+            "schema_version = 2\nversion = \"1.0.0\"\nrepo_archived = true\n\n[crates_io]\nyanked = true\n",
+        )
+        .expect("write meta");
+        fs::create_dir_all(tmp.join("failure@0.1.8")).expect("create crate dir");
+        fs::write(
+            tmp.join("failure@0.1.8/.aifd-meta.toml"),
+            "schema_version = 2\nversion = \"0.1.8\"\nrepo_archived = true\n",
+        )
+        .expect("write meta");
+
+        let mut crates = HashMap::new();
+        crates.insert("serde".to_string(), CrateDoc::default());
+        crates.insert("failure".to_string(), CrateDoc::default());
+        let config = Config {
+            settings: Settings::default(),
+            crates,
+        };
+        let mut lock_versions = HashMap::new();
+        lock_versions.insert("serde".to_string(), "1.0.0".to_string());
+        lock_versions.insert("failure".to_string(), "0.1.8".to_string());
+
+        let statuses = collect_status(&config, &lock_versions, tmp.as_path(), None).await;
+        assert_eq!(statuses[0].crate_name, "failure");
+        assert_eq!(statuses[0].status, DocsStatus::Deprecated);
+        assert_eq!(statuses[0].reason_code, "repo_archived");
+        assert_eq!(statuses[1].crate_name, "serde");
+        assert_eq!(statuses[1].status, DocsStatus::Yanked);
+        assert_eq!(statuses[1].reason_code, "version_yanked");
+
+        assert!(DocsStatus::Yanked.fails_check(&config.settings));
+        assert!(!DocsStatus::Deprecated.fails_check(&config.settings));
+        let strict = Settings {
+            check_fail_on_deprecated: true,
+            ..Settings::default()
+        };
+        assert!(DocsStatus::Deprecated.fails_check(&strict));
+
+        let table = format_status_table(&statuses);
+        assert!(table.contains("| Yanked: 1 | Deprecated: 1"));
+        assert!(table.contains("cargo update -p <crate>"));
+
+        let _ = fs::remove_dir_all(&tmp);
+    }
+
+    #[test]
+    fn index_versions_override_recorded_yanked_flag() {
+        let tmp = std::env::temp_dir().join(format!("aifd-status-index-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&tmp);
+        fs::create_dir_all(tmp.join("serde@1.0.0")).expect("create crate dir");
+        fs::write(
+            tmp.join("serde@1.0.0/.aifd-meta.toml"),
+            "schema_version = 2\nversion = \"1.0.0\"\n\n[crates_io]\nyanked = true\n",
+        )
+        .expect("write meta");
+        let synced = || {
+            crate_status(
+                "serde".to_string(),
+                Some("1.0.0".to_string()),
+                Some("1.0.0".to_string()),
+                DocsStatus::Synced,
+                "up to date",
+                "ok",
+                StatusMode::Lockfile,
+                None,
+                false,
+            )
+        };
+
+        let mut recorded = synced();
+        apply_upstream_warning(&mut recorded, &tmp, None);
+        assert_eq!(recorded.status, DocsStatus::Yanked);
+
+        let index = [IndexVersion {
+            version: "1.0.0".to_string(),
+            yanked: false,
+        }];
+        let mut live = synced();
+        apply_upstream_warning(&mut live, &tmp, Some(&index));
+        assert_eq!(live.status, DocsStatus::Synced);
+
+        let _ = fs::remove_dir_all(&tmp);
+    }
+
+    #[tokio::test]
+    async fn collect_status_reports_rustsec_vulnerabilities() {
+        let tmp = std::env::temp_dir().join(format!("aifd-status-advisory-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&tmp);
+        let docs = tmp.join("docs");
+        fs::create_dir_all(docs.join("time@0.2.22")).expect("create crate dir");
+        fs::write(
+            docs.join("time@0.2.22/.aifd-meta.toml"),
+            "schema_version = 2\nversion = \"0.2.22\"\n",
+        )
+        .expect("write meta");
+        let db = tmp.join("advisory-db");
+        fs::create_dir_all(db.join("crates/time")).expect("create db dir");
+        fs::write(
+            db.join("crates/time/RUSTSEC-2020-0071.md"),
+            "```toml\n[advisory]\nid = \"RUSTSEC-2020-0071\"\npackage = \"time\"\n\n[versions]\npatched = [\">= 0.2.23\"]\n```\n\n# Potential segfault\n",
+        )
+        .expect("write advisory");
+
+        let mut crates = HashMap::new();
+        crates.insert("time".to_string(), CrateDoc::default());
+        let config = Config {
+            settings: Settings {
+                advisory_db: Some(db),
+                ..Settings::default()
+            },
+            crates,
+        };
+        let mut lock_versions = HashMap::new();
+        lock_versions.insert("time".to_string(), "0.2.22".to_string());
+
+        let statuses = collect_status(&config, &lock_versions, docs.as_path(), None).await;
+        assert_eq!(statuses[0].status, DocsStatus::Vulnerable);
+        assert_eq!(statuses[0].reason_code, "advisory");
+        assert!(statuses[0].reason.contains("RUSTSEC-2020-0071"));
+        assert!(DocsStatus::Vulnerable.fails_check(&config.settings));
+
+        let _ = fs::remove_dir_all(&tmp);
+    }
 }
@@ -1,17 +1,51 @@
-use std::collections::{HashMap, HashSet};
+use std::collections::{BTreeMap, HashMap, HashSet};
 use std::fs;
 use std::path::{Path, PathBuf};
 
 use chrono::Utc;
 use tracing::{debug, info};
 
-use crate::config::{Config, CrateDoc};
+use crate::advisory::{render_advisories_markdown, Advisory, ADVISORIES_FILE};
+use crate::config::{
+    ChangelogWindow, ChunkStrategy, Config, ContentSettings, CrateDoc, MarkdownCleanup,
+    TokenEstimatorKind,
+};
 use crate::error::{AiDocsError, Result};
+use crate::fetcher::crates_io::{render_crate_info, CratesIoMetadata};
 use crate::fetcher::github::{FetchedFile, ResolvedRef};
-use crate::processor::changelog;
+use crate::fetcher::latest::DocsRsArtifact;
+use crate::fetcher::sparse_index::UpstreamVersions;
+use crate::processor::api_diff::API_DIFF_FILE;
+use crate::processor::changelog::{self, ChangelogEntry};
+use crate::processor::chunk::{
+    exceeds_budget, render_chunk_index, split_markdown, ChunkManifest, ChunkedFile,
+    CHUNKS_MANIFEST_FILE,
+};
+use crate::processor::cleanup::clean_markdown;
+use crate::processor::links::{rewrite_relative_links, LinkBase};
+use crate::processor::sections::filter_sections;
+use crate::tokens::estimator;
+use crate::upgrade::{UPGRADES_INDEX_FILE, UPGRADE_FILE};
+use crate::usage::UsedPath;
 
 pub const META_SCHEMA_VERSION: u32 = 2;
 
+/// Changelog fetched from the repository next to docs.rs docs in latest-docs
+/// mode (`changelog_window = "newer"`).
+pub const CHANGELOG_FILE: &str = "CHANGELOG.md";
+
+/// The repository changelog saved next to docs.rs docs in latest-docs mode.
+pub struct RepoChangelog {
+    pub repo: String,
+    pub git_ref: String,
+    pub file: FetchedFile,
+    /// `Cargo.lock` version the `newer` window starts from.
+    pub locked_version: Option<String>,
+}
+
+/// Versions recorded by the last lockfile sync, next to the crate folders.
+pub const SYNC_STATE_FILE: &str = ".aifd-sync.toml";
+
 fn default_false() -> bool {
     false
 }
@@ -56,6 +90,80 @@ pub struct CrateMeta {
     pub artifact_sha256: Option<String>,
     #[serde(default)]
     pub artifact_bytes: Option<usize>,
+    /// Estimated tokens of the saved files, by `token_estimator`.
+    #[serde(default)]
+    pub artifact_tokens: Option<usize>,
+    #[serde(default)]
+    pub token_estimator: Option<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub files: Vec<FileMeta>,
+    /// Reason code for a GitHub fallback, e.g. `docsrs_build_failed`.
+    #[serde(default)]
+    pub fallback_reason: Option<String>,
+    /// The GitHub repository was archived when the docs were synced.
+    #[serde(default = "default_false")]
+    pub repo_archived: bool,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub docsrs_pages: Vec<DocsRsPageMeta>,
+    #[serde(default)]
+    pub crates_io: Option<CratesIoMetadata>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub changelog: Option<ChangelogMeta>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub budget: Option<BudgetMeta>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub cleanup: Vec<CleanupMeta>,
+}
+
+/// Share of `total_budget_kb` / `total_budget_tokens` a crate got when files had to be trimmed.
+#[derive(serde::Serialize, serde::Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct BudgetMeta {
+    /// The configured budget, e.g. `512KB` or `150000 tokens`.
+    pub limit: String,
+    pub priority: u32,
+    /// `bytes` or `tokens`.
+    pub unit: String,
+    /// Allocated and wanted size of the crate's Markdown, in `unit`.
+    pub allocated: usize,
+    pub demand: usize,
+    pub trimmed_files: Vec<String>,
+}
+
+/// Which changelog entries the saved Markdown kept, so `status` can explain a cut.
+#[derive(serde::Serialize, serde::Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct ChangelogMeta {
+    /// `changelog_window` as written in the config, e.g. `previous:2`.
+    pub window: String,
+    pub kept_versions: Vec<String>,
+    #[serde(default)]
+    pub omitted_versions: usize,
+}
+
+/// What one `markdown_cleanup` rule, or the crate's section rules, stripped
+/// from a fetched file.
+#[derive(serde::Serialize, serde::Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct CleanupMeta {
+    pub file: String,
+    /// `sections`, `badges`, `images`, `html_comments`, `sponsor_sections` or
+    /// `link_references`.
+    pub rule: String,
+    pub items: usize,
+    pub bytes_before: usize,
+    pub bytes_after: usize,
+}
+
+/// Size of one saved file, so `cost` can skip re-estimating unchanged files.
+#[derive(serde::Serialize, serde::Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct FileMeta {
+    pub path: String,
+    pub bytes: usize,
+    pub tokens: usize,
+}
+
+#[derive(serde::Serialize, serde::Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct DocsRsPageMeta {
+    pub url: String,
+    pub artifact_path: String,
 }
 
 #[derive(Debug, Clone)]
@@ -70,11 +178,45 @@ pub struct SavedCrate {
 
 // crate_config_fingerprint removed in favor of CrateDoc::config_hash
 
+#[cfg(test)]
 fn render_crate_summary(saved: &SavedCrate) -> String {
-    render_summary_with_provenance(saved, None)
+    render_summary_with_provenance(saved, None, None, false)
+}
+
+/// Yanked / deprecated / archived notices, so the AI does not recommend the crate blindly.
+fn render_upstream_warnings(
+    saved: &SavedCrate,
+    crates_io: Option<&CratesIoMetadata>,
+    repo_archived: bool,
+) -> Vec<String> {
+    let mut warnings = Vec::new();
+    if crates_io.is_some_and(|m| m.yanked) {
+        warnings.push(format!(
+            "- ⚠️ **`{}@{}` is yanked on crates.io.** Do not suggest depending on this version; prefer a newer release.",
+            saved.name, saved.version
+        ));
+    }
+    if crates_io.is_some_and(|m| m.deprecated) {
+        warnings.push(format!(
+            "- ⚠️ **`{}` is marked deprecated on crates.io.** Avoid recommending it for new code.",
+            saved.name
+        ));
+    }
+    if repo_archived {
+        warnings.push(
+            "- ⚠️ **The upstream GitHub repository is archived** and no longer receives fixes."
+                .to_string(),
+        );
+    }
+    warnings
 }
 
-fn render_summary_with_provenance(saved: &SavedCrate, provenance: Option<&str>) -> String {
+fn render_summary_with_provenance(
+    saved: &SavedCrate,
+    provenance: Option<&str>,
+    crates_io: Option<&CratesIoMetadata>,
+    repo_archived: bool,
+) -> String {
     let mut content = String::new();
     content.push_str("<!-- This file is auto-generated by ai-fdocs. Do not edit manually. -->\n\n");
 
@@ -88,10 +230,19 @@ fn render_summary_with_provenance(saved: &SavedCrate, provenance: Option<&str>)
         content.push_str(&format!("# {}@{}\n\n", saved.name, saved.version));
     }
 
+    let warnings = render_upstream_warnings(saved, crates_io, repo_archived);
+    if !warnings.is_empty() {
+        content.push_str(&format!("## Warnings\n\n{}\n\n", warnings.join("\n")));
+    }
+
     if let Some(provenance) = provenance {
         content.push_str(&format!("## Source\n\n{}\n\n", provenance.trim()));
     }
 
+    if let Some(info) = crates_io.map(render_crate_info).filter(|i| !i.is_empty()) {
+        content.push_str(&format!("## Crate info\n\n{info}\n\n"));
+    }
+
     if !saved.ai_notes.trim().is_empty() {
         content.push_str(&format!("## AI Notes\n\n{}\n\n", saved.ai_notes.trim()));
     }
@@ -115,7 +266,9 @@ fn latest_docs_summary_provenance(
 ) -> String {
     let mut lines = Vec::new();
     match source_kind {
-        "docsrs" => lines.push(format!("- Source kind: `docsrs` ({docsrs_input_url})")),
+        "docsrs" | "rustdoc_json" | "local_rustdoc" => lines.push(format!(
+            "- Source kind: `{source_kind}` ({docsrs_input_url})"
+        )),
         "github_fallback" => lines.push(format!(
             "- Source kind: `github_fallback` (docs.rs unavailable: {docsrs_input_url})"
         )),
@@ -166,12 +319,88 @@ fn should_inject_header(file_path: &str) -> bool {
     })
 }
 
+/// How large a saved file may get and what happens to larger ones.
+#[derive(Debug, Clone, Copy)]
+pub struct OutputLimits {
+    pub max_file_size_kb: usize,
+    pub chunk_strategy: ChunkStrategy,
+    pub chunk_max_tokens: Option<usize>,
+    pub token_estimator: TokenEstimatorKind,
+    pub markdown_cleanup: MarkdownCleanup,
+}
+
+/// Files to write for one document: the document itself, or a part index under
+/// its name followed by its chunks.
+struct FittedFile {
+    outputs: Vec<(String, String)>,
+    truncated: bool,
+    chunked: Option<ChunkedFile>,
+}
+
+fn is_markdown_name(name: &str) -> bool {
+    Path::new(name)
+        .extension()
+        .is_some_and(|ext| ext.eq_ignore_ascii_case("md") || ext.eq_ignore_ascii_case("markdown"))
+}
+
+/// Apply `limits` to a document saved as `name`: oversized Markdown is chunked
+/// with `chunk_strategy = "headings"`, everything else is truncated (Markdown at
+/// a section boundary, other files at the byte limit).
+fn fit_file(name: &str, content: &str, limits: &OutputLimits) -> FittedFile {
+    let max_bytes = limits.max_file_size_kb * 1024;
+    let tokens = estimator(limits.token_estimator);
+    if limits.chunk_strategy == ChunkStrategy::Headings
+        && is_markdown_name(name)
+        && exceeds_budget(content, max_bytes, limits.chunk_max_tokens, tokens)
+    {
+        let chunked = split_markdown(name, content, max_bytes, limits.chunk_max_tokens, tokens);
+        let mut outputs = vec![(name.to_string(), render_chunk_index(&chunked))];
+        outputs.extend(
+            chunked
+                .chunks
+                .iter()
+                .map(|chunk| (chunk.file.clone(), chunk.content.clone())),
+        );
+        return FittedFile {
+            outputs,
+            truncated: false,
+            chunked: Some(chunked),
+        };
+    }
+
+    let (content, truncated) = if is_markdown_name(name) {
+        truncate_if_needed(content, limits.max_file_size_kb)
+    } else {
+        truncate_text_if_needed(content, limits.max_file_size_kb)
+    };
+    FittedFile {
+        outputs: vec![(name.to_string(), content)],
+        truncated,
+        chunked: None,
+    }
+}
+
 pub fn truncate_if_needed(content: &str, max_size_kb: usize) -> (String, bool) {
     let max_bytes = max_size_kb * 1024;
     if content.len() <= max_bytes {
         return (content.to_string(), false);
     }
 
+    let marker = format!("[TRUNCATED by ai-fdocs at {max_size_kb}KB]");
+    (
+        crate::processor::truncate::truncate_markdown(content, max_bytes, &marker),
+        true,
+    )
+}
+
+/// Cut a non-Markdown file (source, TOML) at the last char boundary within the
+/// limit, where heading and section rules do not apply.
+fn truncate_text_if_needed(content: &str, max_size_kb: usize) -> (String, bool) {
+    let max_bytes = max_size_kb * 1024;
+    if content.len() <= max_bytes {
+        return (content.to_string(), false);
+    }
+
     let boundary = crate::utils::floor_char_boundary(content, max_bytes);
     let truncated = &content[..boundary];
     (
@@ -215,6 +444,7 @@ pub fn is_cached(
     crate_name: &str,
     version: &str,
     crate_config: &CrateDoc,
+    content: &ContentSettings,
 ) -> bool {
     let crate_dir = output_dir.join(format!("{crate_name}@{version}"));
     let meta_path = crate_dir.join(".aifd-meta.toml");
@@ -225,7 +455,7 @@ pub fn is_cached(
 
     match load_meta_with_migration(&meta_path) {
         Some(meta) => {
-            let current_hash = crate_config.config_hash();
+            let current_hash = crate_config.config_hash(content);
             if meta.version != version {
                 return false;
             }
@@ -242,11 +472,54 @@ pub fn is_cached(
     }
 }
 
+/// Section rules, Markdown cleanup and relative-link rewriting for one fetched
+/// Markdown file; what the first two removed is added to `cleanup_meta`.
+fn process_fetched_markdown(
+    mut content: String,
+    flat_name: &str,
+    crate_config: &CrateDoc,
+    markdown_cleanup: &MarkdownCleanup,
+    base: &LinkBase<'_>,
+    cleanup_meta: &mut Vec<CleanupMeta>,
+) -> String {
+    let (kept, sections) = filter_sections(
+        &content,
+        &crate_config.include_sections,
+        &crate_config.exclude_sections,
+    );
+    if kept.len() != content.len() {
+        cleanup_meta.push(CleanupMeta {
+            file: flat_name.to_string(),
+            rule: "sections".to_string(),
+            items: sections,
+            bytes_before: content.len(),
+            bytes_after: kept.len(),
+        });
+        content = kept;
+    }
+    let (cleaned, removals) = clean_markdown(&content, markdown_cleanup);
+    cleanup_meta.extend(removals.into_iter().map(|removal| CleanupMeta {
+        file: flat_name.to_string(),
+        rule: removal.rule.to_string(),
+        items: removal.items,
+        bytes_before: removal.bytes_before,
+        bytes_after: removal.bytes_after,
+    }));
+
+    let (linked, rewritten) = rewrite_relative_links(&cleaned, base);
+    if rewritten > 0 {
+        debug!("{flat_name}: rewrote {rewritten} relative link(s)");
+    }
+    linked
+}
+
 pub struct SaveRequest<'a> {
     pub crate_name: &'a str,
     pub version: &'a str,
     pub fetched_files: &'a [FetchedFile],
     pub crate_config: &'a CrateDoc,
+    /// `Cargo.lock` version when it differs from `version` (latest-docs mode).
+    pub locked_version: Option<&'a str>,
 }
 
 pub fn save_crate_files(
@@ -262,6 +535,17 @@ pub fn save_crate_files(
     fs::create_dir_all(&crate_dir)?;
 
     let mut saved_names = Vec::new();
+    let mut changelog_json = Vec::new();
+    let mut changelog_meta: Option<ChangelogMeta> = None;
+    let mut chunked = Vec::new();
+    let mut file_meta = Vec::new();
+    let mut cleanup_meta = Vec::new();
+    let tokens = estimator(save_ctx.limits.token_estimator);
+    let local_copies: HashMap<String, String> = req
+        .fetched_files
+        .iter()
+        .map(|file| (file.path.clone(), flatten_filename(&file.path)))
+        .collect();
 
     let mut total_bytes = 0;
     let mut any_truncated = false;
@@ -276,39 +560,88 @@ pub fn save_crate_files(
         let mut content = file.content.clone();
 
         if file.path.to_lowercase().contains("changelog") {
-            content = changelog::truncate_changelog(&content, req.version);
-        }
-
-        let (truncated_content, is_truncated) =
-            truncate_if_needed(&content, save_ctx.max_file_size_kb);
-        content = truncated_content;
-        if is_truncated {
-            any_truncated = true;
+            let (windowed, json) = window_changelog(
+                &crate_dir,
+                &flat_name,
+                req.crate_name,
+                req.version,
+                req.locked_version,
+                &req.crate_config.changelog_window,
+                &content,
+                save_ctx.limits.max_file_size_kb * 1024,
+                &mut changelog_meta,
+            )?;
+            if let Some((json_name, json)) = json {
+                hasher.update(json.as_bytes());
+                total_bytes += json.len();
+                file_meta.push(FileMeta {
+                    path: json_name.clone(),
+                    bytes: json.len(),
+                    tokens: tokens.estimate(&json),
+                });
+                changelog_json.push(json_name);
+            }
+            content = windowed;
         }
 
-        if should_inject_header(&file.path) {
-            content = inject_header(
-                &content,
-                save_ctx.repo,
-                &save_ctx.resolved.git_ref,
-                &file.path,
-                save_ctx.resolved.is_fallback,
-                req.version,
-                &file.source_url,
+        if is_markdown_name(&file.path) {
+            let base = LinkBase {
+                repo: save_ctx.repo,
+                git_ref: &save_ctx.resolved.git_ref,
+                file_path: &file.path,
+                local_copies: &local_copies,
+            };
+            content = process_fetched_markdown(
+                content,
+                &flat_name,
+                req.crate_config,
+                &save_ctx.limits.markdown_cleanup,
+                &base,
+                &mut cleanup_meta,
             );
         }
 
-        let file_path = crate_dir.join(&flat_name);
-        let content_bytes = content.as_bytes();
-        fs::write(&file_path, content_bytes)?;
+        let fitted = fit_file(&flat_name, &content, &save_ctx.limits);
+        any_truncated |= fitted.truncated;
+        chunked.extend(fitted.chunked);
+
+        for (name, mut content) in fitted.outputs {
+            if should_inject_header(&file.path) {
+                content = inject_header(
+                    &content,
+                    save_ctx.repo,
+                    &save_ctx.resolved.git_ref,
+                    &file.path,
+                    save_ctx.resolved.is_fallback,
+                    req.version,
+                    &file.source_url,
+                );
+            }
+
+            let file_path = crate_dir.join(&name);
+            let content_bytes = content.as_bytes();
+            fs::write(&file_path, content_bytes)?;
 
-        hasher.update(content_bytes);
-        total_bytes += content_bytes.len();
+            hasher.update(content_bytes);
+            total_bytes += content_bytes.len();
+            file_meta.push(FileMeta {
+                path: name.clone(),
+                bytes: content_bytes.len(),
+                tokens: tokens.estimate(&content),
+            });
 
-        debug!("Saved: {:?}", file_path);
-        saved_names.push(flat_name);
+            debug!("Saved: {:?}", file_path);
+            saved_names.push(name);
+        }
     }
 
+    saved_names.extend(changelog_json);
+    saved_names.extend(save_chunk_manifest(
+        &crate_dir,
+        req.crate_name,
+        req.version,
+        &chunked,
+    )?);
     let artifact_sha256 = format!("{:x}", hasher.finalize());
 
     let meta = CrateMeta {
@@ -317,7 +650,7 @@ pub fn save_crate_files(
         git_ref: save_ctx.resolved.git_ref.clone(),
         fetched_at: Utc::now().format("%Y-%m-%d").to_string(),
         is_fallback: save_ctx.resolved.is_fallback,
-        config_hash: Some(req.crate_config.config_hash()),
+        config_hash: Some(req.crate_config.config_hash(&save_ctx.content)),
         source_kind: Some(save_ctx.source_kind.to_string()),
         artifact_path: save_ctx.artifact_path.map(str::to_string),
         docsrs_input_url: save_ctx.docsrs_input_url.map(str::to_string),
@@ -329,13 +662,23 @@ pub fn save_crate_files(
         truncation_marker: if any_truncated {
             Some(format!(
                 "[TRUNCATED by ai-fdocs at {}KB]",
-                save_ctx.max_file_size_kb
+                save_ctx.limits.max_file_size_kb
             ))
         } else {
             None
         },
         artifact_sha256: Some(artifact_sha256),
         artifact_bytes: Some(total_bytes),
+        artifact_tokens: Some(file_meta.iter().map(|f| f.tokens).sum()),
+        token_estimator: Some(save_ctx.limits.token_estimator.as_str().to_string()),
+        files: file_meta,
+        fallback_reason: save_ctx.fallback_reason.map(str::to_string),
+        repo_archived: save_ctx.repo_archived,
+        docsrs_pages: Vec::new(),
+        crates_io: save_ctx.crates_io.cloned(),
+        changelog: changelog_meta,
+        budget: None,
+        cleanup: cleanup_meta,
     };
 
     save_meta(&crate_dir.join(".aifd-meta.toml"), &meta)?;
@@ -358,30 +701,43 @@ pub fn save_crate_files(
     };
 
     let provenance = if save_ctx.source_kind == "github_fallback" {
-        Some(
-            "- Source kind: `github_fallback`\n- Reason: docs.rs failed and GitHub fallback was used",
-        )
+        match save_ctx.fallback_reason {
+            Some("docsrs_build_failed") => Some(
+                "- Source kind: `github_fallback`\n- Reason: docs.rs has no build for this version (`docsrs_build_failed`); GitHub fallback was used",
+            ),
+            _ => Some(
+                "- Source kind: `github_fallback`\n- Reason: docs.rs failed and GitHub fallback was used",
+            ),
+        }
     } else {
         None
     };
 
     fs::write(
         crate_dir.join("_SUMMARY.md"),
-        render_summary_with_provenance(&saved, provenance),
+        render_summary_with_provenance(
+            &saved,
+            provenance,
+            save_ctx.crates_io,
+            save_ctx.repo_archived,
+        ),
     )?;
 
     Ok(saved)
 }
 
+#[allow(clippy::too_many_arguments)]
 pub fn save_latest_api_markdown(
     output_dir: &Path,
     crate_name: &str,
     version: &str,
-    api_markdown: &str,
-    docsrs_input_url: &str,
-    truncated: bool,
-    max_file_size_kb: usize,
+    artifact: &DocsRsArtifact,
+    limits: &OutputLimits,
+    content: &ContentSettings,
     crate_config: &CrateDoc,
+    crates_io: Option<&CratesIoMetadata>,
+    repo_archived: bool,
+    changelog: Option<&RepoChangelog>,
 ) -> Result<SavedCrate> {
     let crate_dir = output_dir.join(format!("{crate_name}@{version}"));
 
@@ -390,12 +746,93 @@ pub fn save_latest_api_markdown(
     }
     fs::create_dir_all(&crate_dir)?;
 
-    fs::write(crate_dir.join("API.md"), api_markdown)?;
-
-    // Calculate SHA256
+    // Calculate SHA256 over API.md and every module file, in write order.
     use sha2::{Digest, Sha256};
     let mut hasher = Sha256::new();
-    hasher.update(api_markdown.as_bytes());
+    let mut total_bytes = 0;
+    let mut files = Vec::with_capacity(artifact.module_files.len() + 1);
+    let mut truncated = false;
+    let mut chunked = Vec::new();
+    let mut file_meta = Vec::new();
+    let mut changelog_meta: Option<ChangelogMeta> = None;
+    let tokens = estimator(limits.token_estimator);
+
+    let mut documents: Vec<(&str, String)> = std::iter::once(("API.md", artifact.markdown.clone()))
+        .chain(
+            artifact
+                .module_files
+                .iter()
+                .map(|f| (f.path.as_str(), f.markdown.clone())),
+        )
+        .collect();
+    let mut cleanup_meta = Vec::new();
+    let mut changelog_json = None;
+    if let Some(changelog) = changelog {
+        let (windowed, json) = window_changelog(
+            &crate_dir,
+            CHANGELOG_FILE,
+            crate_name,
+            version,
+            changelog.locked_version.as_deref(),
+            &crate_config.changelog_window,
+            &changelog.file.content,
+            limits.max_file_size_kb * 1024,
+            &mut changelog_meta,
+        )?;
+        changelog_json = json;
+        let local_copies =
+            HashMap::from([(changelog.file.path.clone(), CHANGELOG_FILE.to_string())]);
+        let base = LinkBase {
+            repo: &changelog.repo,
+            git_ref: &changelog.git_ref,
+            file_path: &changelog.file.path,
+            local_copies: &local_copies,
+        };
+        let content = process_fetched_markdown(
+            windowed,
+            CHANGELOG_FILE,
+            crate_config,
+            &limits.markdown_cleanup,
+            &base,
+            &mut cleanup_meta,
+        );
+        documents.push((CHANGELOG_FILE, content));
+    }
+    for (path, content) in documents {
+        // Already written by `window_changelog`; listed just before its Markdown.
+        let json = if path == CHANGELOG_FILE {
+            changelog_json.take()
+        } else {
+            None
+        };
+        if let Some((json_name, json)) = json {
+            hasher.update(json.as_bytes());
+            total_bytes += json.len();
+            file_meta.push(FileMeta {
+                path: json_name.clone(),
+                bytes: json.len(),
+                tokens: tokens.estimate(&json),
+            });
+            files.push(json_name);
+        }
+        let fitted = fit_file(path, &content, limits);
+        truncated |= fitted.truncated;
+        chunked.extend(fitted.chunked);
+        for (name, content) in fitted.outputs {
+            fs::write(crate_dir.join(&name), &content)?;
+            hasher.update(content.as_bytes());
+            total_bytes += content.len();
+            file_meta.push(FileMeta {
+                path: name.clone(),
+                bytes: content.len(),
+                tokens: tokens.estimate(&content),
+            });
+            files.push(name);
+        }
+    }
+    files.extend(save_chunk_manifest(
+        &crate_dir, crate_name, version, &chunked,
+    )?);
     let sha256 = format!("{:x}", hasher.finalize());
 
     let meta = CrateMeta {
@@ -404,10 +841,10 @@ pub fn save_latest_api_markdown(
         git_ref: format!("docsrs/{version}"),
         fetched_at: Utc::now().format("%Y-%m-%d").to_string(),
         is_fallback: false,
-        config_hash: Some(crate_config.config_hash()),
-        source_kind: Some("docsrs".to_string()),
+        config_hash: Some(crate_config.config_hash(content)),
+        source_kind: Some(artifact.source_kind.to_string()),
         artifact_path: Some("API.md".to_string()),
-        docsrs_input_url: Some(docsrs_input_url.to_string()),
+        docsrs_input_url: Some(artifact.docsrs_input_url.clone()),
         docsrs_canonical_base_url: Some(format!("https://docs.rs/{crate_name}/{version}")),
         upstream_latest_version: Some(version.to_string()),
         upstream_checked_at: Some(Utc::now().format("%Y-%m-%d").to_string()),
@@ -416,13 +853,30 @@ pub fn save_latest_api_markdown(
         truncation_marker: if truncated {
             Some(format!(
                 "[TRUNCATED by ai-fdocs at {}KB]",
-                max_file_size_kb
+                limits.max_file_size_kb
             ))
         } else {
             None
         }, // Fixed marker logic
         artifact_sha256: Some(sha256),
-        artifact_bytes: Some(api_markdown.len()),
+        artifact_bytes: Some(total_bytes),
+        artifact_tokens: Some(file_meta.iter().map(|f| f.tokens).sum()),
+        token_estimator: Some(limits.token_estimator.as_str().to_string()),
+        files: file_meta,
+        fallback_reason: None,
+        repo_archived,
+        docsrs_pages: artifact
+            .pages
+            .iter()
+            .map(|p| DocsRsPageMeta {
+                url: p.url.clone(),
+                artifact_path: p.artifact_path.clone(),
+            })
+            .collect(),
+        crates_io: crates_io.cloned(),
+        changelog: changelog_meta,
+        budget: None,
+        cleanup: cleanup_meta,
     };
 
     save_meta(&crate_dir.join(".aifd-meta.toml"), &meta)?;
@@ -432,14 +886,18 @@ pub fn save_latest_api_markdown(
         version: version.to_string(),
         git_ref: format!("docsrs/{version}"),
         is_fallback: false,
-        files: vec!["API.md".to_string()],
+        files,
         ai_notes: crate_config.ai_notes.clone(),
     };
 
-    let provenance = latest_docs_summary_provenance("docsrs", docsrs_input_url, truncated);
+    let mut provenance =
+        latest_docs_summary_provenance(artifact.source_kind, &artifact.docsrs_input_url, truncated);
+    if artifact.pages.len() > 1 {
+        provenance.push_str(&format!("\n- Pages crawled: `{}`", artifact.pages.len()));
+    }
     fs::write(
         crate_dir.join("_SUMMARY.md"),
-        render_summary_with_provenance(&saved, Some(&provenance)),
+        render_summary_with_provenance(&saved, Some(&provenance), crates_io, repo_archived),
     )?;
     Ok(saved)
 }
@@ -447,12 +905,15 @@ pub fn save_latest_api_markdown(
 pub struct SaveContext<'a> {
     pub repo: &'a str,
     pub resolved: &'a ResolvedRef,
-    pub max_file_size_kb: usize,
+    pub limits: OutputLimits,
+    pub content: ContentSettings,
     pub source_kind: &'a str,
     pub artifact_path: Option<&'a str>,
     pub docsrs_input_url: Option<&'a str>,
     pub upstream_latest_version: Option<&'a str>,
-    pub truncated: Option<bool>,
+    pub fallback_reason: Option<&'a str>,
+    pub crates_io: Option<&'a CratesIoMetadata>,
+    pub repo_archived: bool,
 }
 
 pub fn read_meta(output_dir: &Path, crate_name: &str, version: &str) -> Option<CrateMeta> {
@@ -495,6 +956,277 @@ pub fn read_cached_info(
     })
 }
 
+const ADVISORIES_HEADING: &str = "## Security advisories\n";
+
+/// Write (or remove) `ADVISORIES.md` for a synced crate and keep the
+/// `## Security advisories` notice in its `_SUMMARY.md` in step. Runs after
+/// every sync, cached crates included, so a newer advisory-db checkout applies.
+pub fn update_advisories(
+    output_dir: &Path,
+    saved: &mut SavedCrate,
+    advisories: &[Advisory],
+    db_root: &Path,
+) -> Result<()> {
+    let crate_dir = output_dir.join(format!("{}@{}", saved.name, saved.version));
+    let advisories_path = crate_dir.join(ADVISORIES_FILE);
+
+    let notice = if advisories.is_empty() {
+        if advisories_path.exists() {
+            fs::remove_file(&advisories_path)?;
+        }
+        saved.files.retain(|f| f != ADVISORIES_FILE);
+        None
+    } else {
+        fs::write(
+            &advisories_path,
+            render_advisories_markdown(&saved.name, &saved.version, advisories, db_root),
+        )?;
+        if !saved.files.iter().any(|f| f == ADVISORIES_FILE) {
+            saved.files.push(ADVISORIES_FILE.to_string());
+        }
+        let ids: Vec<&str> = advisories.iter().map(|a| a.id.as_str()).collect();
+        Some(format!(
+            "{ADVISORIES_HEADING}\n- ⚠️ **RustSec advisories affect this version:** {}. See [{ADVISORIES_FILE}]({ADVISORIES_FILE}) before using the affected APIs.\n\n",
+            ids.join(", ")
+        ))
+    };
+
+    let summary_path = crate_dir.join("_SUMMARY.md");
+    if let Ok(summary) = fs::read_to_string(&summary_path) {
+        let updated = splice_summary_notice(&summary, ADVISORIES_HEADING, notice.as_deref());
+        if updated != summary {
+            fs::write(&summary_path, updated)?;
+        }
+    }
+    Ok(())
+}
+
+const API_DIFF_HEADING: &str = "## API changes in newer releases\n";
+
+/// Whether a synced crate already holds an `API_DIFF.md` starting with `title`,
+/// i.e. one computed against the same pair of versions.
+pub fn has_api_diff(output_dir: &Path, saved: &SavedCrate, title: &str) -> bool {
+    fs::read_to_string(
+        output_dir
+            .join(format!("{}@{}", saved.name, saved.version))
+            .join(API_DIFF_FILE),
+    )
+    .is_ok_and(|existing| existing.lines().next() == Some(title))
+}
+
+/// Write (or remove) `API_DIFF.md` for a synced crate and keep the
+/// `## API changes in newer releases` notice in its `_SUMMARY.md` in step.
+pub fn update_api_diff(
+    output_dir: &Path,
+    saved: &mut SavedCrate,
+    markdown: Option<&str>,
+) -> Result<()> {
+    let crate_dir = output_dir.join(format!("{}@{}", saved.name, saved.version));
+    let diff_path = crate_dir.join(API_DIFF_FILE);
+
+    let notice = match markdown {
+        None => {
+            if diff_path.exists() {
+                fs::remove_file(&diff_path)?;
+            }
+            saved.files.retain(|f| f != API_DIFF_FILE);
+            None
+        }
+        Some(markdown) => {
+            fs::write(&diff_path, markdown)?;
+            if !saved.files.iter().any(|f| f == API_DIFF_FILE) {
+                saved.files.push(API_DIFF_FILE.to_string());
+            }
+            Some(format!(
+                "{API_DIFF_HEADING}\n- Newer releases change the public API. See [{API_DIFF_FILE}]({API_DIFF_FILE}) for what the locked {} lacks before suggesting functions from recent docs.\n\n",
+                saved.version
+            ))
+        }
+    };
+
+    let summary_path = crate_dir.join("_SUMMARY.md");
+    if let Ok(summary) = fs::read_to_string(&summary_path) {
+        let updated = splice_summary_notice(&summary, API_DIFF_HEADING, notice.as_deref());
+        if updated != summary {
+            fs::write(&summary_path, updated)?;
+        }
+    }
+    Ok(())
+}
+
+const BUDGET_HEADING: &str = "## Trimmed to fit the context budget\n";
+
+/// Record a crate's share of the total budget and the resulting file sizes in
+/// its meta and keep the
+/// `## Trimmed to fit the context budget` notice in its `_SUMMARY.md` in step.
+pub fn update_budget_notice(
+    output_dir: &Path,
+    saved: &SavedCrate,
+    budget: Option<BudgetMeta>,
+    token_estimator: TokenEstimatorKind,
+) -> Result<()> {
+    let crate_dir = output_dir.join(format!("{}@{}", saved.name, saved.version));
+    let meta_path = crate_dir.join(".aifd-meta.toml");
+    if let Some(mut meta) = load_meta_with_migration(&meta_path) {
+        let files = meta.files.clone();
+        let sha256 = meta.artifact_sha256.clone();
+        remeasure_files(&crate_dir, &mut meta, token_estimator);
+        if meta.budget != budget || meta.files != files || meta.artifact_sha256 != sha256 {
+            meta.budget.clone_from(&budget);
+            save_meta(&meta_path, &meta)?;
+        }
+    }
+
+    let notice = budget.map(|budget| {
+        let amount = |n: usize| {
+            if budget.unit == "tokens" {
+                format!("~{n} tokens")
+            } else {
+                format!("{:.1} KB", n as f64 / 1024.0)
+            }
+        };
+        let files: Vec<String> = budget
+            .trimmed_files
+            .iter()
+            .map(|f| format!("[{f}]({f})"))
+            .collect();
+        format!(
+            "{BUDGET_HEADING}\n- The project-wide budget ({}) left this crate {} of {} (priority {}). Trimmed: {}. Each file lists the sections cut at its end.\n\n",
+            budget.limit,
+            amount(budget.allocated),
+            amount(budget.demand),
+            budget.priority,
+            files.join(", ")
+        )
+    });
+    let summary_path = crate_dir.join("_SUMMARY.md");
+    if let Ok(summary) = fs::read_to_string(&summary_path) {
+        let updated = splice_summary_notice(&summary, BUDGET_HEADING, notice.as_deref());
+        if updated != summary {
+            fs::write(&summary_path, updated)?;
+        }
+    }
+    Ok(())
+}
+
+/// Re-read the files listed in `meta.files` after they were rewritten in place
+/// and recompute their sizes, the artifact totals and the hash, in list order
+/// as at save time. Files that can no longer be read keep their entry.
+fn remeasure_files(crate_dir: &Path, meta: &mut CrateMeta, token_estimator: TokenEstimatorKind) {
+    use sha2::{Digest, Sha256};
+    let tokens = estimator(token_estimator);
+    let mut hasher = Sha256::new();
+    for file in &mut meta.files {
+        let Ok(content) = fs::read_to_string(crate_dir.join(&file.path)) else {
+            continue;
+        };
+        hasher.update(content.as_bytes());
+        file.bytes = content.len();
+        file.tokens = tokens.estimate(&content);
+    }
+    if meta.files.is_empty() {
+        return;
+    }
+    meta.artifact_sha256 = Some(format!("{:x}", hasher.finalize()));
+    meta.artifact_bytes = Some(meta.files.iter().map(|f| f.bytes).sum());
+    meta.artifact_tokens = Some(meta.files.iter().map(|f| f.tokens).sum());
+    meta.token_estimator = Some(token_estimator.as_str().to_string());
+}
+
+const USAGE_HEADING: &str = "## Used in this project\n";
+
+/// Paths listed in the usage notice; the rest are only counted.
+const MAX_USED_PATHS: usize = 20;
+
+/// Keep the `## Used in this project` notice in a crate's `_SUMMARY.md` in step
+/// with the paths the project's source refers to.
+pub fn update_usage_notice(output_dir: &Path, saved: &SavedCrate, used: &[UsedPath]) -> Result<()> {
+    let notice = (!used.is_empty()).then(|| {
+        let mut notice = format!("{USAGE_HEADING}\n");
+        for path in used.iter().take(MAX_USED_PATHS) {
+            notice.push_str(&format!(
+                "- `{}` ({} file{})\n",
+                path.path,
+                path.files,
+                if path.files == 1 { "" } else { "s" }
+            ));
+        }
+        if used.len() > MAX_USED_PATHS {
+            notice.push_str(&format!("- … and {} more\n", used.len() - MAX_USED_PATHS));
+        }
+        notice.push('\n');
+        notice
+    });
+
+    let summary_path = output_dir
+        .join(format!("{}@{}", saved.name, saved.version))
+        .join("_SUMMARY.md");
+    if let Ok(summary) = fs::read_to_string(&summary_path) {
+        let updated = splice_summary_notice(&summary, USAGE_HEADING, notice.as_deref());
+        if updated != summary {
+            fs::write(&summary_path, updated)?;
+        }
+    }
+    Ok(())
+}
+
+const UPDATE_HEADING: &str = "## Newer version available\n";
+
+/// Keep the `## Newer version available` notice in a synced crate's
+/// `_SUMMARY.md` in step with the releases found in the sparse index.
+pub fn update_upstream_notice(
+    output_dir: &Path,
+    saved: &SavedCrate,
+    upstream: &UpstreamVersions,
+) -> Result<()> {
+    let summary_path = output_dir
+        .join(format!("{}@{}", saved.name, saved.version))
+        .join("_SUMMARY.md");
+    let Ok(summary) = fs::read_to_string(&summary_path) else {
+        return Ok(());
+    };
+
+    let notice = upstream.newer_than(&saved.version).map(|newer| {
+        let mut line = format!(
+            "{UPDATE_HEADING}\n- These docs describe the locked {}; {newer} is published.",
+            saved.version
+        );
+        if let Some(compatible) = upstream
+            .latest_compatible
+            .as_deref()
+            .filter(|c| *c != newer && *c != saved.version)
+        {
+            line.push_str(&format!(" Latest semver-compatible release: {compatible}."));
+        }
+        line.push_str(" APIs added after the locked version are not available here.\n\n");
+        line
+    });
+
+    let updated = splice_summary_notice(&summary, UPDATE_HEADING, notice.as_deref());
+    if updated != summary {
+        fs::write(&summary_path, updated)?;
+    }
+    Ok(())
+}
+
+/// Replace the `heading` section of a summary, placed before the first `##` section.
+fn splice_summary_notice(summary: &str, heading: &str, notice: Option<&str>) -> String {
+    let mut text = summary.to_string();
+    if let Some(start) = text.find(heading) {
+        let after = start + heading.len();
+        let end = text[after..]
+            .find("\n## ")
+            .map_or(text.len(), |i| after + i + 1);
+        text.replace_range(start..end, "");
+    }
+
+    if let Some(notice) = notice {
+        let at = text.find("\n## ").map_or(text.len(), |i| i + 1);
+        text.insert_str(at, notice);
+    }
+    text
+}
+
 pub fn prune(
     output_dir: &Path,
     config: &Config,
@@ -525,7 +1257,7 @@ pub fn prune(
         let should_remove = if configured.contains(crate_name) {
             lock_versions
                 .get(crate_name)
-                .is_none_or(|lock_ver| lock_ver != dir_version)
+                .is_none_or(|lock_ver| !crate::version::same_release(lock_ver, dir_version))
         } else {
             true
         };
@@ -539,14 +1271,276 @@ pub fn prune(
     Ok(())
 }
 
-fn split_name_version(dir_name: &str) -> Option<(&str, &str)> {
+/// Split a `{crate}@{version}` output directory name; directories whose
+/// suffix is not a version are not crate docs and are left alone.
+pub fn split_name_version(dir_name: &str) -> Option<(&str, &str)> {
     let (name, version) = dir_name.rsplit_once('@')?;
-    if name.is_empty() || version.is_empty() {
+    if name.is_empty() || crate::version::parse(version).is_none() {
         return None;
     }
     Some((name, version))
 }
 
+/// What the last lockfile sync left behind, per crate: the version it synced
+/// and, when that sync moved the crate, the version it moved from.
+#[derive(serde::Serialize, serde::Deserialize, Debug, Clone, Default, PartialEq, Eq)]
+pub struct SyncState {
+    #[serde(default)]
+    pub crates: BTreeMap<String, SyncedCrateState>,
+}
+
+#[derive(serde::Serialize, serde::Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct SyncedCrateState {
+    pub version: String,
+    /// Version before the move that produced the crate's `UPGRADE.md`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub upgraded_from: Option<String>,
+}
+
+/// Where a synced crate's `UPGRADE.md` starts.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct UpgradeOrigin {
+    pub from: String,
+    /// The version changed in this sync (as opposed to an earlier one).
+    pub moved_now: bool,
+}
+
+impl SyncState {
+    /// The version the last sync recorded when `version` differs from it, else
+    /// the origin of the move that brought the crate to `version`.
+    pub fn upgrade_origin(&self, crate_name: &str, version: &str) -> Option<UpgradeOrigin> {
+        let recorded = self.crates.get(crate_name)?;
+        if !crate::version::same_release(&recorded.version, version) {
+            return Some(UpgradeOrigin {
+                from: recorded.version.clone(),
+                moved_now: true,
+            });
+        }
+        recorded.upgraded_from.clone().map(|from| UpgradeOrigin {
+            from,
+            moved_now: false,
+        })
+    }
+}
+
+/// State of the last lockfile sync. Output directories from before the state
+/// file existed fall back to the highest version folder per crate.
+pub fn read_sync_state(output_dir: &Path) -> SyncState {
+    match fs::read_to_string(output_dir.join(SYNC_STATE_FILE)) {
+        Ok(raw) => toml::from_str(&raw).unwrap_or_default(),
+        Err(_) => SyncState {
+            crates: synced_versions(output_dir)
+                .into_iter()
+                .map(|(name, version)| {
+                    let state = SyncedCrateState {
+                        version,
+                        upgraded_from: None,
+                    };
+                    (name, state)
+                })
+                .collect(),
+        },
+    }
+}
+
+pub fn write_sync_state(output_dir: &Path, state: &SyncState) -> Result<()> {
+    let content = toml::to_string_pretty(state)
+        .map_err(|e| AiDocsError::Other(format!("Failed to serialize sync state: {e}")))?;
+    fs::create_dir_all(output_dir)?;
+    fs::write(output_dir.join(SYNC_STATE_FILE), content)?;
+    Ok(())
+}
+
+/// Highest synced version per crate in the output directory.
+fn synced_versions(output_dir: &Path) -> HashMap<String, String> {
+    let mut versions: HashMap<String, String> = HashMap::new();
+    let Ok(entries) = fs::read_dir(output_dir) else {
+        return versions;
+    };
+    for entry in entries.flatten() {
+        if !entry.path().is_dir() {
+            continue;
+        }
+        let dir_name = entry.file_name().to_string_lossy().to_string();
+        let Some((name, version)) = split_name_version(&dir_name) else {
+            continue;
+        };
+        if crate::utils::is_version_better(version, versions.get(name).map(String::as_str)) {
+            versions.insert(name.to_string(), version.to_string());
+        }
+    }
+    versions
+}
+
+/// Changelog entries from the `*CHANGELOG*.json` files of a synced crate.
+pub fn read_changelog_entries(output_dir: &Path, saved: &SavedCrate) -> Vec<ChangelogEntry> {
+    #[derive(serde::Deserialize)]
+    struct StoredChangelog {
+        entries: Vec<ChangelogEntry>,
+    }
+
+    let crate_dir = output_dir.join(format!("{}@{}", saved.name, saved.version));
+    saved
+        .files
+        .iter()
+        .filter(|f| f.ends_with(".json") && f.to_lowercase().contains("changelog"))
+        .filter_map(|f| fs::read_to_string(crate_dir.join(f)).ok())
+        .filter_map(|raw| serde_json::from_str::<StoredChangelog>(&raw).ok())
+        .flat_map(|stored| stored.entries)
+        .collect()
+}
+
+/// Write `UPGRADE.md` into a synced crate directory, list it in its files and
+/// record it in the crate's meta.
+pub fn write_upgrade_brief(
+    output_dir: &Path,
+    saved: &mut SavedCrate,
+    markdown: &str,
+    token_estimator: TokenEstimatorKind,
+) -> Result<()> {
+    let crate_dir = output_dir.join(format!("{}@{}", saved.name, saved.version));
+    fs::write(crate_dir.join(UPGRADE_FILE), markdown)?;
+    if !saved.files.iter().any(|f| f == UPGRADE_FILE) {
+        saved.files.push(UPGRADE_FILE.to_string());
+    }
+
+    let meta_path = crate_dir.join(".aifd-meta.toml");
+    if let Some(mut meta) = load_meta_with_migration(&meta_path) {
+        let file = FileMeta {
+            path: UPGRADE_FILE.to_string(),
+            bytes: markdown.len(),
+            tokens: estimator(token_estimator).estimate(markdown),
+        };
+        meta.files.retain(|f| f.path != UPGRADE_FILE);
+        meta.files.push(file);
+        save_meta(&meta_path, &meta)?;
+    }
+    Ok(())
+}
+
+/// Write `_UPGRADES.md`, or remove a stale one when nothing moved in this sync.
+pub fn write_upgrades_index(output_dir: &Path, markdown: Option<&str>) -> Result<()> {
+    let path = output_dir.join(UPGRADES_INDEX_FILE);
+    match markdown {
+        Some(markdown) => {
+            fs::create_dir_all(output_dir)?;
+            fs::write(path, markdown)?;
+        }
+        None if path.exists() => fs::remove_file(path)?,
+        None => {}
+    }
+    Ok(())
+}
+
+/// Save a fetched changelog's entries to `CHANGELOG.json` and cut its Markdown
+/// to `window`, adding the kept and omitted versions to `changelog_meta`.
+/// Returns the windowed Markdown and the JSON file written, if any.
+#[allow(clippy::too_many_arguments)]
+fn window_changelog(
+    crate_dir: &Path,
+    markdown_name: &str,
+    crate_name: &str,
+    version: &str,
+    locked_version: Option<&str>,
+    window: &ChangelogWindow,
+    content: &str,
+    max_bytes: usize,
+    changelog_meta: &mut Option<ChangelogMeta>,
+) -> Result<(String, Option<(String, String)>)> {
+    let json = save_changelog_json(
+        crate_dir,
+        markdown_name,
+        crate_name,
+        version,
+        content,
+        max_bytes,
+    )?;
+    let truncated =
+        changelog::truncate_changelog(content, version, window, locked_version.unwrap_or(version));
+    if !truncated.kept_versions.is_empty() || truncated.omitted_versions > 0 {
+        let meta = changelog_meta.get_or_insert_with(|| ChangelogMeta {
+            window: window.as_config_str(),
+            kept_versions: Vec::new(),
+            omitted_versions: 0,
+        });
+        meta.kept_versions.extend(truncated.kept_versions);
+        meta.omitted_versions += truncated.omitted_versions;
+    }
+    Ok((truncated.content, json))
+}
+
+/// Write the structured entries of a full (untruncated) changelog as JSON next
+/// to its Markdown (`CHANGELOG.md` -> `CHANGELOG.json`), dropping the oldest
+/// entries beyond `max_bytes`. Returns the file name and its content, or `None`
+/// when no version headings were found.
+fn save_changelog_json(
+    crate_dir: &Path,
+    markdown_name: &str,
+    crate_name: &str,
+    version: &str,
+    content: &str,
+    max_bytes: usize,
+) -> Result<Option<(String, String)>> {
+    let entries = changelog::parse_changelog(content);
+    if entries.is_empty() {
+        return Ok(None);
+    }
+
+    let stem = markdown_name
+        .rsplit_once('.')
+        .map_or(markdown_name, |(stem, _)| stem);
+    let json_name = format!("{stem}.json");
+    let render = |kept: usize| {
+        let file = changelog::ChangelogFile {
+            crate_name,
+            version,
+            entries: &entries[..kept],
+            omitted_entries: (kept < entries.len()).then(|| entries.len() - kept),
+        };
+        serde_json::to_string_pretty(&file)
+            .map_err(|e| AiDocsError::Other(format!("failed to serialize {json_name}: {e}")))
+    };
+
+    // Largest newest-first prefix that fits; the newest entry is always kept.
+    let mut json = render(entries.len())?;
+    if json.len() > max_bytes {
+        let (mut fits, mut too_big) = (1, entries.len());
+        while too_big - fits > 1 {
+            let mid = (fits + too_big) / 2;
+            if render(mid)?.len() <= max_bytes {
+                fits = mid;
+            } else {
+                too_big = mid;
+            }
+        }
+        json = render(fits)?;
+    }
+    fs::write(crate_dir.join(&json_name), &json)?;
+    Ok(Some((json_name, json)))
+}
+
+/// Write `_CHUNKS.json` when any document of the crate was chunked.
+fn save_chunk_manifest(
+    crate_dir: &Path,
+    crate_name: &str,
+    version: &str,
+    chunked: &[ChunkedFile],
+) -> Result<Option<String>> {
+    if chunked.is_empty() {
+        return Ok(None);
+    }
+    let manifest = ChunkManifest {
+        crate_name,
+        version,
+        files: chunked,
+    };
+    let json = serde_json::to_string_pretty(&manifest).map_err(|e| {
+        AiDocsError::Other(format!("failed to serialize {CHUNKS_MANIFEST_FILE}: {e}"))
+    })?;
+    fs::write(crate_dir.join(CHUNKS_MANIFEST_FILE), json)?;
+    Ok(Some(CHUNKS_MANIFEST_FILE.to_string()))
+}
+
 pub fn rust_output_dir(base_output_dir: &Path) -> PathBuf {
     if base_output_dir.file_name().and_then(|n| n.to_str()) == Some("rust") {
         return base_output_dir.to_path_buf();
@@ -559,6 +1553,304 @@ mod tests {
     use super::*;
     use crate::config::CrateDoc;
 
+    #[test]
+    fn updates_advisories_file_and_summary_notice() {
+        let tmp = std::env::temp_dir().join(format!("ai-fdocs-advisories-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&tmp);
+        let crate_dir = tmp.join("time@0.2.22");
+        fs::create_dir_all(&crate_dir).expect("mkdir");
+        let summary = "<!-- auto -->\n\n# time@0.2.22\n\n## Files\n\n- [README.md](README.md)\n";
+        fs::write(crate_dir.join("_SUMMARY.md"), summary).expect("write summary");
+
+        let mut saved = SavedCrate {
+            name: "time".to_string(),
+            version: "0.2.22".to_string(),
+            git_ref: "v0.2.22".to_string(),
+            is_fallback: false,
+            files: vec!["README.md".to_string()],
+            ai_notes: String::new(),
+        };
+        let advisory = Advisory {
+            id: "RUSTSEC-2020-0071".to_string(),
+            title: "Potential segfault".to_string(),
+            description: String::new(),
+            date: "2020-11-18".to_string(),
+            url: None,
+            aliases: Vec::new(),
+            informational: None,
+            withdrawn: false,
+            functions: Default::default(),
+            patched: vec![">= 0.2.23".to_string()],
+            unaffected: Vec::new(),
+        };
+
+        let advisories = [advisory];
+        update_advisories(&tmp, &mut saved, &advisories, Path::new("/db"))
+            .expect("write advisories");
+        update_advisories(&tmp, &mut saved, &advisories, Path::new("/db"))
+            .expect("rewrite advisories");
+        assert_eq!(saved.files, vec!["README.md", "ADVISORIES.md"]);
+        assert!(crate_dir.join("ADVISORIES.md").exists());
+        let with_notice = fs::read_to_string(crate_dir.join("_SUMMARY.md")).expect("read");
+        assert_eq!(with_notice.matches("## Security advisories").count(), 1);
+        assert!(with_notice.contains("# time@0.2.22\n\n## Security advisories\n\n- ⚠️ **RustSec advisories affect this version:** RUSTSEC-2020-0071."));
+        assert!(with_notice.ends_with("## Files\n\n- [README.md](README.md)\n"));
+
+        update_advisories(&tmp, &mut saved, &[], Path::new("/db")).expect("clear advisories");
+        assert_eq!(saved.files, vec!["README.md"]);
+        assert!(!crate_dir.join("ADVISORIES.md").exists());
+        assert_eq!(
+            fs::read_to_string(crate_dir.join("_SUMMARY.md")).expect("read"),
+            summary
+        );
+
+        let _ = fs::remove_dir_all(&tmp);
+    }
+
+    #[test]
+    fn writes_and_clears_api_diff_with_summary_notice() {
+        let tmp = std::env::temp_dir().join(format!("ai-fdocs-api-diff-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&tmp);
+        let crate_dir = tmp.join("demo@0.3.1");
+        fs::create_dir_all(&crate_dir).expect("mkdir");
+        let summary = "<!-- auto -->\n\n# demo@0.3.1\n\n## Files\n\n- [API.md](API.md)\n";
+        fs::write(crate_dir.join("_SUMMARY.md"), summary).expect("write summary");
+
+        let mut saved = SavedCrate {
+            name: "demo".to_string(),
+            version: "0.3.1".to_string(),
+            git_ref: "v0.3.1".to_string(),
+            is_fallback: false,
+            files: vec!["API.md".to_string()],
+            ai_notes: String::new(),
+        };
+        let title = "# demo@0.3.1 — API changes up to 0.4.0";
+        assert!(!has_api_diff(&tmp, &saved, title));
+
+        update_api_diff(&tmp, &mut saved, Some(&format!("{title}\n\nbody\n"))).expect("write diff");
+        assert!(has_api_diff(&tmp, &saved, title));
+        assert!(!has_api_diff(
+            &tmp,
+            &saved,
+            "# demo@0.3.1 — API changes up to 0.5.0"
+        ));
+        assert_eq!(saved.files, vec!["API.md", "API_DIFF.md"]);
+        let with_notice = fs::read_to_string(crate_dir.join("_SUMMARY.md")).expect("read");
+        assert!(with_notice.contains("## API changes in newer releases\n\n- Newer releases change the public API. See [API_DIFF.md](API_DIFF.md) for what the locked 0.3.1 lacks"));
+
+        update_api_diff(&tmp, &mut saved, None).expect("clear diff");
+        assert_eq!(saved.files, vec!["API.md"]);
+        assert!(!crate_dir.join("API_DIFF.md").exists());
+        assert_eq!(
+            fs::read_to_string(crate_dir.join("_SUMMARY.md")).expect("read"),
+            summary
+        );
+
+        let _ = fs::remove_dir_all(&tmp);
+    }
+
+    #[test]
+    fn writes_and_clears_newer_version_notice() {
+        let tmp =
+            std::env::temp_dir().join(format!("ai-fdocs-update-notice-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&tmp);
+        let crate_dir = tmp.join("tokio@1.38.0");
+        fs::create_dir_all(&crate_dir).expect("mkdir");
+        let summary = "<!-- auto -->\n\n# tokio@1.38.0\n\n## Files\n\n- [README.md](README.md)\n";
+        fs::write(crate_dir.join("_SUMMARY.md"), summary).expect("write summary");
+
+        let saved = SavedCrate {
+            name: "tokio".to_string(),
+            version: "1.38.0".to_string(),
+            git_ref: "tokio-1.38.0".to_string(),
+            is_fallback: false,
+            files: vec!["README.md".to_string()],
+            ai_notes: String::new(),
+        };
+        let upstream = UpstreamVersions {
+            latest_stable: Some("2.0.0".to_string()),
+            latest_compatible: Some("1.44.0".to_string()),
+            latest: Some("2.1.0-rc.1".to_string()),
+        };
+
+        update_upstream_notice(&tmp, &saved, &upstream).expect("write notice");
+        update_upstream_notice(&tmp, &saved, &upstream).expect("rewrite notice");
+        let with_notice = fs::read_to_string(crate_dir.join("_SUMMARY.md")).expect("read");
+        assert_eq!(with_notice.matches("## Newer version available").count(), 1);
+        assert!(with_notice.contains(
+            "- These docs describe the locked 1.38.0; 2.0.0 is published. Latest semver-compatible release: 1.44.0."
+        ));
+        assert!(with_notice.ends_with("## Files\n\n- [README.md](README.md)\n"));
+
+        let current = UpstreamVersions {
+            latest_stable: Some("1.38.0".to_string()),
+            latest_compatible: Some("1.38.0".to_string()),
+            latest: Some("1.38.0".to_string()),
+        };
+        update_upstream_notice(&tmp, &saved, &current).expect("clear notice");
+        assert_eq!(
+            fs::read_to_string(crate_dir.join("_SUMMARY.md")).expect("read"),
+            summary
+        );
+
+        let _ = fs::remove_dir_all(&tmp);
+    }
+
+    #[test]
+    fn writes_changelog_json_next_to_markdown() {
+        let tmp =
+            std::env::temp_dir().join(format!("ai-fdocs-changelog-json-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&tmp);
+        fs::create_dir_all(&tmp).expect("mkdir");
+
+        let changelog = "# Changelog\n\n## 1.1.0 - 2024-02-01\n### Breaking\n- Removed `old`\n\n## 1.0.0\n- Initial\n";
+        let (name, _) = save_changelog_json(
+            &tmp,
+            "docs__CHANGELOG.md",
+            "demo",
+            "1.1.0",
+            changelog,
+            1 << 20,
+        )
+        .expect("write json")
+        .expect("entries");
+        assert_eq!(name, "docs__CHANGELOG.json");
+
+        let json: serde_json::Value =
+            serde_json::from_str(&fs::read_to_string(tmp.join("docs__CHANGELOG.json")).unwrap())
+                .expect("valid json");
+        assert_eq!(json["crate"], "demo");
+        assert_eq!(json["entries"][0]["version"], "1.1.0");
+        assert_eq!(json["entries"][0]["date"], "2024-02-01");
+        assert_eq!(json["entries"][0]["sections"][0]["kind"], "breaking");
+        assert_eq!(json["entries"][1]["sections"][0]["items"][0], "Initial");
+        assert!(json.get("omitted_entries").is_none());
+
+        assert_eq!(
+            save_changelog_json(
+                &tmp,
+                "CHANGELOG.md",
+                "demo",
+                "1.1.0",
+                "no versions",
+                1 << 20
+            )
+            .unwrap(),
+            None
+        );
+
+        let long: String = (0..200)
+            .map(|minor| format!("## 1.{minor}.0\n- Change number {minor} with some detail\n\n"))
+            .rev()
+            .collect();
+        let (_, capped) = save_changelog_json(&tmp, "CHANGELOG.md", "demo", "1.199.0", &long, 4096)
+            .expect("write json")
+            .expect("entries");
+        assert!(capped.len() <= 4096);
+        let capped: serde_json::Value = serde_json::from_str(&capped).expect("valid json");
+        let kept = capped["entries"].as_array().expect("entries").len();
+        assert!(kept > 1 && kept < 200);
+        assert_eq!(capped["entries"][0]["version"], "1.199.0");
+        assert_eq!(capped["omitted_entries"], 200 - kept);
+
+        let _ = fs::remove_dir_all(&tmp);
+    }
+
+    #[test]
+    fn tracks_synced_versions_and_writes_upgrade_files() {
+        let tmp = std::env::temp_dir().join(format!("ai-fdocs-upgrades-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&tmp);
+        for dir in [
+            "demo@1.0.0",
+            "demo@1.10.0",
+            "demo@1.9.0",
+            "other@0.1.0",
+            "notes",
+        ] {
+            fs::create_dir_all(tmp.join(dir)).expect("mkdir");
+        }
+        let legacy = read_sync_state(&tmp);
+        assert_eq!(legacy.crates.len(), 2);
+        assert_eq!(legacy.crates["demo"].version, "1.10.0");
+        assert_eq!(legacy.crates["other"].version, "0.1.0");
+
+        // The last sync locked demo 1.0.0 next to a newer leftover folder.
+        let mut state = SyncState::default();
+        state.crates.insert(
+            "demo".to_string(),
+            SyncedCrateState {
+                version: "1.0.0".to_string(),
+                upgraded_from: Some("1.10.0".to_string()),
+            },
+        );
+        write_sync_state(&tmp, &state).expect("write state");
+        let state = read_sync_state(&tmp);
+        assert_eq!(
+            state.upgrade_origin("demo", "1.0.0"),
+            Some(UpgradeOrigin {
+                from: "1.10.0".to_string(),
+                moved_now: false,
+            })
+        );
+        assert_eq!(
+            state.upgrade_origin("demo", "1.10.0"),
+            Some(UpgradeOrigin {
+                from: "1.0.0".to_string(),
+                moved_now: true,
+            })
+        );
+        assert_eq!(state.upgrade_origin("other", "0.1.0"), None);
+
+        let crate_dir = tmp.join("demo@1.10.0");
+        let (json_name, _) = save_changelog_json(
+            &crate_dir,
+            "CHANGELOG.md",
+            "demo",
+            "1.10.0",
+            "## 1.10.0\n- New\n\n## 1.9.0\n- Old\n",
+            1 << 20,
+        )
+        .expect("write json")
+        .expect("entries");
+        let mut saved = SavedCrate {
+            name: "demo".to_string(),
+            version: "1.10.0".to_string(),
+            git_ref: "v1.10.0".to_string(),
+            is_fallback: false,
+            files: vec!["CHANGELOG.md".to_string(), json_name],
+            ai_notes: String::new(),
+        };
+        let entries = read_changelog_entries(&tmp, &saved);
+        assert_eq!(entries.len(), 2);
+        assert_eq!(entries[0].version, "1.10.0");
+
+        fs::write(
+            crate_dir.join(".aifd-meta.toml"),
+            "schema_version = 2\nversion = \"1.10.0\"\n",
+        )
+        .expect("write meta");
+        let estimator = TokenEstimatorKind::default();
+        write_upgrade_brief(&tmp, &mut saved, "# demo\n", estimator).expect("write brief");
+        write_upgrade_brief(&tmp, &mut saved, "# demo\n", estimator).expect("rewrite brief");
+        assert!(crate_dir.join(UPGRADE_FILE).exists());
+        assert_eq!(saved.files.iter().filter(|f| *f == UPGRADE_FILE).count(), 1);
+        let meta = read_meta(&tmp, "demo", "1.10.0").expect("meta");
+        let recorded: Vec<_> = meta
+            .files
+            .iter()
+            .filter(|f| f.path == UPGRADE_FILE)
+            .collect();
+        assert_eq!(recorded.len(), 1);
+        assert_eq!(recorded[0].bytes, "# demo\n".len());
+
+        write_upgrades_index(&tmp, Some("# index\n")).expect("write index");
+        assert!(tmp.join(UPGRADES_INDEX_FILE).exists());
+        write_upgrades_index(&tmp, None).expect("clear index");
+        assert!(!tmp.join(UPGRADES_INDEX_FILE).exists());
+
+        let _ = fs::remove_dir_all(&tmp);
+    }
+
     #[test]
     fn test_flatten_root_file() {
         assert_eq!(flatten_filename("README.md"), "README.md");
@@ -586,29 +1878,83 @@ mod tests {
         assert!(result.0.contains("[TRUNCATED by ai-fdocs at 200KB]"));
     }
 
+    #[test]
+    fn truncates_non_markdown_files_without_section_rules() {
+        let limits = OutputLimits {
+            max_file_size_kb: 1,
+            chunk_strategy: ChunkStrategy::Headings,
+            chunk_max_tokens: None,
+            token_estimator: TokenEstimatorKind::Heuristic,
+            markdown_cleanup: MarkdownCleanup::default(),
+        };
+        let toml = "# comment\n[package]\n".repeat(100);
+        let fitted = fit_file("Cargo.toml", &toml, &limits);
+        assert!(fitted.truncated && fitted.chunked.is_none());
+        let (name, content) = &fitted.outputs[0];
+        assert_eq!(name, "Cargo.toml");
+        assert!(content.starts_with(&toml[..1024]));
+        assert!(content.ends_with("\n\n[TRUNCATED by ai-fdocs at 1KB]\n"));
+        assert!(!content.contains("Omitted sections"));
+    }
+
     #[test]
     fn test_split_name_version() {
         assert_eq!(split_name_version("serde@1.0.0"), Some(("serde", "1.0.0")));
         assert_eq!(split_name_version("serde"), None);
+        assert_eq!(
+            split_name_version("openssl-src@300.3.1+3.3.1"),
+            Some(("openssl-src", "300.3.1+3.3.1"))
+        );
+        assert_eq!(split_name_version("notes@home"), None);
     }
 
     #[test]
     fn test_config_fingerprint_changes_when_repo_changes() {
         let mut cfg = CrateDoc {
             repo: Some("serde-rs/serde".to_string()),
-            subpath: None,
-            files: None,
-            sources: None,
-            ai_notes: String::new(),
+            ..CrateDoc::default()
         };
 
-        let fp1 = cfg.config_hash();
+        let content = ContentSettings::default();
+        let fp1 = cfg.config_hash(&content);
         cfg.repo = Some("tokio-rs/tokio".to_string());
-        let fp2 = cfg.config_hash();
+        let fp2 = cfg.config_hash(&content);
 
         assert_ne!(fp1, fp2);
     }
 
+    #[test]
+    fn test_config_fingerprint_changes_with_content_settings() {
+        let cfg = CrateDoc::default();
+        let defaults = ContentSettings::default();
+        let base = cfg.config_hash(&defaults);
+
+        let changed = [
+            ContentSettings {
+                markdown_cleanup: MarkdownCleanup {
+                    images: true,
+                    ..MarkdownCleanup::default()
+                },
+                ..defaults
+            },
+            ContentSettings {
+                chunk_strategy: ChunkStrategy::Headings,
+                ..defaults
+            },
+            ContentSettings {
+                chunk_max_tokens: Some(4000),
+                ..defaults
+            },
+            ContentSettings {
+                api_source: crate::config::ApiSource::RustdocJson,
+                ..defaults
+            },
+        ];
+        for content in changed {
+            assert_ne!(cfg.config_hash(&content), base, "{content:?}");
+        }
+    }
+
     #[test]
     fn test_latest_docs_summary_provenance_includes_source_and_truncation() {
         let p = latest_docs_summary_provenance("docsrs", "https://docs.rs/crate/serde/1.0.0", true);
@@ -677,4 +2023,294 @@ config_fingerprint = "abc"
 
         let _ = fs::remove_dir_all(&tmp);
     }
+
+    #[test]
+    fn save_crate_files_records_cleanup_sizes() {
+        let tmp =
+            std::env::temp_dir().join(format!("ai-fdocs-cleanup-meta-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&tmp);
+
+        let readme = "<!-- generated -->\n# demo\n\n[![CI](https://github.com/o/demo/actions/workflows/ci.yml/badge.svg)](https://github.com/o/demo/actions)\n\nA demo crate.\n";
+        let files = [FetchedFile {
+            path: "README.md".to_string(),
+            source_url: "https://github.com/o/demo/blob/v1.0.0/README.md".to_string(),
+            content: readme.to_string(),
+        }];
+        let resolved = ResolvedRef {
+            git_ref: "v1.0.0".to_string(),
+            is_fallback: false,
+        };
+        let save_ctx = SaveContext {
+            repo: "o/demo",
+            resolved: &resolved,
+            limits: OutputLimits {
+                max_file_size_kb: 200,
+                chunk_strategy: ChunkStrategy::Truncate,
+                chunk_max_tokens: None,
+                token_estimator: TokenEstimatorKind::Heuristic,
+                markdown_cleanup: MarkdownCleanup::default(),
+            },
+            content: ContentSettings::default(),
+            source_kind: "github",
+            artifact_path: None,
+            docsrs_input_url: None,
+            upstream_latest_version: None,
+            fallback_reason: None,
+            crates_io: None,
+            repo_archived: false,
+        };
+        let crate_doc = CrateDoc::default();
+        save_crate_files(
+            &tmp,
+            &save_ctx,
+            SaveRequest {
+                crate_name: "demo",
+                version: "1.0.0",
+                fetched_files: &files,
+                crate_config: &crate_doc,
+                locked_version: None,
+            },
+        )
+        .expect("save crate files");
+
+        let meta = read_meta(&tmp, "demo", "1.0.0").expect("read meta");
+        let rules: Vec<&str> = meta.cleanup.iter().map(|c| c.rule.as_str()).collect();
+        assert_eq!(rules, ["html_comments", "badges"]);
+        assert!(meta
+            .cleanup
+            .iter()
+            .all(|c| c.file == "README.md" && c.items == 1));
+        assert_eq!(meta.cleanup[0].bytes_before, readme.len());
+        assert_eq!(meta.cleanup[0].bytes_after, meta.cleanup[1].bytes_before);
+        assert_eq!(
+            meta.cleanup[1].bytes_after,
+            "# demo\n\nA demo crate.\n".len()
+        );
+
+        let _ = fs::remove_dir_all(&tmp);
+    }
+
+    #[test]
+    fn windows_latest_changelog_from_locked_version() {
+        let tmp =
+            std::env::temp_dir().join(format!("ai-fdocs-latest-changelog-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&tmp);
+
+        let artifact = DocsRsArtifact {
+            source_kind: "docsrs",
+            markdown: "# demo@1.44.0\n".to_string(),
+            docsrs_input_url: "https://docs.rs/crate/demo/1.44.0".to_string(),
+            module_files: Vec::new(),
+            pages: Vec::new(),
+        };
+        let changelog = RepoChangelog {
+            repo: "acme/demo".to_string(),
+            git_ref: "v1.44.0".to_string(),
+            file: FetchedFile {
+                path: "demo/CHANGELOG.md".to_string(),
+                source_url: String::new(),
+                content: "# Changelog\n<!-- release tooling -->\n\n## 1.44.0\n- new, see [the guide](docs/guide.md)\n\n## 1.43.0\n- mid\n\n## 1.42.0\n- locked\n\n## 1.41.0\n- old\n".to_string(),
+            },
+            locked_version: Some("1.42.0".to_string()),
+        };
+        let crate_doc = CrateDoc {
+            changelog_window: ChangelogWindow::NewerThanLocked,
+            ..CrateDoc::default()
+        };
+
+        let saved = save_latest_api_markdown(
+            &tmp,
+            "demo",
+            "1.44.0",
+            &artifact,
+            &OutputLimits {
+                max_file_size_kb: 200,
+                chunk_strategy: ChunkStrategy::Truncate,
+                chunk_max_tokens: None,
+                token_estimator: TokenEstimatorKind::Heuristic,
+                markdown_cleanup: MarkdownCleanup::default(),
+            },
+            &ContentSettings::default(),
+            &crate_doc,
+            None,
+            false,
+            Some(&changelog),
+        )
+        .expect("save latest artifact");
+        assert_eq!(
+            saved.files,
+            vec!["API.md", "CHANGELOG.json", "CHANGELOG.md"]
+        );
+
+        let markdown = fs::read_to_string(tmp.join("demo@1.44.0/CHANGELOG.md")).expect("read");
+        assert!(markdown.contains("## 1.42.0"));
+        assert!(!markdown.contains("## 1.41.0"));
+        assert!(!markdown.contains("release tooling"));
+        assert!(markdown
+            .contains("[the guide](https://github.com/acme/demo/blob/v1.44.0/demo/docs/guide.md)"));
+        let json = fs::read_to_string(tmp.join("demo@1.44.0/CHANGELOG.json")).expect("read");
+        assert!(json.contains("1.41.0"));
+
+        let meta = read_meta(&tmp, "demo", "1.44.0").expect("read meta");
+        let changelog = meta.changelog.expect("changelog meta");
+        assert_eq!(changelog.window, "newer");
+        assert_eq!(changelog.kept_versions, vec!["1.44.0", "1.43.0", "1.42.0"]);
+        assert_eq!(changelog.omitted_versions, 1);
+        assert!(meta.files.iter().any(|f| f.path == "CHANGELOG.json"));
+        assert_eq!(meta.cleanup.len(), 1);
+        assert_eq!(meta.cleanup[0].file, CHANGELOG_FILE);
+        assert_eq!(meta.cleanup[0].rule, "html_comments");
+
+        let _ = fs::remove_dir_all(&tmp);
+    }
+
+    #[test]
+    fn test_save_latest_api_markdown_writes_module_files_and_pages() {
+        use crate::fetcher::latest::{CrawledPage, ModuleFile};
+
+        let tmp =
+            std::env::temp_dir().join(format!("ai-fdocs-latest-pages-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&tmp);
+
+        let artifact = DocsRsArtifact {
+            source_kind: "docsrs",
+            markdown: "# tokio@1.44.0\n".to_string(),
+            docsrs_input_url: "https://docs.rs/crate/tokio/1.44.0".to_string(),
+            module_files: vec![ModuleFile {
+                path: "API__sync.md".to_string(),
+                markdown: "# tokio::sync@1.44.0\n".to_string(),
+            }],
+            pages: vec![
+                CrawledPage {
+                    url: "https://docs.rs/tokio/1.44.0/tokio/index.html".to_string(),
+                    artifact_path: "API.md".to_string(),
+                },
+                CrawledPage {
+                    url: "https://docs.rs/tokio/1.44.0/tokio/sync/index.html".to_string(),
+                    artifact_path: "API__sync.md".to_string(),
+                },
+            ],
+        };
+
+        let crates_io = CratesIoMetadata {
+            license: Some("MIT".to_string()),
+            rust_version: Some("1.70".to_string()),
+            yanked: true,
+            ..CratesIoMetadata::default()
+        };
+        let saved = save_latest_api_markdown(
+            &tmp,
+            "tokio",
+            "1.44.0",
+            &artifact,
+            &OutputLimits {
+                max_file_size_kb: 200,
+                chunk_strategy: ChunkStrategy::Truncate,
+                chunk_max_tokens: None,
+                token_estimator: TokenEstimatorKind::Heuristic,
+                markdown_cleanup: MarkdownCleanup::default(),
+            },
+            &ContentSettings::default(),
+            &CrateDoc::default(),
+            Some(&crates_io),
+            true,
+            None,
+        )
+        .expect("save latest artifact");
+        assert_eq!(saved.files, vec!["API.md", "API__sync.md"]);
+        assert!(tmp.join("tokio@1.44.0/API__sync.md").exists());
+
+        let meta = read_meta(&tmp, "tokio", "1.44.0").expect("read meta");
+        assert_eq!(meta.docsrs_pages.len(), 2);
+        assert_eq!(meta.docsrs_pages[1].artifact_path, "API__sync.md");
+
+        let summary =
+            fs::read_to_string(tmp.join("tokio@1.44.0/_SUMMARY.md")).expect("read summary");
+        assert!(summary.contains("Pages crawled: `2`"));
+        assert!(summary
+            .contains("## Crate info\n\n- License: `MIT`\n- MSRV (`rust-version`): `1.70`\n"));
+        assert!(summary.contains("## Warnings\n\n- ⚠️ **`tokio@1.44.0` is yanked on crates.io.**"));
+        assert!(summary.contains("- ⚠️ **The upstream GitHub repository is archived**"));
+        assert!(!summary.contains("deprecated"));
+        assert_eq!(meta.crates_io, Some(crates_io));
+        assert!(meta.repo_archived);
+
+        let _ = fs::remove_dir_all(&tmp);
+    }
+
+    #[test]
+    fn chunks_oversized_markdown_with_manifest_and_part_index() {
+        let tmp = std::env::temp_dir().join(format!("ai-fdocs-chunks-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&tmp);
+
+        let mut markdown = String::from("# demo@0.4.0\n\n");
+        for module in ["io", "net", "sync"] {
+            markdown.push_str(&format!("## Module {module}\n\n"));
+            markdown.push_str(&format!("Docs for {module}. ").repeat(60));
+            markdown.push_str("\n\n");
+        }
+        let artifact = DocsRsArtifact {
+            source_kind: "rustdoc_json",
+            markdown,
+            docsrs_input_url: "https://docs.rs/crate/demo/0.4.0".to_string(),
+            module_files: Vec::new(),
+            pages: Vec::new(),
+        };
+        let limits = OutputLimits {
+            max_file_size_kb: 1,
+            chunk_strategy: ChunkStrategy::Headings,
+            chunk_max_tokens: None,
+            token_estimator: TokenEstimatorKind::Heuristic,
+            markdown_cleanup: MarkdownCleanup::default(),
+        };
+
+        let saved = save_latest_api_markdown(
+            &tmp,
+            "demo",
+            "0.4.0",
+            &artifact,
+            &limits,
+            &ContentSettings::default(),
+            &CrateDoc::default(),
+            None,
+            false,
+            None,
+        )
+        .expect("save chunked artifact");
+        assert_eq!(saved.files[0], "API.md");
+        assert_eq!(saved.files[1], "API.part-01.md");
+        assert_eq!(saved.files.last().map(String::as_str), Some("_CHUNKS.json"));
+
+        let crate_dir = tmp.join("demo@0.4.0");
+        let index = fs::read_to_string(crate_dir.join("API.md")).expect("part index");
+        assert!(index.contains("- [API.part-02.md](API.part-02.md) — demo@0.4.0 › Module net"));
+        let manifest: serde_json::Value = serde_json::from_str(
+            &fs::read_to_string(crate_dir.join("_CHUNKS.json")).expect("manifest"),
+        )
+        .expect("valid json");
+        assert_eq!(manifest["crate"], "demo");
+        assert_eq!(manifest["files"][0]["source"], "API.md");
+        let chunks = manifest["files"][0]["chunks"].as_array().expect("chunks");
+        assert_eq!(chunks.len(), saved.files.len() - 2);
+        assert!(chunks
+            .iter()
+            .all(|c| c["bytes"].as_u64().is_some_and(|b| b <= 1024)
+                && c["estimated_tokens"].as_u64().is_some()));
+
+        let summary = fs::read_to_string(crate_dir.join("_SUMMARY.md")).expect("summary");
+        for chunk in chunks {
+            let file = chunk["file"].as_str().expect("file name");
+            assert!(summary.contains(&format!("- [{file}]({file})")));
+        }
+        let meta = read_meta(&tmp, "demo", "0.4.0").expect("read meta");
+        assert_eq!(meta.truncated, Some(false));
+        assert_eq!(meta.token_estimator.as_deref(), Some("heuristic"));
+        assert_eq!(meta.files.len(), saved.files.len() - 1);
+        assert_eq!(
+            meta.artifact_tokens,
+            Some(meta.files.iter().map(|f| f.tokens).sum())
+        );
+
+        let _ = fs::remove_dir_all(&tmp);
+    }
 }
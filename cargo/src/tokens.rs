@@ -0,0 +1,71 @@
+use crate::config::TokenEstimatorKind;
+
+/// Estimates how many tokens a model spends reading a text.
+pub trait TokenEstimator: Send + Sync {
+    fn estimate(&self, text: &str) -> usize;
+}
+
+/// Rough BPE approximation: ASCII words and identifiers cost a token per four
+/// characters, other letters (CJK, Cyrillic, …) one per two, and every
+/// punctuation character one. Whitespace is free.
+pub struct Heuristic;
+
+impl TokenEstimator for Heuristic {
+    fn estimate(&self, text: &str) -> usize {
+        let mut tokens = 0;
+        let mut ascii_run = 0;
+        let mut other_run = 0;
+        let flush = |tokens: &mut usize, ascii_run: &mut usize, other_run: &mut usize| {
+            *tokens += ascii_run.div_ceil(4) + other_run.div_ceil(2);
+            *ascii_run = 0;
+            *other_run = 0;
+        };
+        for ch in text.chars() {
+            if ch.is_ascii_alphanumeric() || ch == '_' {
+                ascii_run += 1;
+            } else if ch.is_alphanumeric() {
+                other_run += 1;
+            } else {
+                flush(&mut tokens, &mut ascii_run, &mut other_run);
+                if !ch.is_whitespace() {
+                    tokens += 1;
+                }
+            }
+        }
+        flush(&mut tokens, &mut ascii_run, &mut other_run);
+        tokens
+    }
+}
+
+/// The common "four characters per token" rule of thumb.
+pub struct Chars;
+
+impl TokenEstimator for Chars {
+    fn estimate(&self, text: &str) -> usize {
+        text.chars().count().div_ceil(4)
+    }
+}
+
+pub fn estimator(kind: TokenEstimatorKind) -> &'static dyn TokenEstimator {
+    match kind {
+        TokenEstimatorKind::Heuristic => &Heuristic,
+        TokenEstimatorKind::Chars => &Chars,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{estimator, Chars, Heuristic, TokenEstimator};
+    use crate::config::TokenEstimatorKind;
+
+    #[test]
+    fn heuristic_counts_words_punctuation_and_wide_scripts() {
+        assert_eq!(Heuristic.estimate(""), 0);
+        assert_eq!(Heuristic.estimate("Hello world"), 4);
+        assert_eq!(Heuristic.estimate("fn main() {}"), 6);
+        assert_eq!(Heuristic.estimate("spawn_blocking"), 4);
+        assert_eq!(Heuristic.estimate("привет мир"), 5);
+        assert_eq!(Chars.estimate("привет мир"), 3);
+        assert_eq!(estimator(TokenEstimatorKind::Chars).estimate("abcdefgh"), 2);
+    }
+}
@@ -0,0 +1,313 @@
+use chrono::Utc;
+
+use crate::fetcher::github::ReleaseNote;
+use crate::processor::changelog::{entry_from_notes, ChangelogEntry, SectionKind};
+use crate::version;
+
+pub const UPGRADE_FILE: &str = "UPGRADE.md";
+pub const UPGRADES_INDEX_FILE: &str = "_UPGRADES.md";
+
+/// A crate whose locked version moved during a sync, with the releases in between.
+#[derive(Debug, Clone)]
+pub struct UpgradeBrief {
+    pub crate_name: String,
+    pub from: String,
+    pub to: String,
+    /// Releases above the lower version up to and including the higher one,
+    /// newest first.
+    pub entries: Vec<ChangelogEntry>,
+}
+
+impl UpgradeBrief {
+    /// Changelog entries in the range, completed by GitHub release notes for
+    /// versions the changelog does not mention.
+    pub fn new(
+        crate_name: &str,
+        from: &str,
+        to: &str,
+        changelog: &[ChangelogEntry],
+        releases: &[ReleaseNote],
+    ) -> Self {
+        let (low, high) = if version::compare(from, to).is_le() {
+            (from, to)
+        } else {
+            (to, from)
+        };
+        let in_range = |v: &str| {
+            version::parse(v).is_some()
+                && version::compare(v, low).is_gt()
+                && version::compare(v, high).is_le()
+        };
+
+        let mut entries: Vec<ChangelogEntry> = Vec::new();
+        for entry in changelog.iter().filter(|e| in_range(&e.version)) {
+            if !entries
+                .iter()
+                .any(|e| version::same_release(&e.version, &entry.version))
+            {
+                entries.push(entry.clone());
+            }
+        }
+        for release in releases {
+            let Some(v) = version::version_from_tag(crate_name, &release.tag_name) else {
+                continue;
+            };
+            if !in_range(&v)
+                || entries
+                    .iter()
+                    .any(|e| version::same_release(&e.version, &v))
+            {
+                continue;
+            }
+            entries.push(entry_from_notes(
+                &v,
+                release
+                    .published_at
+                    .as_deref()
+                    .map(|d| d.chars().take(10).collect()),
+                release.html_url.clone(),
+                release.body.as_deref().unwrap_or_default(),
+            ));
+        }
+        entries.sort_by(|a, b| version::compare(&b.version, &a.version));
+
+        Self {
+            crate_name: crate_name.to_string(),
+            from: from.to_string(),
+            to: to.to_string(),
+            entries,
+        }
+    }
+
+    pub fn is_downgrade(&self) -> bool {
+        version::compare(&self.to, &self.from).is_lt()
+    }
+
+    /// `(version, item)` pairs from sections of the given kinds, newest first.
+    fn items(&self, kinds: &[SectionKind]) -> Vec<(&str, &str)> {
+        self.entries
+            .iter()
+            .flat_map(|entry| {
+                entry
+                    .sections
+                    .iter()
+                    .filter(|s| kinds.contains(&s.kind))
+                    .flat_map(|s| &s.items)
+                    .map(|item| (entry.version.as_str(), item.as_str()))
+            })
+            .collect()
+    }
+
+    fn breaking_changes(&self) -> Vec<(&str, &str)> {
+        self.items(&[SectionKind::Breaking, SectionKind::Removed])
+    }
+
+    fn deprecations(&self) -> Vec<(&str, &str)> {
+        self.items(&[SectionKind::Deprecated])
+    }
+}
+
+/// `UPGRADE.md` for the new crate directory: breaking changes and deprecations
+/// first, then every release entry in the range.
+pub fn render_upgrade_markdown(brief: &UpgradeBrief) -> String {
+    let direction = if brief.is_downgrade() {
+        "Downgrade"
+    } else {
+        "Upgrade"
+    };
+    let mut out = format!(
+        "# {}@{} — {direction} from {}\n\n",
+        brief.crate_name, brief.to, brief.from
+    );
+    out.push_str(&format!(
+        "`Cargo.lock` moved from `{}` to `{}`. Sources: changelog entries and GitHub release notes{}.\n\n",
+        brief.from,
+        brief.to,
+        if brief.is_downgrade() {
+            "; the releases below are no longer available after the downgrade"
+        } else {
+            "; code written against the old version may need the changes below"
+        }
+    ));
+
+    if brief.entries.is_empty() {
+        out.push_str("No changelog or release-note entries were found between these versions.\n");
+        return out;
+    }
+
+    for (title, items) in [
+        ("Breaking changes", brief.breaking_changes()),
+        ("Deprecations", brief.deprecations()),
+    ] {
+        if items.is_empty() {
+            continue;
+        }
+        out.push_str(&format!("## {title}\n\n"));
+        for (v, item) in items {
+            out.push_str(&format!("- **{v}**: {item}\n"));
+        }
+        out.push('\n');
+    }
+
+    out.push_str("## Releases\n\n");
+    for entry in &brief.entries {
+        out.push_str(&format!("### {}", entry.version));
+        if let Some(date) = &entry.date {
+            out.push_str(&format!(" ({date})"));
+        }
+        if entry.yanked {
+            out.push_str(" [YANKED]");
+        }
+        out.push_str("\n\n");
+        if let Some(url) = &entry.url {
+            out.push_str(&format!("Source: {url}\n\n"));
+        }
+        if !entry.body.is_empty() {
+            out.push_str(&demote_headings(&entry.body));
+            out.push_str("\n\n");
+        }
+    }
+    out
+}
+
+/// Release bodies sit under `###`; push their own headings below that level.
+fn demote_headings(body: &str) -> String {
+    body.lines()
+        .map(|line| {
+            let level = line.chars().take_while(|c| *c == '#').count();
+            if level == 0 || !line[level..].starts_with(' ') {
+                return line.to_string();
+            }
+            format!("{}{}", "#".repeat((level + 3).min(6)), &line[level..])
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// `_UPGRADES.md`: every crate whose locked version moved in this sync.
+pub fn render_upgrades_index(briefs: &[UpgradeBrief]) -> String {
+    let date = Utc::now().format("%Y-%m-%d").to_string();
+    let mut out = String::new();
+    out.push_str("<!-- This file is auto-generated by ai-fdocs. Do not edit manually. -->\n");
+    out.push_str(&format!("<!-- Generated: {date} -->\n\n"));
+    out.push_str("# Crate version changes in the last sync\n\n");
+
+    let mut sorted: Vec<&UpgradeBrief> = briefs.iter().collect();
+    sorted.sort_by(|a, b| a.crate_name.cmp(&b.crate_name));
+    for brief in sorted {
+        let breaking = brief.breaking_changes().len();
+        let deprecations = brief.deprecations().len();
+        out.push_str(&format!(
+            "- [{name}]({name}@{to}/{UPGRADE_FILE}): `{from}` → `{to}`{downgrade} — {releases} release{rs}, {breaking} breaking change{bs}, {deprecations} deprecation{ds}\n",
+            name = brief.crate_name,
+            to = brief.to,
+            from = brief.from,
+            downgrade = if brief.is_downgrade() { " (downgrade)" } else { "" },
+            releases = brief.entries.len(),
+            rs = plural(brief.entries.len()),
+            bs = plural(breaking),
+            ds = plural(deprecations),
+        ));
+    }
+    out
+}
+
+fn plural(n: usize) -> &'static str {
+    if n == 1 {
+        ""
+    } else {
+        "s"
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{render_upgrade_markdown, render_upgrades_index, UpgradeBrief};
+    use crate::fetcher::github::ReleaseNote;
+    use crate::processor::changelog::parse_changelog;
+
+    const CHANGELOG: &str = r#"# Changelog
+
+## 1.3.0 - 2024-04-01
+### Added
+- Not part of the upgrade
+
+## 1.2.0 - 2024-03-01
+### Changed
+- **Breaking:** `connect` is now async
+### Deprecated
+- `Client::old`
+
+## 1.1.0 - 2024-02-01
+### Removed
+- `legacy` feature
+
+## 1.0.0 - 2024-01-01
+- Initial
+"#;
+
+    fn release(tag: &str, body: &str) -> ReleaseNote {
+        ReleaseNote {
+            tag_name: tag.to_string(),
+            body: Some(body.to_string()),
+            published_at: Some("2024-02-15T10:00:00Z".to_string()),
+            html_url: Some(format!("https://github.com/o/r/releases/tag/{tag}")),
+            draft: false,
+        }
+    }
+
+    #[test]
+    fn collects_entries_between_versions_with_release_notes() {
+        let changelog = parse_changelog(CHANGELOG);
+        let releases = [
+            release("v1.1.5", "### Fixed\n- Leak in pool"),
+            release("v1.2.0", "duplicate of the changelog entry"),
+        ];
+        let brief = UpgradeBrief::new("demo", "1.0.0", "1.2.0", &changelog, &releases);
+
+        let versions: Vec<&str> = brief.entries.iter().map(|e| e.version.as_str()).collect();
+        assert_eq!(versions, vec!["1.2.0", "1.1.5", "1.1.0"]);
+        assert_eq!(brief.entries[1].date.as_deref(), Some("2024-02-15"));
+        assert!(!brief.is_downgrade());
+
+        let markdown = render_upgrade_markdown(&brief);
+        assert!(markdown.starts_with("# demo@1.2.0 — Upgrade from 1.0.0\n"));
+        let breaking = markdown
+            .find("## Breaking changes")
+            .expect("breaking section");
+        let deprecations = markdown
+            .find("## Deprecations")
+            .expect("deprecations section");
+        let releases_at = markdown.find("## Releases").expect("releases section");
+        assert!(breaking < deprecations && deprecations < releases_at);
+        assert!(markdown.contains("- **1.2.0**: **Breaking:** `connect` is now async\n"));
+        assert!(markdown.contains("- **1.1.0**: `legacy` feature\n"));
+        assert!(markdown.contains("- **1.2.0**: `Client::old`\n"));
+        assert!(markdown.contains(
+            "### 1.1.5 (2024-02-15)\n\nSource: https://github.com/o/r/releases/tag/v1.1.5"
+        ));
+        assert!(markdown.contains("###### Fixed\n- Leak in pool"));
+        assert!(!markdown.contains("Not part of the upgrade"));
+        assert!(!markdown.contains("Initial"));
+    }
+
+    #[test]
+    fn renders_downgrades_and_index() {
+        let changelog = parse_changelog(CHANGELOG);
+        let down = UpgradeBrief::new("demo", "1.2.0", "1.1.0", &changelog, &[]);
+        assert!(down.is_downgrade());
+        assert_eq!(down.entries.len(), 1);
+        assert!(render_upgrade_markdown(&down).starts_with("# demo@1.1.0 — Downgrade from 1.2.0\n"));
+
+        let empty = UpgradeBrief::new("other", "0.1.0", "0.2.0", &[], &[]);
+        assert!(render_upgrade_markdown(&empty).contains("No changelog or release-note entries"));
+
+        let up = UpgradeBrief::new("demo", "1.0.0", "1.2.0", &changelog, &[]);
+        let index = render_upgrades_index(&[up, empty]);
+        assert!(index.contains("# Crate version changes in the last sync\n"));
+        assert!(index.contains(
+            "- [demo](demo@1.2.0/UPGRADE.md): `1.0.0` → `1.2.0` — 2 releases, 2 breaking changes, 1 deprecation\n"
+        ));
+        assert!(index.find("[demo]") < index.find("[other]"));
+    }
+}
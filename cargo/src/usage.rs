@@ -0,0 +1,384 @@
+use std::collections::{BTreeSet, HashMap};
+use std::fs;
+use std::path::Path;
+
+use regex::Regex;
+
+/// Directories that never hold the project's own code.
+const SKIPPED_DIRS: &[&str] = &["target", "vendor", "node_modules"];
+
+/// Paths into the standard library and the current crate, which are not dependencies.
+const LOCAL_ROOTS: &[&str] = &["std", "core", "alloc", "crate", "self", "super", "Self"];
+
+/// A dependency path the project refers to, e.g. `tokio::sync::mpsc`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct UsedPath {
+    pub path: String,
+    /// Number of `.rs` files that mention it.
+    pub files: usize,
+}
+
+/// Dependency paths found in the workspace's `.rs` files through `use`
+/// declarations and qualified paths, keyed by crate name (`-` as `_`).
+#[derive(Debug, Default)]
+pub struct ProjectUsage {
+    crates: HashMap<String, Vec<UsedPath>>,
+}
+
+impl ProjectUsage {
+    /// Scan every `.rs` file under `project_dir`, skipping hidden directories,
+    /// `target/` and `vendor/`.
+    pub fn scan(project_dir: &Path) -> Self {
+        let mut files = Vec::new();
+        collect_rust_files(project_dir, &mut files);
+        files.sort();
+        Self::from_sources(
+            files
+                .iter()
+                .filter_map(|path| fs::read_to_string(path).ok())
+                .collect::<Vec<_>>()
+                .iter()
+                .map(String::as_str),
+        )
+    }
+
+    pub fn from_sources<'a>(sources: impl IntoIterator<Item = &'a str>) -> Self {
+        let mut counts: HashMap<String, HashMap<String, usize>> = HashMap::new();
+        for source in sources {
+            for path in file_paths(source) {
+                let root = path.split("::").next().unwrap_or_default().to_string();
+                *counts.entry(root).or_default().entry(path).or_default() += 1;
+            }
+        }
+
+        let crates = counts
+            .into_iter()
+            .map(|(root, paths)| {
+                let mut paths: Vec<UsedPath> = paths
+                    .into_iter()
+                    .map(|(path, files)| UsedPath { path, files })
+                    .collect();
+                paths.sort_by(|a, b| b.files.cmp(&a.files).then(a.path.cmp(&b.path)));
+                (root, paths)
+            })
+            .collect();
+        Self { crates }
+    }
+
+    /// Paths into `crate_name`, most widely used first.
+    pub fn for_crate(&self, crate_name: &str) -> &[UsedPath] {
+        self.crates
+            .get(&crate_name.replace('-', "_"))
+            .map_or(&[], Vec::as_slice)
+    }
+
+    /// Paths into `crate_name` relative to its root (`sync::mpsc`), for ranking
+    /// modules and items; the crate root itself is left out.
+    pub fn relative_paths(&self, crate_name: &str) -> Vec<String> {
+        self.for_crate(crate_name)
+            .iter()
+            .filter_map(|used| used.path.split_once("::").map(|(_, rest)| rest.to_string()))
+            .collect()
+    }
+}
+
+/// Whether `path` (relative to the crate root) is used or contains a used item.
+pub fn covers(used: &[String], path: &str) -> bool {
+    !path.is_empty()
+        && used
+            .iter()
+            .any(|u| u == path || (u.starts_with(path) && u[path.len()..].starts_with("::")))
+}
+
+fn collect_rust_files(dir: &Path, out: &mut Vec<std::path::PathBuf>) {
+    let Ok(entries) = fs::read_dir(dir) else {
+        return;
+    };
+    for entry in entries.flatten() {
+        let path = entry.path();
+        let name = entry.file_name();
+        let name = name.to_string_lossy();
+        if path.is_dir() {
+            if !name.starts_with('.') && !SKIPPED_DIRS.contains(&name.as_ref()) {
+                collect_rust_files(&path, out);
+            }
+        } else if name.ends_with(".rs") {
+            out.push(path);
+        }
+    }
+}
+
+/// Every non-local path one file refers to, with `use` aliases resolved.
+fn file_paths(source: &str) -> BTreeSet<String> {
+    let use_re = Regex::new(r"(?:^|[^\w:])use\s+([^;]+);").expect("valid use regex");
+    let path_re =
+        Regex::new(r"\b[A-Za-z_]\w*(?:::[A-Za-z_]\w*)+").expect("valid qualified path regex");
+
+    let mut code = strip_comments_and_strings(source);
+    let mut paths = BTreeSet::new();
+    let mut aliases: HashMap<String, String> = HashMap::new();
+    let mut use_spans = Vec::new();
+    for caps in use_re.captures_iter(&code) {
+        let tree = caps.get(1).expect("use tree group");
+        use_spans.push(tree.range());
+        let mut expanded = Vec::new();
+        expand_use_tree("", tree.as_str(), &mut expanded);
+        for (path, alias) in expanded {
+            if let Some(alias) = alias {
+                aliases.insert(alias, path.clone());
+            }
+            paths.insert(path);
+        }
+    }
+    for range in use_spans {
+        code.replace_range(range.clone(), &" ".repeat(range.len()));
+    }
+
+    for found in path_re.find_iter(&code) {
+        let path = found.as_str();
+        let (first, rest) = path.split_once("::").unwrap_or((path, ""));
+        match aliases.get(first) {
+            Some(full) => paths.insert(format!("{full}::{rest}")),
+            None => paths.insert(path.to_string()),
+        };
+    }
+
+    paths.retain(|path| {
+        let first = path.split("::").next().unwrap_or_default();
+        !first.is_empty()
+            && !LOCAL_ROOTS.contains(&first)
+            && first.chars().next().is_some_and(|c| c.is_ascii_lowercase())
+    });
+    paths
+}
+
+/// Expand a use tree (`tokio::sync::{self, mpsc::Sender as Tx}`) into full
+/// paths with the name each brings into scope.
+fn expand_use_tree(prefix: &str, tree: &str, out: &mut Vec<(String, Option<String>)>) {
+    let tree = tree.trim().trim_start_matches("::").trim();
+    let join = |rest: &str| {
+        let rest = rest.trim().trim_end_matches("::").trim();
+        match (prefix.is_empty(), rest.is_empty()) {
+            (_, true) => prefix.to_string(),
+            (true, false) => rest.to_string(),
+            (false, false) => format!("{prefix}::{rest}"),
+        }
+    };
+
+    if let (Some(open), Some(close)) = (tree.find('{'), tree.rfind('}')) {
+        if open < close {
+            let base = join(&tree[..open]);
+            for part in split_top_level(&tree[open + 1..close]) {
+                expand_use_tree(&base, part, out);
+            }
+            return;
+        }
+    }
+
+    let (path, alias) = match tree.split_once(" as ") {
+        Some((path, alias)) => (path.trim(), Some(alias.trim())),
+        None => (tree, None),
+    };
+    let compact: String = path.chars().filter(|c| !c.is_whitespace()).collect();
+    let (full, glob) = match compact.as_str() {
+        "self" => (prefix.to_string(), false),
+        "*" => (prefix.to_string(), true),
+        _ if compact.ends_with("::*") => (join(&compact[..compact.len() - 3]), true),
+        _ => (join(&compact), false),
+    };
+    if full.is_empty() {
+        return;
+    }
+    let name = if glob {
+        None
+    } else {
+        alias
+            .or_else(|| full.rsplit("::").next())
+            .filter(|name| *name != "_")
+            .map(str::to_string)
+    };
+    out.push((full, name));
+}
+
+/// Split on commas outside nested braces.
+fn split_top_level(list: &str) -> Vec<&str> {
+    let mut parts = Vec::new();
+    let mut depth = 0;
+    let mut start = 0;
+    for (i, c) in list.char_indices() {
+        match c {
+            '{' => depth += 1,
+            '}' => depth -= 1,
+            ',' if depth == 0 => {
+                parts.push(&list[start..i]);
+                start = i + 1;
+            }
+            _ => {}
+        }
+    }
+    parts.push(&list[start..]);
+    parts.retain(|p| !p.trim().is_empty());
+    parts
+}
+
+/// Blank out comments, string and char literals (keeping line breaks) so paths
+/// in docs and strings are not mistaken for code.
+fn strip_comments_and_strings(source: &str) -> String {
+    let chars: Vec<char> = source.chars().collect();
+    let mut out = String::with_capacity(source.len());
+    let blank = |c: char| if c == '\n' { '\n' } else { ' ' };
+    let is_ident = |c: Option<&char>| c.is_some_and(|c| c.is_alphanumeric() || *c == '_');
+    let mut i = 0;
+    while i < chars.len() {
+        let c = chars[i];
+        let next = chars.get(i + 1).copied();
+        if c == '/' && next == Some('/') {
+            while i < chars.len() && chars[i] != '\n' {
+                out.push(' ');
+                i += 1;
+            }
+        } else if c == '/' && next == Some('*') {
+            let mut depth = 0;
+            while i < chars.len() {
+                if chars[i] == '/' && chars.get(i + 1) == Some(&'*') {
+                    depth += 1;
+                    out.push_str("  ");
+                    i += 2;
+                } else if chars[i] == '*' && chars.get(i + 1) == Some(&'/') {
+                    depth -= 1;
+                    out.push_str("  ");
+                    i += 2;
+                    if depth == 0 {
+                        break;
+                    }
+                } else {
+                    out.push(blank(chars[i]));
+                    i += 1;
+                }
+            }
+        } else if c == 'r'
+            && matches!(next, Some('"' | '#'))
+            && (!is_ident(i.checked_sub(1).and_then(|p| chars.get(p)))
+                || (i >= 1
+                    && chars[i - 1] == 'b'
+                    && !is_ident(i.checked_sub(2).and_then(|p| chars.get(p)))))
+        {
+            let hashes = chars[i + 1..].iter().take_while(|c| **c == '#').count();
+            if chars.get(i + 1 + hashes) != Some(&'"') {
+                out.push(c);
+                i += 1;
+                continue;
+            }
+            let closing: Vec<char> = std::iter::once('"')
+                .chain(std::iter::repeat_n('#', hashes))
+                .collect();
+            out.push(' ');
+            i += 1;
+            for _ in 0..=hashes {
+                out.push(' ');
+                i += 1;
+            }
+            while i < chars.len() && !chars[i..].starts_with(&closing) {
+                out.push(blank(chars[i]));
+                i += 1;
+            }
+            for _ in 0..closing.len().min(chars.len() - i) {
+                out.push(' ');
+                i += 1;
+            }
+        } else if c == '"' {
+            out.push(' ');
+            i += 1;
+            while i < chars.len() && chars[i] != '"' {
+                let skip = if chars[i] == '\\' { 2 } else { 1 };
+                for _ in 0..skip.min(chars.len() - i) {
+                    out.push(blank(chars[i]));
+                    i += 1;
+                }
+            }
+            if i < chars.len() {
+                out.push(' ');
+                i += 1;
+            }
+        } else if c == '\'' && next == Some('\\') {
+            out.push(' ');
+            i += 1;
+            while i < chars.len() && chars[i] != '\'' {
+                let skip = if chars[i] == '\\' { 2 } else { 1 };
+                for _ in 0..skip.min(chars.len() - i) {
+                    out.push(' ');
+                    i += 1;
+                }
+            }
+            if i < chars.len() {
+                out.push(' ');
+                i += 1;
+            }
+        } else if c == '\'' && chars.get(i + 2) == Some(&'\'') {
+            out.push_str("   ");
+            i += 3;
+        } else {
+            out.push(c);
+            i += 1;
+        }
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{covers, ProjectUsage};
+
+    const MAIN_RS: &str = r#"
+use std::sync::Arc;
+use tokio::sync::{self, mpsc::{self, Sender as Tx}, Mutex};
+use serde::{Deserialize, Serialize};
+use crate::config::Config;
+
+// tokio::fs::read is only mentioned in a comment
+fn main() {
+    let (tx, rx) = mpsc::channel::<u8>(8);
+    let text = "tokio::net::TcpStream in a string";
+    let quote = '"';
+    tokio::spawn(async move { let _ = serde_json::to_string(&1); });
+    let value: Tx<u8> = tx;
+}
+"#;
+
+    const LIB_RS: &str = "pub use tokio::sync::mpsc;\nfn run() { tokio::spawn(async {}); }\n";
+
+    #[test]
+    fn collects_use_trees_aliases_and_qualified_paths() {
+        let usage = ProjectUsage::from_sources([MAIN_RS, LIB_RS]);
+        let tokio: Vec<(&str, usize)> = usage
+            .for_crate("tokio")
+            .iter()
+            .map(|u| (u.path.as_str(), u.files))
+            .collect();
+        assert_eq!(
+            tokio,
+            [
+                ("tokio::spawn", 2),
+                ("tokio::sync::mpsc", 2),
+                ("tokio::sync", 1),
+                ("tokio::sync::Mutex", 1),
+                ("tokio::sync::mpsc::Sender", 1),
+                ("tokio::sync::mpsc::channel", 1),
+            ]
+        );
+        assert_eq!(usage.for_crate("serde").len(), 2);
+        assert_eq!(
+            usage.for_crate("serde-json")[0].path,
+            "serde_json::to_string"
+        );
+        assert!(usage.for_crate("std").is_empty());
+        assert!(usage.for_crate("crate").is_empty());
+
+        let relative = usage.relative_paths("tokio");
+        assert!(covers(&relative, "sync"));
+        assert!(covers(&relative, "sync::mpsc"));
+        assert!(covers(&relative, "spawn"));
+        assert!(!covers(&relative, "net"));
+        assert!(!covers(&relative, "sync::mp"));
+    }
+}
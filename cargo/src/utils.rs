@@ -2,33 +2,7 @@ use chrono::{NaiveDate, Utc};
 
 /// Semver-aware version comparison. Returns true if new_v > current_best.
 pub fn is_version_better(new_v: &str, current_best: Option<&str>) -> bool {
-    let Some(best) = current_best else {
-        return true;
-    };
-
-    // Very simple semver-ish comparison: split by dots and try to compare numbers
-    let new_parts: Vec<&str> = new_v.split('.').collect();
-    let best_parts: Vec<&str> = best.split('.').collect();
-
-    for i in 0..new_parts.len().max(best_parts.len()) {
-        let n = new_parts.get(i).and_then(|&s| s.parse::<u32>().ok());
-        let b = best_parts.get(i).and_then(|&s| s.parse::<u32>().ok());
-
-        match (n, b) {
-            (Some(nv), Some(bv)) if nv != bv => return nv > bv,
-            (Some(_), None) => return true,
-            (None, Some(_)) => return false,
-            _ => {
-                let ns = new_parts.get(i).unwrap_or(&"");
-                let bs = best_parts.get(i).unwrap_or(&"");
-                if ns != bs {
-                    return ns > bs;
-                }
-            }
-        }
-    }
-
-    false
+    current_best.is_none_or(|best| crate::version::compare(new_v, best).is_gt())
 }
 
 /// Rounds down to the nearest char boundary.
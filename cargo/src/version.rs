@@ -0,0 +1,148 @@
+use std::cmp::Ordering;
+
+use semver::Version;
+
+/// Parse a crate version, tolerating a leading `v` and missing minor/patch
+/// parts (`v1.2` is read as `1.2.0`).
+pub fn parse(version: &str) -> Option<Version> {
+    let version = version.trim();
+    let version = version.strip_prefix('v').unwrap_or(version);
+    if let Ok(v) = Version::parse(version) {
+        return Some(v);
+    }
+
+    let split_at = version.find(['-', '+']).unwrap_or(version.len());
+    let (core, suffix) = version.split_at(split_at);
+    let padded = match core.split('.').count() {
+        1 => format!("{core}.0.0{suffix}"),
+        2 => format!("{core}.0{suffix}"),
+        _ => return None,
+    };
+    Version::parse(&padded).ok()
+}
+
+/// Semver precedence, with build metadata as the final tie-breaker. Versions
+/// that do not parse sort below every valid one and compare as plain strings.
+pub fn compare(a: &str, b: &str) -> Ordering {
+    match (parse(a), parse(b)) {
+        (Some(a), Some(b)) => a.cmp(&b),
+        (Some(_), None) => Ordering::Greater,
+        (None, Some(_)) => Ordering::Less,
+        (None, None) => a.cmp(b),
+    }
+}
+
+/// Whether both strings name the same release. Build metadata is ignored:
+/// crates.io does not accept versions that differ only in it.
+pub fn same_release(a: &str, b: &str) -> bool {
+    match (parse(a), parse(b)) {
+        (Some(a), Some(b)) => a.cmp_precedence(&b) == Ordering::Equal,
+        _ => a == b,
+    }
+}
+
+/// `(major, minor)` series a version belongs to; prereleases and build
+/// metadata do not change it.
+pub fn minor_series(version: &str) -> Option<(u64, u64)> {
+    let v = parse(version)?;
+    Some((v.major, v.minor))
+}
+
+/// Git tags a release is commonly published under, most likely first. Build
+/// metadata is rarely part of a tag, so the plain release is tried as well.
+pub fn tag_candidates(crate_name: &str, version: &str) -> Vec<String> {
+    let mut versions = vec![version.to_string()];
+    if let Some(v) = parse(version).filter(|v| !v.build.is_empty()) {
+        let mut plain = v.clone();
+        plain.build = semver::BuildMetadata::EMPTY;
+        versions.push(plain.to_string());
+    }
+
+    let mut tags = Vec::new();
+    for v in &versions {
+        for tag in [
+            format!("v{v}"),
+            v.clone(),
+            format!("{crate_name}-v{v}"),
+            format!("{crate_name}-{v}"),
+        ] {
+            if !tags.contains(&tag) {
+                tags.push(tag);
+            }
+        }
+    }
+    tags
+}
+
+/// Version named by a git tag such as `v1.2.3`, `1.2.3`, `serde-v1.2.3` or
+/// `serde-1.2.3`; tags of other crates in a monorepo yield `None`.
+pub fn version_from_tag(crate_name: &str, tag: &str) -> Option<String> {
+    let rest = tag.strip_prefix(crate_name).map_or(tag, |rest| {
+        rest.strip_prefix(['-', '@', '/', '_']).unwrap_or(rest)
+    });
+    let rest = rest.strip_prefix('v').unwrap_or(rest);
+    if !rest.starts_with(|c: char| c.is_ascii_digit()) {
+        return None;
+    }
+    parse(rest).map(|_| rest.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{compare, minor_series, parse, same_release, tag_candidates, version_from_tag};
+    use std::cmp::Ordering;
+
+    #[test]
+    fn orders_prereleases_and_build_metadata() {
+        assert_eq!(compare("1.0.0-rc.10", "1.0.0-rc.2"), Ordering::Greater);
+        assert_eq!(compare("1.0.0-rc.2", "1.0.0"), Ordering::Less);
+        assert_eq!(compare("1.0.0-alpha", "1.0.0-alpha.1"), Ordering::Less);
+        assert_eq!(compare("1.10.0", "1.9.3"), Ordering::Greater);
+        assert_eq!(compare("1.0.0+build.2", "1.0.0+build.1"), Ordering::Greater);
+        assert_eq!(compare("1.0.0", "main"), Ordering::Greater);
+        assert_eq!(compare("v1.2", "1.2.0"), Ordering::Equal);
+
+        assert!(same_release("1.0.0+build.1", "1.0.0"));
+        assert!(!same_release("1.0.0-rc.1", "1.0.0"));
+        assert_eq!(parse("2").map(|v| v.to_string()).as_deref(), Some("2.0.0"));
+        assert!(parse("1.2.3.4").is_none());
+    }
+
+    #[test]
+    fn groups_versions_into_minor_series() {
+        assert_eq!(minor_series("0.13.1"), Some((0, 13)));
+        assert_eq!(minor_series("v1.38"), Some((1, 38)));
+        assert_eq!(minor_series("2.0.0-rc.1+build.5"), Some((2, 0)));
+        assert_eq!(minor_series("latest"), None);
+    }
+
+    #[test]
+    fn builds_tag_candidates_with_and_without_build_metadata() {
+        assert_eq!(
+            tag_candidates("serde", "1.0.0"),
+            vec!["v1.0.0", "1.0.0", "serde-v1.0.0", "serde-1.0.0"]
+        );
+        let tags = tag_candidates("openssl-src", "300.3.1+3.3.1");
+        assert_eq!(tags[0], "v300.3.1+3.3.1");
+        assert!(tags.contains(&"openssl-src-v300.3.1".to_string()));
+        assert_eq!(tags.len(), 8);
+    }
+
+    #[test]
+    fn reads_versions_from_release_tags() {
+        assert_eq!(
+            version_from_tag("serde", "v1.0.210").as_deref(),
+            Some("1.0.210")
+        );
+        assert_eq!(
+            version_from_tag("tokio", "tokio-1.38.0").as_deref(),
+            Some("1.38.0")
+        );
+        assert_eq!(
+            version_from_tag("axum", "axum-v0.8.0-rc.1").as_deref(),
+            Some("0.8.0-rc.1")
+        );
+        assert_eq!(version_from_tag("tokio", "tokio-util-0.7.11"), None);
+        assert_eq!(version_from_tag("demo", "nightly"), None);
+    }
+}
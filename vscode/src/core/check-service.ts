@@ -30,7 +30,7 @@ interface CheckSummary {
 }
 
 interface CheckStatusItem {
-    status: 'Synced' | 'SyncedFallback' | 'Outdated' | 'Missing' | 'Corrupted';
+    status: 'Synced' | 'SyncedFallback' | 'Outdated' | 'Missing' | 'Corrupted' | 'Yanked' | 'Deprecated' | 'Vulnerable';
     source_kind?: SourceKind;
     reason_code?: string;
 }
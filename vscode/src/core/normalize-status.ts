@@ -43,11 +43,15 @@ interface NpmStatusOutput {
 const VALID_STATUSES = new Set<DependencyStatus['status']>([
     'Synced',
     'SyncedFallback',
+    'UpdateAvailable',
     'Outdated',
     'Missing',
     'Corrupted',
     'Incomplete',
     'ReadError',
+    'Yanked',
+    'Deprecated',
+    'Vulnerable',
 ]);
 
 function asObject(value: unknown): RawObject | undefined {
@@ -106,7 +110,7 @@ function getBySource(summary: NpmSummary | undefined): Partial<Record<SourceKind
 }
 
 export function normalizeDependencyStatus(raw: DependencyStatus): DependencyItemStatus {
-    if (raw.status === 'Synced' || raw.status === 'SyncedFallback') {
+    if (raw.status === 'Synced' || raw.status === 'SyncedFallback' || raw.status === 'UpdateAvailable') {
         return 'synced';
     }
     if (raw.status === 'Outdated') {
@@ -115,10 +119,19 @@ export function normalizeDependencyStatus(raw: DependencyStatus): DependencyItem
     if (raw.status === 'Missing') {
         return 'missing';
     }
+    if (raw.status === 'Yanked') {
+        return 'yanked';
+    }
+    if (raw.status === 'Deprecated') {
+        return 'deprecated';
+    }
+    if (raw.status === 'Vulnerable') {
+        return 'vulnerable';
+    }
     return 'corrupted';
 }
 
-export type DependencyItemStatus = 'synced' | 'outdated' | 'missing' | 'corrupted';
+export type DependencyItemStatus = 'synced' | 'outdated' | 'missing' | 'corrupted' | 'yanked' | 'deprecated' | 'vulnerable';
 
 export function parseStatusOutput(jsonOutput: string): StatusOutput {
     try {
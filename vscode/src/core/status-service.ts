@@ -12,7 +12,14 @@ import { parseStatusOutput } from './normalize-status';
 import { serializeReport } from './reporting';
 
 function accumulateByStatus(dependencyStatus: DependencyStatus): SourceMetrics {
-    if (dependencyStatus.status === 'Synced' || dependencyStatus.status === 'SyncedFallback') {
+    if (
+        dependencyStatus.status === 'Synced' ||
+        dependencyStatus.status === 'SyncedFallback' ||
+        dependencyStatus.status === 'UpdateAvailable' ||
+        dependencyStatus.status === 'Yanked' ||
+        dependencyStatus.status === 'Deprecated' ||
+        dependencyStatus.status === 'Vulnerable'
+    ) {
         return { synced: 1, cached: 0, skipped: 0, errors: 0 };
     }
     if (dependencyStatus.status === 'Missing') {
@@ -1,7 +1,7 @@
 import * as vscode from 'vscode';
 import { BinaryManager } from './binary-manager';
 import { DependencyStatus, getPackageName } from './types';
-import { normalizeDependencyStatus, parseStatusOutput } from './core/normalize-status';
+import { DependencyItemStatus, normalizeDependencyStatus, parseStatusOutput } from './core/normalize-status';
 import * as path from 'path';
 import * as fs from 'fs';
 
@@ -70,7 +70,7 @@ export class DependencyTreeProvider implements vscode.TreeDataProvider<TreeItemT
             const normalized = normalizeDependencyStatus(dep);
             if (normalized === 'synced') {
                 synced += 1;
-            } else if (normalized === 'outdated') {
+            } else if (normalized === 'outdated' || normalized === 'deprecated') {
                 outdated += 1;
             } else {
                 errors += 1;
@@ -83,7 +83,7 @@ export class DependencyTreeProvider implements vscode.TreeDataProvider<TreeItemT
 
 export class DependencyItem extends vscode.TreeItem {
     readonly packageVersion: string;
-    readonly normalizedStatus: 'synced' | 'outdated' | 'missing' | 'corrupted';
+    readonly normalizedStatus: DependencyItemStatus;
     readonly sourceKind: string;
     readonly fallback: boolean;
     readonly lastSyncAt?: string;
@@ -196,6 +196,17 @@ export class DependencyItem extends vscode.TreeItem {
             lines.push(`Last Sync: ${this.lastSyncAt}`);
         }
 
+        const upstream = this.dependency.upstream;
+        if (upstream?.latest_stable) {
+            lines.push(`Latest Stable: ${upstream.latest_stable}`);
+        }
+        if (upstream?.latest_compatible) {
+            lines.push(`Latest Compatible: ${upstream.latest_compatible}`);
+        }
+        if (upstream?.latest && upstream.latest !== upstream.latest_stable) {
+            lines.push(`Latest: ${upstream.latest}`);
+        }
+
         if (this.dependency.reason) {
             lines.push(`Reason: ${this.dependency.reason}`);
         }
@@ -208,6 +219,9 @@ export class DependencyItem extends vscode.TreeItem {
         if (this.fallback) {
             parts.push('fallback');
         }
+        if (this.dependency.status === 'UpdateAvailable') {
+            parts.push('update available');
+        }
         return parts.join(' · ');
     }
 
@@ -221,6 +235,12 @@ export class DependencyItem extends vscode.TreeItem {
                 return new vscode.ThemeIcon('error', new vscode.ThemeColor('charts.red'));
             case 'corrupted':
                 return new vscode.ThemeIcon('tools', new vscode.ThemeColor('charts.orange'));
+            case 'yanked':
+                return new vscode.ThemeIcon('circle-slash', new vscode.ThemeColor('charts.red'));
+            case 'deprecated':
+                return new vscode.ThemeIcon('archive', new vscode.ThemeColor('charts.yellow'));
+            case 'vulnerable':
+                return new vscode.ThemeIcon('shield', new vscode.ThemeColor('charts.red'));
             default:
                 return new vscode.ThemeIcon('question');
         }
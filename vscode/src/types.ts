@@ -10,12 +10,29 @@ export interface StatusSummary {
     by_source?: Partial<Record<SourceKind, number>>;
 }
 
+export interface UpstreamVersions {
+    latest_stable?: string;
+    latest_compatible?: string;
+    latest?: string;
+}
+
 export interface DependencyStatus {
     crate_name?: string; // For Rust
     package_name?: string; // For Node.js
     lock_version: string;
     docs_version?: string;
-    status: 'Synced' | 'SyncedFallback' | 'Outdated' | 'Missing' | 'Corrupted' | 'Incomplete' | 'ReadError';
+    status:
+        | 'Synced'
+        | 'SyncedFallback'
+        | 'UpdateAvailable'
+        | 'Outdated'
+        | 'Missing'
+        | 'Corrupted'
+        | 'Incomplete'
+        | 'ReadError'
+        | 'Yanked'
+        | 'Deprecated'
+        | 'Vulnerable';
     reason?: string;
     mode?: string;
     source_kind?: SourceKind;
@@ -24,6 +41,7 @@ export interface DependencyStatus {
     provenance_url?: string;
     is_fallback?: boolean;
     last_sync_at?: string;
+    upstream?: UpstreamVersions;
 }
 
 export interface StatusOutput {
@@ -37,7 +55,7 @@ export interface CheckStatus {
     crate_name?: string;
     lock_version?: string;
     docs_version?: string;
-    status: 'Synced' | 'SyncedFallback' | 'Outdated' | 'Missing' | 'Corrupted';
+    status: 'Synced' | 'SyncedFallback' | 'Outdated' | 'Missing' | 'Corrupted' | 'Yanked' | 'Deprecated' | 'Vulnerable';
     reason?: string;
     reason_code?: string;
     source_kind?: SourceKind;